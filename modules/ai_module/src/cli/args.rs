@@ -0,0 +1,167 @@
+use ai_module::inference::game_input::InputKind;
+use clap::{Args, Parser, Subcommand};
+use json::timestamp::observation_time::ObservationTime;
+use std::path::PathBuf;
+
+/// The PlayBase AI node: extracts observations from raw game inputs.
+#[derive(Parser, Debug)]
+#[command(name = "ai_module", version)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Extract the observation of one raw input and print its JSON.
+    Extract(ExtractArgs),
+    /// Print the model registry entry of the selected engine's model.
+    Model(ModelArgs),
+    /// Run a golden input set in deterministic mode and compare the observations with the expected ones.
+    SelfTest(SelfTestArgs),
+    /// Run the node: extract the observations of queued inputs and deliver them, signed, to a coordinator.
+    #[cfg(feature = "node")]
+    Node(NodeArgs),
+}
+
+#[cfg(feature = "node")]
+#[derive(Args, Debug)]
+pub struct NodeArgs {
+    /// The directory capture tools drop raw inputs into.
+    #[arg(long, env = "AI_QUEUE_DIR")]
+    pub queue: PathBuf,
+
+    /// The directory observations wait in until they are delivered.
+    #[arg(long, env = "AI_SPOOL_DIR")]
+    pub spool: PathBuf,
+
+    /// The coordinator's HTTP API, e.g. `http://127.0.0.1:8080`.
+    #[arg(long, env = "AI_COORDINATOR_URL", required_unless_present = "inbox", conflicts_with = "inbox")]
+    pub coordinator: Option<String>,
+
+    /// The coordinator's inbox directory, receiving signed envelopes instead.
+    #[arg(long, env = "AI_COORDINATOR_INBOX")]
+    pub inbox: Option<PathBuf>,
+
+    /// The node's signer, a JSON signer configuration: a `keystore`, a `mnemonic` or a `remote`
+    /// signing service. Its address is the observations' `uploader`.
+    #[arg(long, env = "AI_NODE_SIGNER")]
+    pub signer: PathBuf,
+
+    /// The node id written into the observations as `ainode`.
+    #[arg(long, env = "AI_NODE")]
+    pub node: String,
+
+    /// The game of inputs whose meta file names none.
+    #[arg(long, env = "AI_GAME")]
+    pub game: Option<String>,
+
+    /// What inputs are when their meta file does not say: frame, log or replay.
+    #[arg(long, default_value_t = InputKind::Frame)]
+    pub kind: InputKind,
+
+    /// The capture source of inputs whose meta file names none.
+    #[arg(long, default_value_t = 0)]
+    pub source: u64,
+
+    #[command(flatten)]
+    pub engine: EngineArgs,
+
+    /// Extract in deterministic mode.
+    #[arg(long, env = "AI_DETERMINISTIC")]
+    pub deterministic: bool,
+
+    /// Seconds between two polls of the queue and the spool.
+    #[arg(long, env = "AI_POLL_INTERVAL", default_value_t = 5)]
+    pub poll_interval: u64,
+
+    /// How many times delivering an observation is tried before it is given up.
+    #[arg(long, env = "AI_MAX_ATTEMPTS", default_value_t = 8)]
+    pub max_attempts: u32,
+
+    /// Seconds to wait after the first failed delivery, doubled after every further one.
+    #[arg(long, env = "AI_RETRY_BACKOFF", default_value_t = 2)]
+    pub retry_backoff: u64,
+}
+
+#[derive(Args, Debug)]
+pub struct SelfTestArgs {
+    /// The golden set manifest.
+    #[arg(env = "AI_GOLDEN_SET")]
+    pub golden_set: PathBuf,
+
+    #[command(flatten)]
+    pub engine: EngineArgs,
+}
+
+#[derive(Args, Debug)]
+pub struct ModelArgs {
+    #[command(flatten)]
+    pub engine: EngineArgs,
+
+    /// The model's name in the registry.
+    #[arg(long)]
+    pub id: String,
+
+    /// A game the model extracts observations for; repeat for several games.
+    #[arg(long = "game", required = true)]
+    pub games: Vec<String>,
+}
+
+/// Which inference engine extracts observations: an ONNX model, else a log grammar, or else the
+/// rule-based engine. Without any, only the games whose plugin has its own engine are read.
+#[derive(Args, Debug)]
+pub struct EngineArgs {
+    /// The ONNX model to run on the CPU.
+    #[arg(long, env = "AI_MODEL", requires = "labels")]
+    pub model: Option<PathBuf>,
+
+    /// The label map of the ONNX model.
+    #[arg(long, env = "AI_LABELS")]
+    pub labels: Option<PathBuf>,
+
+    /// The HUD regions an image model reads in each game's frames.
+    #[arg(long, env = "AI_HUD_LAYOUTS", requires = "model")]
+    pub hud_layouts: Option<PathBuf>,
+
+    /// The grammar parsing log inputs, used without a model.
+    #[arg(long, env = "AI_LOG_GRAMMAR")]
+    pub grammar: Option<PathBuf>,
+
+    /// The rules of the rule-based engine, used without a model or grammar.
+    #[arg(long, env = "AI_RULES")]
+    pub rules: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+pub struct ExtractArgs {
+    /// The raw input file.
+    pub input: PathBuf,
+
+    #[command(flatten)]
+    pub engine: EngineArgs,
+
+    /// The game the input was captured from.
+    #[arg(long)]
+    pub game: String,
+
+    /// What the input is: frame, log or replay.
+    #[arg(long, default_value_t = InputKind::Frame)]
+    pub kind: InputKind,
+
+    /// Which capture source of the node the input came from.
+    #[arg(long, default_value_t = 0)]
+    pub source: u64,
+
+    /// The node id written into the observation as `ainode`.
+    #[arg(long, env = "AI_NODE")]
+    pub node: String,
+
+    /// When the input was captured, e.g. `2024-08-12 16:35:35 UTC`; the extraction time when unset.
+    #[arg(long)]
+    pub captured_at: Option<ObservationTime>,
+
+    /// Extract in deterministic mode, which needs `--captured-at`.
+    #[arg(long, env = "AI_DETERMINISTIC", requires = "captured_at")]
+    pub deterministic: bool,
+}
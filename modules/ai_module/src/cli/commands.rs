@@ -0,0 +1,171 @@
+use ai_module::conformance::golden_set::{CaseOutcome, GoldenSet};
+use ai_module::inference::determinism::Determinism;
+use ai_module::inference::engine::{InferenceEngine, InferenceRunner};
+use ai_module::inference::game_input::GameInput;
+use ai_module::inference::log_grammar::LogGrammarEngine;
+use ai_module::inference::observation::NodeInfo;
+#[cfg(feature = "onnx")]
+use ai_module::inference::onnx_engine::OnnxEngine;
+use ai_module::inference::rule_based::RuleBasedEngine;
+#[cfg(feature = "node")]
+use ai_module::node::daemon::NodeDaemon;
+#[cfg(feature = "node")]
+use ai_module::node::delivery::Delivery;
+#[cfg(feature = "node")]
+use ai_module::node::input_queue::{InputMeta, InputQueue};
+#[cfg(feature = "node")]
+use ai_module::node::spool::{RetryPolicy, Spool};
+use ai_module::plugins::game_plugin::GamePlugins;
+#[cfg(feature = "onnx")]
+use ai_module::preprocessing::hud_layout::HudLayouts;
+use ai_module::registry::model_registry::RegisteredModel;
+#[cfg(feature = "node")]
+use ethers::signers::Signer;
+#[cfg(feature = "node")]
+use fingerprint::signer::signer_config::SignerConfig;
+use std::error::Error;
+use std::fs;
+#[cfg(feature = "node")]
+use std::time::Duration;
+
+#[cfg(feature = "node")]
+use crate::cli::args::NodeArgs;
+use crate::cli::args::{Cli, Command, EngineArgs, ExtractArgs, ModelArgs, SelfTestArgs};
+
+/// Runs a command.
+///
+/// # Parameters
+/// - `cli`: The parsed command line.
+///
+/// # Returns
+/// - `Result<(), Box<dyn Error>>`: An error if the command failed.
+pub fn run(cli: Cli) -> Result<(), Box<dyn Error>> {
+    match cli.command {
+        Command::Extract(args) => extract(args),
+        Command::Model(args) => model(args),
+        Command::SelfTest(args) => self_test(args),
+        #[cfg(feature = "node")]
+        Command::Node(args) => node(args),
+    }
+}
+
+/// Loads the engine the command line selects, if it selects one.
+fn engine(args: &EngineArgs) -> Result<Option<Box<dyn InferenceEngine>>, Box<dyn Error>> {
+    match (&args.model, &args.labels, &args.grammar, &args.rules) {
+        #[cfg(feature = "onnx")]
+        (Some(model), Some(labels), _, _) => {
+            let mut engine = OnnxEngine::load(model, labels)?;
+            if let Some(hud_layouts) = &args.hud_layouts {
+                engine = engine.with_hud_layouts(HudLayouts::load(hud_layouts)?);
+            }
+            Ok(Some(Box::new(engine)))
+        }
+        #[cfg(not(feature = "onnx"))]
+        (Some(_), _, _, _) => Err("this build has no ONNX support, rebuild with the `onnx` feature".into()),
+        #[cfg(feature = "onnx")]
+        (Some(_), None, _, _) => Err("--model needs --labels".into()),
+        (None, _, Some(grammar), _) => Ok(Some(Box::new(LogGrammarEngine::load(grammar)?))),
+        (None, _, None, Some(rules)) => Ok(Some(Box::new(RuleBasedEngine::load(rules)?))),
+        (None, _, None, None) => Ok(None),
+    }
+}
+
+/// A runner with the engine the command line selects and the builtin plugins. A node without an
+/// engine only reads the games whose plugin extracts them.
+fn runner(args: &EngineArgs, node: NodeInfo) -> Result<InferenceRunner, Box<dyn Error>> {
+    let runner = match engine(args)? {
+        Some(engine) => InferenceRunner::new(engine, node),
+        None => InferenceRunner::without_engine(node),
+    };
+    Ok(runner.with_plugins(GamePlugins::builtin()))
+}
+
+fn extract(args: ExtractArgs) -> Result<(), Box<dyn Error>> {
+    let mut runner = runner(&args.engine, NodeInfo::new(args.node))?;
+    if args.deterministic {
+        runner = runner.with_determinism(Determinism::default());
+    }
+    let bytes = fs::read(&args.input).map_err(|err| format!("cannot read {}: {}", args.input.display(), err))?;
+    let mut input = GameInput::new(args.game, args.kind, bytes).from_source(args.source);
+    if let Some(captured_at) = args.captured_at {
+        input = input.captured_at(captured_at);
+    }
+
+    let observation = runner.observe(&input)?;
+    println!("{}", serde_json::to_string_pretty(&observation)?);
+    Ok(())
+}
+
+fn model(args: ModelArgs) -> Result<(), Box<dyn Error>> {
+    let engine = engine(&args.engine)?.ok_or("select the engine to register with --model and --labels, --grammar or --rules")?;
+    let info = engine.model();
+    let model = RegisteredModel {
+        id: args.id,
+        hash: info.model.parse().map_err(|err| format!("model hash {}: {}", info.model, err))?,
+        version: info.version.parse().map_err(|err| format!("model version {}: {}", info.version, err))?,
+        games: args.games,
+        schema: Default::default(),
+        deprecated: false,
+    };
+    println!("{}", serde_json::to_string_pretty(&model)?);
+    Ok(())
+}
+
+fn self_test(args: SelfTestArgs) -> Result<(), Box<dyn Error>> {
+    let set = GoldenSet::load(&args.golden_set)?;
+    let report = set.run(&set.runner(engine(&args.engine)?, GamePlugins::builtin()));
+
+    for case in &report.cases {
+        match &case.outcome {
+            CaseOutcome::Passed => println!("ok      {}", case.name),
+            CaseOutcome::Differs(differences) => {
+                println!("differs {}", case.name);
+                for difference in differences {
+                    println!("        `{}`: expected {}, got {}", difference.field, difference.expected, difference.actual);
+                }
+            }
+            CaseOutcome::Failed(reason) => println!("failed  {}: {}", case.name, reason),
+        }
+    }
+    let passed = report.cases.iter().filter(|case| case.outcome == CaseOutcome::Passed).count();
+    println!("{} of {} cases passed", passed, report.cases.len());
+
+    if !report.passed() {
+        return Err("the node's setup is not conformant with the golden set".into());
+    }
+    Ok(())
+}
+
+#[cfg(feature = "node")]
+fn node(args: NodeArgs) -> Result<(), Box<dyn Error>> {
+    // Logs go to stderr, as the coordinator's do
+    let filter = tracing_subscriber::EnvFilter::try_from_default_env().unwrap_or_else(|_| tracing_subscriber::EnvFilter::new("info"));
+    tracing_subscriber::fmt().with_env_filter(filter).with_writer(std::io::stderr).init();
+
+    let runtime = tokio::runtime::Runtime::new()?;
+    let text = fs::read_to_string(&args.signer).map_err(|err| format!("cannot read the signer {}: {}", args.signer.display(), err))?;
+    let config: SignerConfig = serde_json::from_str(&text).map_err(|err| format!("invalid signer {}: {}", args.signer.display(), err))?;
+    if config == SignerConfig::PrivateKey {
+        // It reads the coordinator's transaction key from the environment, not a node's own
+        return Err("a node signs with a keystore, a mnemonic or a remote signer, not `private_key`".into());
+    }
+    // The node signs envelopes, never transactions, so the chain id is not used
+    let signer = runtime.block_on(config.load(1)).map_err(|err| format!("cannot load the signer {}: {}", args.signer.display(), err))?;
+    let mut runner = runner(&args.engine, NodeInfo::new(args.node).uploaded_by(signer.address()))?;
+    if args.deterministic {
+        runner = runner.with_determinism(Determinism::default());
+    }
+    let defaults = InputMeta { game: args.game, kind: Some(args.kind), source: Some(args.source), captured_at: None };
+    let queue = InputQueue::open(&args.queue, defaults).map_err(|err| format!("cannot open the queue {}: {}", args.queue.display(), err))?;
+    let spool = Spool::open(&args.spool).map_err(|err| format!("cannot open the spool {}: {}", args.spool.display(), err))?;
+    let delivery = match (args.coordinator, args.inbox) {
+        (Some(url), _) => Delivery::http(&url, Duration::from_secs(30))?,
+        (None, Some(inbox)) => Delivery::Inbox(inbox),
+        (None, None) => return Err("select a coordinator with --coordinator or --inbox".into()),
+    };
+    let retry = RetryPolicy { max_attempts: args.max_attempts, initial_backoff: Duration::from_secs(args.retry_backoff), ..RetryPolicy::default() };
+    let daemon = NodeDaemon::new(runner, queue, spool, delivery, signer)?.with_retry(retry);
+
+    runtime.block_on(daemon.run(Duration::from_secs(args.poll_interval)))?;
+    Ok(())
+}
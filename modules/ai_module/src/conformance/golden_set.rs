@@ -0,0 +1,225 @@
+use json::timestamp::observation_time::ObservationTime;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::inference::determinism::Determinism;
+use crate::inference::engine::{InferenceEngine, InferenceRunner};
+use crate::inference::game_input::{GameInput, InputKind};
+use crate::inference::observation::NodeInfo;
+use crate::plugins::game_plugin::GamePlugins;
+
+/// One raw input of a golden set and the observation every conformant node extracts from it.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct GoldenCase {
+    pub name: String,
+    /// The raw input, relative to the manifest.
+    pub input: PathBuf,
+    pub game: String,
+    pub kind: InputKind,
+    #[serde(default)]
+    pub source: u64,
+    pub captured_at: ObservationTime,
+    /// The expected observation JSON, relative to the manifest.
+    pub expected: PathBuf,
+}
+
+/// Raw inputs with the observations they must yield, to prove a node's setup is conformant.
+///
+/// ```json
+/// {
+///   "node": "golden",
+///   "determinism": { "seed": 0, "confidence_decimals": 4 },
+///   "cases": [
+///     { "name": "arena_match_01", "input": "../arena/match_01.log", "game": "arena", "kind": "log",
+///       "source": 1, "captured_at": "2024-08-12 16:35:35 UTC", "expected": "arena_match_01.json" }
+///   ]
+/// }
+/// ```
+///
+/// Cases run in deterministic mode as node `node`, so expected observations do not depend on the
+/// node running the set or on the time it runs.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct GoldenSet {
+    pub node: String,
+    #[serde(default)]
+    pub determinism: Determinism,
+    pub cases: Vec<GoldenCase>,
+    /// The directory of the manifest, which case paths are relative to.
+    #[serde(skip)]
+    pub root: PathBuf,
+}
+
+/// A field of an observation that differs from the expected one.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct FieldDifference {
+    pub field: String,
+    /// The expected value, `null` if the expected observation lacks the field.
+    pub expected: Value,
+    /// The extracted value, `null` if the observation lacks the field.
+    pub actual: Value,
+}
+
+/// How one case of a self-test went.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum CaseOutcome {
+    Passed,
+    /// The observation differs from the expected one in these fields.
+    Differs(Vec<FieldDifference>),
+    /// The case could not run: its files could not be read, or no observation was extracted.
+    Failed(String),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CaseReport {
+    pub name: String,
+    pub outcome: CaseOutcome,
+}
+
+/// The outcome of every case of a self-test.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SelfTestReport {
+    pub cases: Vec<CaseReport>,
+}
+
+impl SelfTestReport {
+    /// Whether every case passed.
+    pub fn passed(&self) -> bool {
+        self.cases.iter().all(|case| case.outcome == CaseOutcome::Passed)
+    }
+}
+
+impl GoldenSet {
+    /// Loads a golden set manifest.
+    ///
+    /// # Parameters
+    /// - `path`: The manifest; case paths are relative to its directory.
+    ///
+    /// # Returns
+    /// - `Result<GoldenSet, String>`: The golden set, or why it cannot be read.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path).map_err(|err| format!("cannot read {}: {}", path.display(), err))?;
+        let mut set: GoldenSet = serde_json::from_str(&text).map_err(|err| format!("invalid golden set {}: {}", path.display(), err))?;
+        set.root = path.parent().map(Path::to_path_buf).unwrap_or_default();
+        Ok(set)
+    }
+
+    /// A runner for the set: the engine under test, if the node has one, the plugins, the set's
+    /// node and deterministic mode.
+    pub fn runner(&self, engine: Option<Box<dyn InferenceEngine>>, plugins: GamePlugins) -> InferenceRunner {
+        let node = NodeInfo::new(self.node.clone());
+        let runner = match engine {
+            Some(engine) => InferenceRunner::new(engine, node),
+            None => InferenceRunner::without_engine(node),
+        };
+        runner.with_plugins(plugins).with_determinism(self.determinism)
+    }
+
+    /// Runs every case and compares its observation with the expected one.
+    ///
+    /// # Parameters
+    /// - `runner`: The runner under test, usually made by `runner`.
+    ///
+    /// # Returns
+    /// - `SelfTestReport`: The outcome of every case, in order.
+    pub fn run(&self, runner: &InferenceRunner) -> SelfTestReport {
+        let cases = self
+            .cases
+            .iter()
+            .map(|case| {
+                let outcome = match self.run_case(runner, case) {
+                    Ok(differences) if differences.is_empty() => CaseOutcome::Passed,
+                    Ok(differences) => CaseOutcome::Differs(differences),
+                    Err(reason) => CaseOutcome::Failed(reason),
+                };
+                CaseReport { name: case.name.clone(), outcome }
+            })
+            .collect();
+        SelfTestReport { cases }
+    }
+
+    fn run_case(&self, runner: &InferenceRunner, case: &GoldenCase) -> Result<Vec<FieldDifference>, String> {
+        let input_path = self.root.join(&case.input);
+        let bytes = fs::read(&input_path).map_err(|err| format!("cannot read {}: {}", input_path.display(), err))?;
+        let expected_path = self.root.join(&case.expected);
+        let expected = fs::read_to_string(&expected_path).map_err(|err| format!("cannot read {}: {}", expected_path.display(), err))?;
+        let expected: Value = serde_json::from_str(&expected).map_err(|err| format!("invalid JSON {}: {}", expected_path.display(), err))?;
+
+        let input = GameInput::new(case.game.clone(), case.kind, bytes).from_source(case.source).captured_at(case.captured_at);
+        let actual = runner.observe(&input).map_err(|err| err.to_string())?.to_json();
+        Ok(differences(&expected, &actual))
+    }
+}
+
+/// The top-level fields in which two observations differ, in the order of their names.
+fn differences(expected: &Value, actual: &Value) -> Vec<FieldDifference> {
+    let empty = serde_json::Map::new();
+    let expected = expected.as_object().unwrap_or(&empty);
+    let actual = actual.as_object().unwrap_or(&empty);
+
+    let mut fields: Vec<&String> = expected.keys().chain(actual.keys()).collect();
+    fields.sort();
+    fields.dedup();
+    fields
+        .into_iter()
+        .filter(|field| expected.get(*field) != actual.get(*field))
+        .map(|field| FieldDifference {
+            field: field.clone(),
+            expected: expected.get(field).cloned().unwrap_or(Value::Null),
+            actual: actual.get(field).cloned().unwrap_or(Value::Null),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::inference::rule_based::RuleBasedEngine;
+
+    fn golden_set(manifest: &str) -> GoldenSet {
+        GoldenSet::load(Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/golden").join(manifest)).unwrap()
+    }
+
+    fn sample_rules() -> Box<dyn InferenceEngine> {
+        Box::new(RuleBasedEngine::load(concat!(env!("CARGO_MANIFEST_DIR"), "/rules/sample_rules.json")).unwrap())
+    }
+
+    #[test]
+    fn test_shipped_golden_sets_pass() {
+        // The arena cases are extracted by the arena plugin, on a node without an engine
+        let set = golden_set("manifest.json");
+        let report = set.run(&set.runner(None, GamePlugins::builtin()));
+        assert_eq!(report.cases.len(), set.cases.len());
+        assert!(report.passed(), "{:?}", report);
+
+        let set = golden_set("sample_manifest.json");
+        let report = set.run(&set.runner(Some(sample_rules()), GamePlugins::builtin()));
+        assert_eq!(report.cases.len(), set.cases.len());
+        assert!(report.passed(), "{:?}", report);
+    }
+
+    #[test]
+    fn test_reports_the_fields_that_differ() {
+        let set = golden_set("sample_manifest.json");
+        // A node whose rules spell one value differently, so its model differs too
+        let mut rules = RuleBasedEngine::load(concat!(env!("CARGO_MANIFEST_DIR"), "/rules/sample_rules.json")).unwrap().rules().to_vec();
+        rules.iter_mut().filter(|rule| rule.value == "North").for_each(|rule| rule.value = "Nord".to_string());
+        let report = set.run(&set.runner(Some(Box::new(RuleBasedEngine::new(rules))), GamePlugins::builtin()));
+        assert!(!report.passed());
+
+        let differs = report.cases.iter().find_map(|case| match &case.outcome {
+            CaseOutcome::Differs(differences) => Some(differences),
+            _ => None,
+        });
+        let fields: Vec<&str> = differs.unwrap().iter().map(|difference| difference.field.as_str()).collect();
+        assert_eq!(fields, vec!["aimodel", "place2"]);
+
+        let mut missing = set.clone();
+        missing.cases[0].input = PathBuf::from("missing.log");
+        let report = missing.run(&missing.runner(Some(sample_rules()), GamePlugins::builtin()));
+        assert!(matches!(report.cases[0].outcome, CaseOutcome::Failed(_)));
+    }
+}
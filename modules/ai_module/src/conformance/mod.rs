@@ -0,0 +1 @@
+pub mod golden_set;
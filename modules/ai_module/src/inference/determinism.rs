@@ -0,0 +1,68 @@
+use serde::{Deserialize, Serialize};
+
+use crate::inference::observation::Extraction;
+
+/// Settings of the deterministic execution mode, in which every node extracts the same
+/// observation from the same input.
+///
+/// In this mode engines run on a single thread, so floating point sums are always reduced in the
+/// same order, and engines that sample draw from `seed`; none of the built-in engines sample.
+/// Inputs are encoded by the fixed preprocessing of the engine, inputs without a capture time are
+/// rejected instead of being stamped with the clock, and confidences are rounded so that the last
+/// bits of CPU-specific kernels do not show in observations.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Determinism {
+    /// The seed of any sampling an engine does.
+    pub seed: u64,
+    /// The number of decimals confidences are rounded to.
+    pub confidence_decimals: u32,
+}
+
+impl Default for Determinism {
+    fn default() -> Self {
+        Determinism { seed: 0, confidence_decimals: 4 }
+    }
+}
+
+impl Determinism {
+    /// Rounds a confidence to `confidence_decimals` decimals.
+    pub fn round(&self, confidence: f64) -> f64 {
+        let scale = 10f64.powi(self.confidence_decimals as i32);
+        (confidence * scale).round() / scale
+    }
+
+    /// Rounds every confidence of an extraction.
+    ///
+    /// # Parameters
+    /// - `extraction`: The extraction, with its confidences as the engine reported them.
+    ///
+    /// # Returns
+    /// - `Extraction`: The extraction with rounded confidences.
+    pub fn rounded(&self, mut extraction: Extraction) -> Extraction {
+        for confidence in extraction.confidence.values_mut() {
+            *confidence = self.round(*confidence);
+        }
+        extraction
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::inference::observation::{Confidences, ObservationField};
+
+    #[test]
+    fn test_rounds_confidences_to_the_set_decimals() {
+        let determinism = Determinism::default();
+        let extraction = Extraction {
+            confidence: Confidences::from([(ObservationField::Character, 0.731058578630005), (ObservationField::Place, 0.73105857863)]),
+            ..Extraction::default()
+        };
+
+        let rounded = determinism.rounded(extraction);
+
+        assert_eq!(rounded.confidence(ObservationField::Character), 0.7311);
+        assert_eq!(rounded.confidence(ObservationField::Character), rounded.confidence(ObservationField::Place));
+        assert_eq!(Determinism { confidence_decimals: 1, ..determinism }.round(0.66), 0.7);
+    }
+}
@@ -0,0 +1,261 @@
+use json::timestamp::observation_time::ObservationTime;
+use std::fmt;
+
+use crate::inference::determinism::Determinism;
+use crate::inference::game_input::{GameInput, InputKind};
+use crate::inference::observation::{Extraction, GameObservation, ModelInfo, NodeInfo, ObservationField};
+use crate::plugins::game_plugin::GamePlugins;
+
+/// Why an inference engine could not extract an observation from an input.
+#[derive(Debug, Clone, PartialEq)]
+pub enum InferenceError {
+    /// The engine does not handle this kind of input or game.
+    UnsupportedInput(String),
+    /// The engine found no value for a field.
+    MissingField(ObservationField),
+    /// A value the game's output schema does not allow.
+    OutsideSchema { field: ObservationField, value: String },
+    /// The model failed to load or run.
+    Model(String),
+}
+
+impl fmt::Display for InferenceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InferenceError::UnsupportedInput(reason) => write!(f, "unsupported input: {}", reason),
+            InferenceError::MissingField(field) => write!(f, "no value found for `{}`", field),
+            InferenceError::OutsideSchema { field, value } => write!(f, "`{}` cannot be `{}`", field, value),
+            InferenceError::Model(reason) => write!(f, "model error: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for InferenceError {}
+
+/// Extracts the fields of an observation from raw game inputs.
+///
+/// Implementations only extract the game facts; the metadata every observation carries, which
+/// model and node produced it, when and from which input, is filled in by `InferenceRunner`.
+pub trait InferenceEngine: Send + Sync {
+    /// The model the engine runs, identifying its observations.
+    fn model(&self) -> ModelInfo;
+
+    /// Whether the engine can extract observations from this kind of input.
+    fn supports(&self, kind: InputKind) -> bool;
+
+    /// Extracts the observation fields from one raw input.
+    ///
+    /// # Parameters
+    /// - `input`: The raw input.
+    ///
+    /// # Returns
+    /// - `Result<Extraction, InferenceError>`: The extracted fields, or why there are none.
+    fn extract(&self, input: &GameInput) -> Result<Extraction, InferenceError>;
+
+    /// Extracts the observation fields in deterministic mode, on a single thread and drawing any
+    /// randomness from the seed. The default extracts as `extract` does, for engines that are
+    /// deterministic already.
+    ///
+    /// # Parameters
+    /// - `input`: The raw input.
+    /// - `determinism`: The settings of the deterministic mode.
+    ///
+    /// # Returns
+    /// - `Result<Extraction, InferenceError>`: The extracted fields, or why there are none.
+    fn extract_deterministic(&self, input: &GameInput, _determinism: &Determinism) -> Result<Extraction, InferenceError> {
+        self.extract(input)
+    }
+}
+
+/// Turns raw game inputs into observations with an inference engine, on behalf of a node.
+///
+/// Inputs are extracted by the node's engine, the model the operator chose. A node without one
+/// extracts the inputs of a game with a plugin by the plugin's engine, if it has one. Inputs of a
+/// game with a plugin are normalized and checked against its schema whichever engine read them.
+pub struct InferenceRunner {
+    engine: Option<Box<dyn InferenceEngine>>,
+    plugins: GamePlugins,
+    node: NodeInfo,
+    determinism: Option<Determinism>,
+}
+
+impl InferenceRunner {
+    pub fn new(engine: Box<dyn InferenceEngine>, node: NodeInfo) -> Self {
+        InferenceRunner { engine: Some(engine), plugins: GamePlugins::new(), node, determinism: None }
+    }
+
+    /// A runner without an engine of its own, reading only the games whose plugin extracts them.
+    pub fn without_engine(node: NodeInfo) -> Self {
+        InferenceRunner { engine: None, plugins: GamePlugins::new(), node, determinism: None }
+    }
+
+    pub fn with_plugins(mut self, plugins: GamePlugins) -> Self {
+        self.plugins = plugins;
+        self
+    }
+
+    /// Runs every extraction in deterministic mode.
+    pub fn with_determinism(mut self, determinism: Determinism) -> Self {
+        self.determinism = Some(determinism);
+        self
+    }
+
+    /// The settings of the deterministic mode, if the runner is in it.
+    pub fn determinism(&self) -> Option<&Determinism> {
+        self.determinism.as_ref()
+    }
+
+    /// The node's own engine, if it has one.
+    pub fn engine(&self) -> Option<&dyn InferenceEngine> {
+        self.engine.as_deref()
+    }
+
+    pub fn node(&self) -> &NodeInfo {
+        &self.node
+    }
+
+    /// Extracts the observation of one raw input.
+    ///
+    /// # Parameters
+    /// - `input`: The raw input.
+    ///
+    /// # Returns
+    /// - `Result<GameObservation, InferenceError>`: The observation, with the model, node, time
+    ///   and input hash filled in, or why the engine could not extract it. In deterministic mode
+    ///   inputs without a capture time are unsupported.
+    pub fn observe(&self, input: &GameInput) -> Result<GameObservation, InferenceError> {
+        let plugin = self.plugins.get(&input.game);
+        let engine = self
+            .engine
+            .as_deref()
+            .or_else(|| plugin.and_then(|plugin| plugin.extractor()))
+            .ok_or_else(|| InferenceError::UnsupportedInput(format!("no engine extracts {} inputs", input.game)))?;
+        if !engine.supports(input.kind) {
+            return Err(InferenceError::UnsupportedInput(format!("the engine does not read {} inputs", input.kind)));
+        }
+        let (mut extraction, timestamp) = match &self.determinism {
+            Some(determinism) => {
+                let timestamp = input.captured_at.ok_or_else(|| {
+                    InferenceError::UnsupportedInput("deterministic mode needs the capture time of every input".to_string())
+                })?;
+                (determinism.rounded(engine.extract_deterministic(input, determinism)?), timestamp)
+            }
+            None => (engine.extract(input)?, input.captured_at.unwrap_or_else(ObservationTime::now)),
+        };
+        if let Some(plugin) = plugin {
+            extraction = plugin.normalized(extraction)?;
+        }
+        let model = engine.model();
+        let confidence = extraction.confidences();
+        let score = match &self.determinism {
+            Some(determinism) => determinism.round(extraction.score()),
+            None => extraction.score(),
+        };
+        let reference = input.reference();
+
+        Ok(GameObservation {
+            game: input.game.clone(),
+            character: extraction.character,
+            ability: extraction.ability,
+            place: extraction.place,
+            place2: extraction.place2,
+            aimodel: model.model,
+            aiversion: model.version,
+            ainode: self.node.node.clone(),
+            uploader: self.node.uploader,
+            timestamp,
+            source: input.source,
+            sourcetype: input.kind.source_type(),
+            hash_inputdata: reference.digest.as_bytes().to_vec(),
+            hash_algorithm: reference.algorithm,
+            confidence,
+            score,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::inference::observation::Confidences;
+    use json::input_data::input_reference::InputReference;
+
+    struct FixedEngine;
+
+    impl InferenceEngine for FixedEngine {
+        fn model(&self) -> ModelInfo {
+            ModelInfo { model: "fixed".to_string(), version: "1.0.0".to_string() }
+        }
+
+        fn supports(&self, kind: InputKind) -> bool {
+            kind == InputKind::Log
+        }
+
+        fn extract(&self, _input: &GameInput) -> Result<Extraction, InferenceError> {
+            Ok(Extraction {
+                character: "Mage".to_string(),
+                ability: "Fireball".to_string(),
+                place: "Bridge".to_string(),
+                place2: "North".to_string(),
+                confidence: Confidences::from([(ObservationField::Character, 0.5)]),
+            })
+        }
+    }
+
+    #[test]
+    fn test_runner_fills_in_the_observation_metadata() {
+        let runner = InferenceRunner::new(Box::new(FixedEngine), NodeInfo::new("node-7"));
+        let time = ObservationTime::parse("2024-08-12 16:35:35 UTC").unwrap();
+        let input = GameInput::new("arena", InputKind::Log, b"Mage casts Fireball".to_vec()).from_source(3).captured_at(time);
+
+        let observation = runner.observe(&input).unwrap().to_json();
+
+        assert_eq!(observation["game"], "arena");
+        assert_eq!(observation["character"], "Mage");
+        assert_eq!(observation["aimodel"], "fixed");
+        assert_eq!(observation["aiversion"], "1.0.0");
+        assert_eq!(observation["ainode"], "node-7");
+        assert_eq!(observation["uploader"], "0x0000000000000000000000000000000000000000");
+        assert_eq!(observation["timestamp"], "2024-08-12 16:35:35 UTC");
+        assert_eq!(observation["source"], 3);
+        assert_eq!(observation["sourcetype"], 1);
+        assert_eq!(InputReference::from_observation(&observation).unwrap(), input.reference());
+        assert_eq!(ObservationTime::from_observation(&observation).unwrap(), time);
+        // Fields without a reported confidence are certain
+        assert_eq!(observation["confidence"]["character"], 0.5);
+        assert_eq!(observation["confidence"]["place2"], 1.0);
+        assert_eq!(observation["score"], 0.875);
+
+        let frame = GameInput::new("arena", InputKind::Frame, Vec::new());
+        assert!(matches!(runner.observe(&frame), Err(InferenceError::UnsupportedInput(_))));
+    }
+
+    #[test]
+    fn test_the_node_engine_takes_precedence_over_a_plugin_extractor() {
+        let input = GameInput::new("arena", InputKind::Log, include_bytes!("../../fixtures/arena/match_01.log").to_vec());
+        let plugins = GamePlugins::builtin();
+        let extractor = plugins.get("arena").and_then(|plugin| plugin.extractor()).unwrap().model();
+        let runner = InferenceRunner::new(Box::new(FixedEngine), NodeInfo::new("node-7")).with_plugins(GamePlugins::builtin());
+        let observation = runner.observe(&input).unwrap();
+        assert_eq!(observation.aimodel, "fixed");
+        assert_eq!(observation.place2, "North");
+
+        // Without an engine, only games whose plugin extracts them are read
+        let runner = InferenceRunner::without_engine(NodeInfo::new("node-7")).with_plugins(plugins);
+        assert_eq!(runner.observe(&input).unwrap().aimodel, extractor.model);
+        let other = GameInput::new("sample", InputKind::Log, b"Mage casts Fireball".to_vec());
+        assert!(matches!(runner.observe(&other), Err(InferenceError::UnsupportedInput(_))));
+    }
+
+    #[test]
+    fn test_deterministic_mode_needs_the_capture_time() {
+        let runner = InferenceRunner::new(Box::new(FixedEngine), NodeInfo::new("node-7")).with_determinism(Determinism::default());
+        let input = GameInput::new("arena", InputKind::Log, b"Mage casts Fireball".to_vec());
+        assert!(matches!(runner.observe(&input), Err(InferenceError::UnsupportedInput(_))));
+
+        let time = ObservationTime::parse("2024-08-12 16:35:35 UTC").unwrap();
+        let first = runner.observe(&input.clone().captured_at(time)).unwrap();
+        let second = runner.observe(&input.captured_at(time)).unwrap();
+        assert_eq!(first, second);
+    }
+}
@@ -0,0 +1,100 @@
+use json::input_data::input_reference::{InputHashAlgorithm, InputReference};
+use json::timestamp::observation_time::ObservationTime;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// What a raw game input is, written into observations as `sourcetype`.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum InputKind {
+    /// A captured video frame or screenshot.
+    #[default]
+    Frame,
+    /// A chunk of a game log, such as a combat log or chat transcript.
+    Log,
+    /// A chunk of a replay file.
+    Replay,
+}
+
+impl InputKind {
+    pub const ALL: [InputKind; 3] = [InputKind::Frame, InputKind::Log, InputKind::Replay];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            InputKind::Frame => "frame",
+            InputKind::Log => "log",
+            InputKind::Replay => "replay",
+        }
+    }
+
+    /// The `sourcetype` of observations extracted from this kind of input.
+    pub fn source_type(&self) -> u8 {
+        match self {
+            InputKind::Frame => 0,
+            InputKind::Log => 1,
+            InputKind::Replay => 2,
+        }
+    }
+}
+
+impl fmt::Display for InputKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for InputKind {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        InputKind::ALL
+            .into_iter()
+            .find(|kind| kind.name() == name)
+            .ok_or_else(|| format!("unsupported input kind `{}`", name))
+    }
+}
+
+/// A raw game input an AI node extracts observations from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GameInput {
+    /// The game the input was captured from, written into observations as `game`.
+    pub game: String,
+    pub kind: InputKind,
+    /// Which capture source of the node the input came from, written as `source`.
+    pub source: u64,
+    /// When the input was captured. Observations of inputs without a capture time are stamped
+    /// with the time they were extracted.
+    pub captured_at: Option<ObservationTime>,
+    pub bytes: Vec<u8>,
+}
+
+impl GameInput {
+    /// A raw input from the node's first capture source, without a capture time.
+    ///
+    /// # Parameters
+    /// - `game`: The game the input was captured from.
+    /// - `kind`: What the input is.
+    /// - `bytes`: The raw input, exactly as captured.
+    ///
+    /// # Returns
+    /// - `GameInput`: The input.
+    pub fn new(game: impl Into<String>, kind: InputKind, bytes: Vec<u8>) -> Self {
+        GameInput { game: game.into(), kind, source: 0, captured_at: None, bytes }
+    }
+
+    pub fn from_source(mut self, source: u64) -> Self {
+        self.source = source;
+        self
+    }
+
+    pub fn captured_at(mut self, time: ObservationTime) -> Self {
+        self.captured_at = Some(time);
+        self
+    }
+
+    /// The reference observations of this input declare in `hash_inputdata`.
+    pub fn reference(&self) -> InputReference {
+        InputReference::of(&self.bytes, InputHashAlgorithm::Keccak256)
+    }
+}
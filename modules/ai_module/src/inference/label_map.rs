@@ -0,0 +1,192 @@
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+use crate::inference::engine::InferenceError;
+use crate::inference::observation::ObservationField;
+use crate::preprocessing::frame::ImageSpec;
+
+/// How a raw input is turned into the model's input tensor.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum InputEncoding {
+    /// Every byte scaled to `[0, 1]`, truncated or padded with zeros to the input shape.
+    #[default]
+    Bytes,
+    /// The raw input decoded as a PNG or JPEG frame, with the game's HUD regions preprocessed as
+    /// `image` declares; the input shape must hold the values of every region.
+    Image,
+}
+
+/// The model's input tensor, a single `f32` tensor.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct InputSpec {
+    pub shape: Vec<usize>,
+    #[serde(default)]
+    pub encoding: InputEncoding,
+    /// How frames are preprocessed, for the `image` encoding.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image: Option<ImageSpec>,
+}
+
+impl InputSpec {
+    /// How many values the input tensor holds.
+    pub fn len(&self) -> usize {
+        self.shape.iter().product()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// What the scores of a model output are, which decides how the confidence of a label is read.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputScores {
+    /// Unnormalized scores; the confidence of a label is its softmax probability.
+    #[default]
+    Logits,
+    /// Probabilities; the confidence of a label is its score, clamped to `[0, 1]`.
+    Probabilities,
+}
+
+impl OutputScores {
+    /// The confidence of the label at `index`, from 0 to 1.
+    ///
+    /// # Parameters
+    /// - `scores`: Every score of the output.
+    /// - `index`: The position of the label.
+    pub fn confidence(&self, scores: &[f32], index: usize) -> f64 {
+        let Some(score) = scores.get(index) else {
+            return 0.0;
+        };
+        match self {
+            OutputScores::Logits => {
+                // Shifted by the highest score so the exponentials cannot overflow
+                let max = scores.iter().copied().fold(f32::NEG_INFINITY, f32::max) as f64;
+                let total: f64 = scores.iter().map(|score| (*score as f64 - max).exp()).sum();
+                (*score as f64 - max).exp() / total
+            }
+            OutputScores::Probabilities => (*score as f64).clamp(0.0, 1.0),
+        }
+    }
+}
+
+/// Which model output holds the scores of a field, and the label of each score.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct FieldLabels {
+    pub field: ObservationField,
+    /// The index of the output among the model's outputs.
+    pub output: usize,
+    /// The field value of each score, in the output's order.
+    pub labels: Vec<String>,
+    #[serde(default)]
+    pub scores: OutputScores,
+}
+
+/// Declares how a model is run and how its outputs map to observation fields.
+///
+/// ```json
+/// {
+///   "version": "1.2.0",
+///   "input": { "shape": [1, 64] },
+///   "fields": [
+///     { "field": "character", "output": 0, "labels": ["Mage", "Knight"] }
+///   ]
+/// }
+/// ```
+///
+/// Each field takes the label of the highest score in its output. Outputs hold logits unless
+/// their field sets `"scores": "probabilities"`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct LabelMap {
+    /// The model's semantic version, written into observations as `aiversion`.
+    pub version: Version,
+    pub input: InputSpec,
+    pub fields: Vec<FieldLabels>,
+}
+
+impl LabelMap {
+    /// Loads and checks a label map.
+    ///
+    /// # Parameters
+    /// - `path`: The label map JSON file.
+    ///
+    /// # Returns
+    /// - `Result<LabelMap, InferenceError>`: The label map, or why it cannot be used.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, InferenceError> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path).map_err(|err| InferenceError::Model(format!("cannot read {}: {}", path.display(), err)))?;
+        let labels: LabelMap =
+            serde_json::from_str(&text).map_err(|err| InferenceError::Model(format!("invalid label map {}: {}", path.display(), err)))?;
+        labels.check()?;
+        Ok(labels)
+    }
+
+    /// Checks that every field is mapped once and has labels, that the input is not empty and
+    /// that the `image` encoding declares how frames are preprocessed.
+    pub fn check(&self) -> Result<(), InferenceError> {
+        if self.input.is_empty() {
+            return Err(InferenceError::Model("the input shape is empty".to_string()));
+        }
+        match (self.input.encoding, &self.input.image) {
+            (InputEncoding::Image, Some(image)) => image.check()?,
+            (InputEncoding::Image, None) => return Err(InferenceError::Model("the image encoding needs an `image` spec".to_string())),
+            _ => {}
+        }
+        let mut mapped = HashSet::new();
+        for labels in &self.fields {
+            if !mapped.insert(labels.field) {
+                return Err(InferenceError::Model(format!("`{}` is mapped more than once", labels.field)));
+            }
+            if labels.labels.is_empty() {
+                return Err(InferenceError::Model(format!("`{}` has no labels", labels.field)));
+            }
+        }
+        Ok(())
+    }
+
+    /// The labels of a field, if the model extracts it.
+    pub fn field(&self, field: ObservationField) -> Option<&FieldLabels> {
+        self.fields.iter().find(|labels| labels.field == field)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_rejects_duplicate_or_unlabeled_fields() {
+        let mut labels: LabelMap = serde_json::from_str(
+            r#"{
+                "version": "1.2.0",
+                "input": { "shape": [1, 4] },
+                "fields": [{ "field": "character", "output": 0, "labels": ["Mage", "Knight"] }]
+            }"#,
+        )
+        .unwrap();
+        assert!(labels.check().is_ok());
+        assert_eq!(labels.input.encoding, InputEncoding::Bytes);
+        assert_eq!(labels.version.to_string(), "1.2.0");
+
+        labels.fields.push(FieldLabels { field: ObservationField::Character, output: 1, labels: vec!["Mage".to_string()], scores: Default::default() });
+        assert!(labels.check().is_err());
+        labels.fields[1] = FieldLabels { field: ObservationField::Ability, output: 1, labels: Vec::new(), scores: Default::default() };
+        assert!(labels.check().is_err());
+
+        assert!(serde_json::from_str::<LabelMap>(r#"{ "version": "1.2", "input": { "shape": [4] }, "fields": [] }"#).is_err());
+    }
+
+    #[test]
+    fn test_confidence_of_logits_and_probabilities() {
+        let logits = OutputScores::Logits;
+        assert!((logits.confidence(&[2.0, 2.0], 0) - 0.5).abs() < 1e-9);
+        assert!((logits.confidence(&[1000.0, 0.0], 0) - 1.0).abs() < 1e-9);
+        assert_eq!(OutputScores::Probabilities.confidence(&[0.2, 1.3], 1), 1.0);
+        assert_eq!(OutputScores::Probabilities.confidence(&[0.2], 3), 0.0);
+    }
+}
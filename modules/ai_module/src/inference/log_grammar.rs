@@ -0,0 +1,215 @@
+use json::envelope::observation_envelope::canonical_json;
+use json::input_data::input_reference::InputHashAlgorithm;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::inference::engine::{InferenceEngine, InferenceError};
+use crate::inference::game_input::{GameInput, InputKind};
+use crate::inference::observation::{Extraction, ModelInfo, ObservationField};
+
+/// The named group of the `line` pattern holding the text rules are matched against.
+pub const MESSAGE_GROUP: &str = "message";
+
+/// A rule of a log grammar, matched against the message of every log line.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct LogRule {
+    /// A regex whose named groups `character`, `ability`, `place` and `place2` capture field values.
+    pub pattern: String,
+    /// Values set when a line matches, for facts a line states without spelling them.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub values: BTreeMap<ObservationField, String>,
+}
+
+/// Rules parsing a game's combat log or chat transcript into observation fields.
+///
+/// ```json
+/// {
+///   "line": "^\\[\\d{2}:\\d{2}:\\d{2}\\] (?P<message>.*)$",
+///   "rules": [
+///     { "pattern": "^lobby: player \\w+ picked (?P<character>.+)$" },
+///     { "pattern": "heading to the northern keep", "values": { "place": "Keep", "place2": "North" } }
+///   ]
+/// }
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct LogGrammar {
+    /// A regex every line must match to be read, whose `message` group, if any, is the text the
+    /// rules are matched against. Without it, every line is read as a whole.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub line: Option<String>,
+    pub rules: Vec<LogRule>,
+}
+
+/// Extracts observations from game logs with a `LogGrammar`.
+///
+/// Lines are read in order and every rule is matched against each of them; a field takes the
+/// first value a rule captures or sets for it, trimmed. Each input is one event of the log, so
+/// nodes split logs into a chunk per event before extracting them. Like the `RuleBasedEngine`,
+/// its `aimodel` is the keccak256 hash of the grammar's canonical JSON.
+#[derive(Debug, Clone)]
+pub struct LogGrammarEngine {
+    grammar: LogGrammar,
+    line: Option<Regex>,
+    rules: Vec<Regex>,
+}
+
+/// Compiles a pattern, checking its named groups are observation fields or one of `allowed`.
+fn compile(pattern: &str, allowed: &[&str]) -> Result<Regex, InferenceError> {
+    let regex = Regex::new(pattern).map_err(|err| InferenceError::Model(format!("invalid pattern `{}`: {}", pattern, err)))?;
+    if let Some(name) = regex.capture_names().flatten().find(|name| !allowed.contains(name) && ObservationField::from_str(name).is_err()) {
+        return Err(InferenceError::Model(format!("pattern `{}` captures `{}`, which is not an observation field", pattern, name)));
+    }
+    Ok(regex)
+}
+
+impl LogGrammarEngine {
+    /// Compiles a grammar.
+    ///
+    /// # Parameters
+    /// - `grammar`: The grammar.
+    ///
+    /// # Returns
+    /// - `Result<LogGrammarEngine, InferenceError>`: The engine, or the first pattern that is not a
+    ///   valid regex, captures a group that is not a field, or sets no field.
+    pub fn new(grammar: LogGrammar) -> Result<Self, InferenceError> {
+        let line = grammar.line.as_deref().map(|pattern| compile(pattern, &[MESSAGE_GROUP])).transpose()?;
+        let rules = grammar
+            .rules
+            .iter()
+            .map(|rule| {
+                let regex = compile(&rule.pattern, &[])?;
+                if regex.capture_names().flatten().next().is_none() && rule.values.is_empty() {
+                    return Err(InferenceError::Model(format!("pattern `{}` sets no field", rule.pattern)));
+                }
+                Ok(regex)
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(LogGrammarEngine { grammar, line, rules })
+    }
+
+    /// Loads and compiles a grammar file.
+    ///
+    /// # Parameters
+    /// - `path`: The grammar file.
+    ///
+    /// # Returns
+    /// - `Result<LogGrammarEngine, InferenceError>`: The engine, or why the grammar cannot be used.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, InferenceError> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path).map_err(|err| InferenceError::Model(format!("cannot read {}: {}", path.display(), err)))?;
+        let grammar = serde_json::from_str(&text).map_err(|err| InferenceError::Model(format!("invalid grammar in {}: {}", path.display(), err)))?;
+        LogGrammarEngine::new(grammar)
+    }
+
+    pub fn grammar(&self) -> &LogGrammar {
+        &self.grammar
+    }
+
+    /// The text of a line the rules are matched against, or `None` if the line is not read.
+    fn message<'a>(&self, line: &'a str) -> Option<&'a str> {
+        match &self.line {
+            Some(pattern) => {
+                let captures = pattern.captures(line)?;
+                Some(captures.name(MESSAGE_GROUP).map_or(line, |message| message.as_str()))
+            }
+            None => Some(line),
+        }
+    }
+}
+
+impl InferenceEngine for LogGrammarEngine {
+    fn model(&self) -> ModelInfo {
+        let grammar = serde_json::to_value(&self.grammar).unwrap_or_default();
+        let hash = InputHashAlgorithm::Keccak256.digest(canonical_json(&grammar).as_bytes());
+        ModelInfo { model: format!("{:?}", hash), version: env!("CARGO_PKG_VERSION").to_string() }
+    }
+
+    fn supports(&self, kind: InputKind) -> bool {
+        kind == InputKind::Log
+    }
+
+    fn extract(&self, input: &GameInput) -> Result<Extraction, InferenceError> {
+        let text = String::from_utf8_lossy(&input.bytes);
+        let mut values: BTreeMap<ObservationField, String> = BTreeMap::new();
+
+        for message in text.lines().filter_map(|line| self.message(line)) {
+            for (rule, regex) in self.grammar.rules.iter().zip(&self.rules) {
+                let Some(captures) = regex.captures(message) else {
+                    continue;
+                };
+                for field in ObservationField::ALL {
+                    let value = captures.name(field.name()).map(|value| value.as_str()).or(rule.values.get(&field).map(String::as_str));
+                    if let Some(value) = value.map(str::trim).filter(|value| !value.is_empty()) {
+                        values.entry(field).or_insert_with(|| value.to_string());
+                    }
+                }
+            }
+        }
+
+        let mut extraction = Extraction::default();
+        for field in ObservationField::ALL {
+            extraction.set(field, values.remove(&field).ok_or(InferenceError::MissingField(field))?);
+        }
+        Ok(extraction)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugins::arena::{ArenaPlugin, ARENA_GAME};
+    use crate::plugins::game_plugin::GamePlugin;
+
+    fn arena_grammar() -> LogGrammarEngine {
+        LogGrammarEngine::load(concat!(env!("CARGO_MANIFEST_DIR"), "/rules/arena_grammar.json")).unwrap()
+    }
+
+    #[test]
+    fn test_parses_recorded_match_logs() {
+        let engine = arena_grammar();
+        let log = GameInput::new(ARENA_GAME, InputKind::Log, include_bytes!("../../fixtures/arena/match_02.log").to_vec());
+
+        let extraction = engine.extract(&log).unwrap();
+
+        assert_eq!((extraction.character.as_str(), extraction.ability.as_str()), ("Magus", "fire ball"));
+        assert_eq!((extraction.place.as_str(), extraction.place2.as_str()), ("KEEP", "south"));
+        let normalized = ArenaPlugin::new().normalized(extraction).unwrap();
+        assert_eq!((normalized.character.as_str(), normalized.ability.as_str()), ("Mage", "Fireball"));
+
+        // A chat transcript names the place without spelling the fields out
+        let chat = LogGrammarEngine::new(LogGrammar {
+            line: None,
+            rules: vec![
+                LogRule { pattern: r"^(?P<character>\w+) uses (?P<ability>.+)$".to_string(), values: BTreeMap::new() },
+                LogRule {
+                    pattern: r"(?i)northern keep".to_string(),
+                    values: BTreeMap::from([(ObservationField::Place, "Keep".to_string()), (ObservationField::Place2, "North".to_string())]),
+                },
+            ],
+        })
+        .unwrap();
+        let transcript = GameInput::new("chat", InputKind::Log, b"Knight uses Shield Bash\r\nheading to the Northern Keep\r\n".to_vec());
+        let extraction = chat.extract(&transcript).unwrap();
+        assert_eq!((extraction.character.as_str(), extraction.ability.as_str()), ("Knight", "Shield Bash"));
+        assert_eq!((extraction.place.as_str(), extraction.place2.as_str()), ("Keep", "North"));
+
+        let partial = GameInput::new("chat", InputKind::Log, b"Knight uses Shield Bash".to_vec());
+        assert_eq!(chat.extract(&partial), Err(InferenceError::MissingField(ObservationField::Place)));
+    }
+
+    #[test]
+    fn test_rejects_patterns_that_cannot_set_fields() {
+        let grammar = |pattern: &str| LogGrammar { line: None, rules: vec![LogRule { pattern: pattern.to_string(), values: BTreeMap::new() }] };
+
+        assert!(LogGrammarEngine::new(grammar("(?P<character>\\w+")).is_err());
+        assert!(LogGrammarEngine::new(grammar("(?P<weapon>\\w+)")).is_err());
+        assert!(LogGrammarEngine::new(grammar("casts")).is_err());
+        assert!(LogGrammarEngine::new(grammar("(?P<place2>\\w+)")).is_ok());
+        assert!(!arena_grammar().supports(InputKind::Frame));
+        assert_ne!(arena_grammar().model(), LogGrammarEngine::new(grammar("(?P<place2>\\w+)")).unwrap().model());
+    }
+}
@@ -0,0 +1,9 @@
+pub mod determinism;
+pub mod engine;
+pub mod game_input;
+pub mod label_map;
+pub mod log_grammar;
+pub mod observation;
+#[cfg(feature = "onnx")]
+pub mod onnx_engine;
+pub mod rule_based;
@@ -0,0 +1,160 @@
+use ethers::types::Address;
+use json::input_data::input_reference::InputHashAlgorithm;
+use json::timestamp::observation_time::ObservationTime;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+/// The fields an inference engine extracts from a raw input, the ones fingerprints are computed from.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum ObservationField {
+    Character,
+    Ability,
+    Place,
+    Place2,
+}
+
+impl ObservationField {
+    pub const ALL: [ObservationField; 4] =
+        [ObservationField::Character, ObservationField::Ability, ObservationField::Place, ObservationField::Place2];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ObservationField::Character => "character",
+            ObservationField::Ability => "ability",
+            ObservationField::Place => "place",
+            ObservationField::Place2 => "place2",
+        }
+    }
+}
+
+impl fmt::Display for ObservationField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for ObservationField {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        ObservationField::ALL
+            .into_iter()
+            .find(|field| field.name() == name)
+            .ok_or_else(|| format!("unknown observation field `{}`", name))
+    }
+}
+
+/// The values a model or game allows for each field; fields missing here can take any value.
+pub type OutputSchema = BTreeMap<ObservationField, Vec<String>>;
+
+/// How sure an engine is of each extracted value, from 0 to 1.
+pub type Confidences = BTreeMap<ObservationField, f64>;
+
+/// What an inference engine extracted from one raw input.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct Extraction {
+    pub character: String,
+    pub ability: String,
+    pub place: String,
+    pub place2: String,
+    /// The confidence of each value; values without one are certain, as rules extract them.
+    #[serde(default)]
+    pub confidence: Confidences,
+}
+
+impl Extraction {
+    pub fn get(&self, field: ObservationField) -> &str {
+        match field {
+            ObservationField::Character => &self.character,
+            ObservationField::Ability => &self.ability,
+            ObservationField::Place => &self.place,
+            ObservationField::Place2 => &self.place2,
+        }
+    }
+
+    pub fn set(&mut self, field: ObservationField, value: impl Into<String>) {
+        let value = value.into();
+        match field {
+            ObservationField::Character => self.character = value,
+            ObservationField::Ability => self.ability = value,
+            ObservationField::Place => self.place = value,
+            ObservationField::Place2 => self.place2 = value,
+        }
+    }
+
+    /// The confidence of a field's value, 1 if the engine did not report one.
+    pub fn confidence(&self, field: ObservationField) -> f64 {
+        self.confidence.get(&field).copied().unwrap_or(1.0).clamp(0.0, 1.0)
+    }
+
+    /// The confidence of every field.
+    pub fn confidences(&self) -> Confidences {
+        ObservationField::ALL.into_iter().map(|field| (field, self.confidence(field))).collect()
+    }
+
+    /// The overall confidence of the extraction, the mean of the field confidences.
+    pub fn score(&self) -> f64 {
+        ObservationField::ALL.iter().map(|field| self.confidence(*field)).sum::<f64>() / ObservationField::ALL.len() as f64
+    }
+}
+
+/// The model behind an inference engine, written into observations as `aimodel` and `aiversion`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ModelInfo {
+    pub model: String,
+    pub version: String,
+}
+
+/// The AI node running inference, written into observations as `ainode` and `uploader`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct NodeInfo {
+    pub node: String,
+    /// The address that signs the node's observations, the zero address when they are unsigned.
+    pub uploader: Address,
+}
+
+impl NodeInfo {
+    /// A node whose observations are not signed.
+    pub fn new(node: impl Into<String>) -> Self {
+        NodeInfo { node: node.into(), uploader: Address::zero() }
+    }
+
+    pub fn uploaded_by(mut self, uploader: Address) -> Self {
+        self.uploader = uploader;
+        self
+    }
+}
+
+/// An observation as the coordinator loads it.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct GameObservation {
+    pub game: String,
+    pub character: String,
+    pub ability: String,
+    pub place: String,
+    pub place2: String,
+    pub aimodel: String,
+    pub aiversion: String,
+    pub ainode: String,
+    pub uploader: Address,
+    pub timestamp: ObservationTime,
+    pub source: u64,
+    pub sourcetype: u8,
+    /// The hash of the raw input the observation was extracted from, as an array of bytes.
+    pub hash_inputdata: Vec<u8>,
+    pub hash_algorithm: InputHashAlgorithm,
+    /// How sure the model was of each extracted value, from 0 to 1.
+    pub confidence: Confidences,
+    /// The overall confidence, the mean of `confidence`.
+    pub score: f64,
+}
+
+impl GameObservation {
+    /// The observation as the JSON object the coordinator loads.
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::to_value(self).unwrap_or_default()
+    }
+}
@@ -0,0 +1,226 @@
+use ethers::types::H256;
+use json::input_data::input_reference::InputHashAlgorithm;
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+use tract_onnx::prelude::*;
+
+use crate::inference::determinism::Determinism;
+use crate::inference::engine::{InferenceEngine, InferenceError};
+use crate::inference::game_input::{GameInput, InputKind};
+use crate::inference::label_map::{InputEncoding, LabelMap};
+use crate::inference::observation::{Extraction, ModelInfo, ObservationField};
+use crate::preprocessing::hud_layout::HudLayouts;
+
+/// Runs an ONNX model on the CPU.
+///
+/// The model takes one `f32` tensor, built from the raw input as its label map declares, and
+/// scores the labels of each field in one of its outputs. Observations name the model by the
+/// keccak256 hash of the model file, so nodes only agree on `aimodel` when they run the exact
+/// same weights, and by the semantic version in the label map.
+///
+/// Models with the `image` encoding read frames; they read the HUD regions of each game's
+/// layout, or the whole frame of games without one.
+pub struct OnnxEngine {
+    plan: Arc<TypedRunnableModel>,
+    labels: LabelMap,
+    hash: H256,
+    hud: HudLayouts,
+}
+
+fn model_error(err: impl std::fmt::Display) -> InferenceError {
+    InferenceError::Model(err.to_string())
+}
+
+impl OnnxEngine {
+    /// Loads a model file and its label map.
+    ///
+    /// # Parameters
+    /// - `model`: The ONNX model file.
+    /// - `labels`: The label map JSON file.
+    ///
+    /// # Returns
+    /// - `Result<OnnxEngine, InferenceError>`: The engine, or why the model cannot be run.
+    pub fn load(model: impl AsRef<Path>, labels: impl AsRef<Path>) -> Result<Self, InferenceError> {
+        let model = model.as_ref();
+        let bytes = fs::read(model).map_err(|err| InferenceError::Model(format!("cannot read {}: {}", model.display(), err)))?;
+        OnnxEngine::from_bytes(&bytes, LabelMap::load(labels)?)
+    }
+
+    /// Prepares a model for running.
+    ///
+    /// # Parameters
+    /// - `model`: The ONNX model file's bytes.
+    /// - `labels`: How the model's input is built and its outputs are read.
+    ///
+    /// # Returns
+    /// - `Result<OnnxEngine, InferenceError>`: The engine, or why the model cannot be run.
+    pub fn from_bytes(model: &[u8], labels: LabelMap) -> Result<Self, InferenceError> {
+        labels.check()?;
+        let plan = tract_onnx::onnx()
+            .model_for_read(&mut &model[..])
+            .and_then(|graph| graph.with_input_fact(0, f32::fact(&labels.input.shape).into()))
+            .and_then(|graph| graph.into_optimized())
+            .and_then(|graph| graph.into_runnable())
+            .map_err(|err| InferenceError::Model(format!("cannot load the ONNX model: {:#}", err)))?;
+        if let Some(labels) = labels.fields.iter().find(|labels| labels.output >= plan.model().outputs.len()) {
+            return Err(InferenceError::Model(format!(
+                "`{}` reads output {}, the model has {}",
+                labels.field,
+                labels.output,
+                plan.model().outputs.len()
+            )));
+        }
+        Ok(OnnxEngine { plan, labels, hash: InputHashAlgorithm::Keccak256.digest(model), hud: HudLayouts::default() })
+    }
+
+    /// Reads frames through the HUD regions of each game's layout.
+    pub fn with_hud_layouts(mut self, hud: HudLayouts) -> Self {
+        self.hud = hud;
+        self
+    }
+
+    pub fn labels(&self) -> &LabelMap {
+        &self.labels
+    }
+
+    /// Builds the model's input tensor from a raw input.
+    fn input_tensor(&self, input: &GameInput) -> Result<Tensor, InferenceError> {
+        let spec = &self.labels.input;
+        let values: Vec<f32> = match spec.encoding {
+            InputEncoding::Bytes => {
+                let mut values: Vec<f32> = input.bytes.iter().take(spec.len()).map(|byte| *byte as f32 / 255.0).collect();
+                values.resize(spec.len(), 0.0);
+                values
+            }
+            InputEncoding::Image => {
+                let image = spec.image.as_ref().ok_or_else(|| InferenceError::Model("the image encoding needs an `image` spec".to_string()))?;
+                let values = image.preprocess(&input.bytes, self.hud.regions(&input.game))?;
+                if values.len() != spec.len() {
+                    return Err(InferenceError::UnsupportedInput(format!(
+                        "{} frames preprocess to {} values, the model reads {}",
+                        input.game,
+                        values.len(),
+                        spec.len()
+                    )));
+                }
+                values
+            }
+        };
+        tract_ndarray::ArrayD::from_shape_vec(spec.shape.clone(), values).map(Tensor::from).map_err(model_error)
+    }
+}
+
+/// The index of the highest score, the first one on a tie.
+fn best_score(scores: &[f32]) -> Option<usize> {
+    scores
+        .iter()
+        .enumerate()
+        .fold(None, |best: Option<(usize, f32)>, (index, score)| match best {
+            Some((_, best_score)) if *score <= best_score => best,
+            _ => Some((index, *score)),
+        })
+        .map(|(index, _)| index)
+}
+
+impl InferenceEngine for OnnxEngine {
+    fn model(&self) -> ModelInfo {
+        ModelInfo { model: format!("{:?}", self.hash), version: self.labels.version.to_string() }
+    }
+
+    fn supports(&self, kind: InputKind) -> bool {
+        self.labels.input.encoding != InputEncoding::Image || kind == InputKind::Frame
+    }
+
+    fn extract(&self, input: &GameInput) -> Result<Extraction, InferenceError> {
+        let outputs = self.plan.run(tvec!(self.input_tensor(input)?.into())).map_err(model_error)?;
+
+        let mut extraction = Extraction::default();
+        for field in ObservationField::ALL {
+            let labels = self.labels.field(field).ok_or(InferenceError::MissingField(field))?;
+            let scores: Vec<f32> = outputs[labels.output].to_plain_array_view::<f32>().map_err(model_error)?.iter().copied().collect();
+            let (index, label) = best_score(&scores)
+                .and_then(|index| Some((index, labels.labels.get(index)?)))
+                .ok_or_else(|| InferenceError::Model(format!("output {} has {} scores for {} labels", labels.output, scores.len(), labels.labels.len())))?;
+            extraction.set(field, label.clone());
+            extraction.confidence.insert(field, labels.scores.confidence(&scores, index));
+        }
+        Ok(extraction)
+    }
+
+    fn extract_deterministic(&self, input: &GameInput, _determinism: &Determinism) -> Result<Extraction, InferenceError> {
+        // Matrix products split across threads could sum in a different order on each run
+        multithread::multithread_tract_scope(multithread::Executor::SingleThread, || self.extract(input))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::two_label_model;
+
+    #[test]
+    fn test_maps_model_outputs_to_labels() {
+        let (model, labels) = two_label_model();
+        let engine = OnnxEngine::from_bytes(&model, labels).unwrap();
+
+        let first = engine.extract(&GameInput::new("arena", InputKind::Frame, vec![255, 0, 0, 0])).unwrap();
+        let second = engine.extract(&GameInput::new("arena", InputKind::Frame, vec![0, 255])).unwrap();
+
+        assert_eq!((first.character.as_str(), first.place2.as_str()), ("Mage", "North"));
+        assert_eq!((second.ability.as_str(), second.place.as_str()), ("Shield Bash", "Keep"));
+        // The winning logit is 1 and the other 0, a softmax probability of e / (e + 1)
+        let expected = std::f64::consts::E / (std::f64::consts::E + 1.0);
+        assert!((first.confidence(ObservationField::Character) - expected).abs() < 1e-6);
+        assert!((second.score() - expected).abs() < 1e-6);
+        assert_eq!(engine.model().model, format!("{:?}", InputHashAlgorithm::Keccak256.digest(&model)));
+        assert_eq!(engine.model().version, "1.2.0");
+    }
+
+    #[test]
+    fn test_rejects_label_maps_that_do_not_fit_the_model() {
+        let (model, mut labels) = two_label_model();
+        labels.fields[3].output = 4;
+        assert!(OnnxEngine::from_bytes(&model, labels).is_err());
+
+        let (model, mut labels) = two_label_model();
+        labels.fields[0].labels.pop();
+        let engine = OnnxEngine::from_bytes(&model, labels).unwrap();
+        assert!(matches!(engine.extract(&GameInput::new("arena", InputKind::Frame, vec![0, 255])), Err(InferenceError::Model(_))));
+
+        assert!(OnnxEngine::from_bytes(b"not a model", two_label_model().1).is_err());
+    }
+
+    #[test]
+    fn test_reads_the_hud_regions_of_frames() {
+        use crate::inference::engine::InferenceRunner;
+        use crate::inference::label_map::InputSpec;
+        use crate::inference::observation::NodeInfo;
+        use crate::preprocessing::frame::{ColorMode, ImageSpec};
+        use crate::preprocessing::hud_layout::HudRegion;
+        use std::collections::BTreeMap;
+
+        let png = include_bytes!("../../fixtures/frames/frame_01.png").to_vec();
+        let (model, mut labels) = two_label_model();
+        // One gray pixel of each of the 4 regions; only the portrait is bright, so every field takes its first label
+        labels.input = InputSpec {
+            shape: vec![1, 4],
+            encoding: InputEncoding::Image,
+            image: Some(ImageSpec { width: 1, height: 1, color: ColorMode::Gray, mean: Vec::new(), std: Vec::new() }),
+        };
+        let region = |name: &str, x: f32, y: f32| HudRegion { name: name.to_string(), x, y, width: 0.25, height: 0.25 };
+        let layout = vec![region("portrait", 0.0, 0.0), region("top", 0.375, 0.0), region("center", 0.375, 0.375), region("minimap", 0.75, 0.75)];
+        let hud = HudLayouts { games: BTreeMap::from([("arena".to_string(), layout.clone()), ("chess".to_string(), layout[..2].to_vec())]) };
+        let engine = OnnxEngine::from_bytes(&model, labels).unwrap().with_hud_layouts(hud);
+        let runner = InferenceRunner::new(Box::new(engine), NodeInfo::new("node-1"));
+
+        let frame = GameInput::new("arena", InputKind::Frame, png.clone());
+        let observation = runner.observe(&frame).unwrap();
+        assert_eq!((observation.character.as_str(), observation.place2.as_str()), ("Mage", "North"));
+        // The observation hashes the frame as captured, not its preprocessed regions
+        assert_eq!(observation.hash_inputdata, InputHashAlgorithm::Keccak256.digest(&png).as_bytes().to_vec());
+
+        assert!(matches!(runner.observe(&GameInput::new("chess", InputKind::Frame, png.clone())), Err(InferenceError::UnsupportedInput(_))));
+        assert!(matches!(runner.observe(&GameInput::new("arena", InputKind::Log, png)), Err(InferenceError::UnsupportedInput(_))));
+    }
+}
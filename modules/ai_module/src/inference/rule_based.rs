@@ -0,0 +1,128 @@
+use json::envelope::observation_envelope::canonical_json;
+use json::input_data::input_reference::InputHashAlgorithm;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+use crate::inference::engine::{InferenceEngine, InferenceError};
+use crate::inference::game_input::{GameInput, InputKind};
+use crate::inference::observation::{Extraction, ModelInfo, ObservationField};
+
+/// Sets a field to `value` when the raw input contains `contains`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Rule {
+    pub field: ObservationField,
+    pub contains: String,
+    pub value: String,
+}
+
+/// A reference engine that extracts fields by looking for fixed byte strings in the raw input.
+///
+/// Each field takes the value of the first rule for it whose `contains` occurs in the input, so
+/// the same rules always extract the same observation from the same input. The engine stands in
+/// for a real model in tests and on nodes that only need to exercise the pipeline. Its `aimodel`
+/// is the keccak256 hash of the rules' canonical JSON, so nodes running the same rules agree on it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuleBasedEngine {
+    rules: Vec<Rule>,
+}
+
+impl RuleBasedEngine {
+    pub fn new(rules: Vec<Rule>) -> Self {
+        RuleBasedEngine { rules }
+    }
+
+    /// Loads the rules from a JSON array of `{"field", "contains", "value"}` objects.
+    ///
+    /// # Parameters
+    /// - `path`: The rules file.
+    ///
+    /// # Returns
+    /// - `Result<RuleBasedEngine, InferenceError>`: The engine, or why the rules cannot be loaded.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, InferenceError> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path).map_err(|err| InferenceError::Model(format!("cannot read {}: {}", path.display(), err)))?;
+        let rules = serde_json::from_str(&text).map_err(|err| InferenceError::Model(format!("invalid rules in {}: {}", path.display(), err)))?;
+        Ok(RuleBasedEngine::new(rules))
+    }
+
+    pub fn rules(&self) -> &[Rule] {
+        &self.rules
+    }
+}
+
+/// Whether `needle` occurs in `haystack`.
+fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+    needle.is_empty() || haystack.windows(needle.len()).any(|window| window == needle)
+}
+
+impl InferenceEngine for RuleBasedEngine {
+    fn model(&self) -> ModelInfo {
+        let rules = serde_json::to_value(&self.rules).unwrap_or_default();
+        let hash = InputHashAlgorithm::Keccak256.digest(canonical_json(&rules).as_bytes());
+        ModelInfo { model: format!("{:?}", hash), version: env!("CARGO_PKG_VERSION").to_string() }
+    }
+
+    fn supports(&self, _kind: InputKind) -> bool {
+        true
+    }
+
+    fn extract(&self, input: &GameInput) -> Result<Extraction, InferenceError> {
+        let mut extraction = Extraction::default();
+        for field in ObservationField::ALL {
+            let rule = self
+                .rules
+                .iter()
+                .find(|rule| rule.field == field && contains(&input.bytes, rule.contains.as_bytes()))
+                .ok_or(InferenceError::MissingField(field))?;
+            extraction.set(field, rule.value.clone());
+        }
+        Ok(extraction)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(field: ObservationField, contains: &str, value: &str) -> Rule {
+        Rule { field, contains: contains.to_string(), value: value.to_string() }
+    }
+
+    fn engine() -> RuleBasedEngine {
+        RuleBasedEngine::new(vec![
+            rule(ObservationField::Character, "MAGE", "Mage"),
+            rule(ObservationField::Character, "KNIGHT", "Knight"),
+            rule(ObservationField::Ability, "fireball", "Fireball"),
+            rule(ObservationField::Place, "bridge", "Bridge"),
+            rule(ObservationField::Place2, "", "unknown"),
+        ])
+    }
+
+    #[test]
+    fn test_first_matching_rule_sets_each_field() {
+        let input = GameInput::new("arena", InputKind::Log, b"KNIGHT blocks, MAGE casts fireball on the bridge".to_vec());
+
+        let extraction = engine().extract(&input).unwrap();
+
+        assert_eq!(extraction.character, "Mage");
+        assert_eq!(extraction.ability, "Fireball");
+        assert_eq!(extraction.place, "Bridge");
+        assert_eq!(extraction.place2, "unknown");
+        assert_eq!(engine().extract(&input).unwrap(), extraction);
+
+        let unmatched = GameInput::new("arena", InputKind::Log, b"KNIGHT casts fireball".to_vec());
+        assert_eq!(engine().extract(&unmatched), Err(InferenceError::MissingField(ObservationField::Place)));
+    }
+
+    #[test]
+    fn test_model_identifies_the_rules() {
+        let model = engine().model();
+
+        assert_eq!(model, engine().model());
+        assert!(model.model.starts_with("0x") && model.model.len() == 66);
+        let mut other = engine();
+        other.rules.pop();
+        assert_ne!(other.model().model, model.model);
+    }
+}
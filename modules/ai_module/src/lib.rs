@@ -0,0 +1,10 @@
+pub mod conformance;
+pub mod inference;
+#[cfg(feature = "node")]
+pub mod node;
+pub mod plugins;
+pub mod preprocessing;
+pub mod registry;
+
+#[cfg(all(test, feature = "onnx"))]
+mod test_utils;
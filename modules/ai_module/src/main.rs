@@ -1,3 +1,14 @@
+mod cli;
+
+use clap::Parser;
+use std::process;
+
+use cli::args::Cli;
+
 fn main() {
-    println!("Hello, world!");
+    let cli = Cli::parse();
+    if let Err(err) = cli::commands::run(cli) {
+        eprintln!("error: {}", err);
+        process::exit(1);
+    }
 }
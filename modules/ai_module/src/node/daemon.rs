@@ -0,0 +1,287 @@
+use ethers::signers::Signer;
+use fingerprint::signer::fingerprint_signer::FingerprintSigner;
+use json::envelope::observation_envelope::unix_now;
+use std::io;
+use std::time::Duration;
+
+use crate::inference::engine::InferenceRunner;
+use crate::node::delivery::{Delivery, DeliveryError};
+use crate::node::input_queue::InputQueue;
+use crate::node::spool::{RetryPolicy, Spool};
+
+/// What one cycle of the daemon did.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CycleReport {
+    /// Inputs observations were extracted from and spooled.
+    pub extracted: usize,
+    /// Inputs no observation could be extracted from.
+    pub failed_inputs: usize,
+    pub delivered: usize,
+    /// Observations whose delivery failed and will be tried again.
+    pub retrying: usize,
+    /// Observations rejected by the coordinator or out of attempts.
+    pub given_up: usize,
+}
+
+/// An AI node running as a daemon: it takes raw inputs from its queue, extracts their
+/// observations, spools them and delivers them, signed, to the coordinator.
+///
+/// Every observation is spooled before it is delivered, so observations extracted while the
+/// coordinator is unreachable, or before a restart, are delivered later, with backoff.
+pub struct NodeDaemon {
+    runner: InferenceRunner,
+    queue: InputQueue,
+    spool: Spool,
+    delivery: Delivery,
+    signer: FingerprintSigner,
+    retry: RetryPolicy,
+}
+
+impl NodeDaemon {
+    /// # Parameters
+    /// - `runner`: Extracts the observations, as the node whose `uploader` is the signer's address.
+    /// - `queue`: The raw inputs to extract.
+    /// - `spool`: Where observations wait for delivery.
+    /// - `delivery`: Where observations are delivered.
+    /// - `signer`: The node's signer, sealing every delivery.
+    ///
+    /// # Returns
+    /// - `Result<NodeDaemon, String>`: The daemon, or an error if the runner's uploader is not the signer.
+    pub fn new(runner: InferenceRunner, queue: InputQueue, spool: Spool, delivery: Delivery, signer: FingerprintSigner) -> Result<Self, String> {
+        if runner.node().uploader != signer.address() {
+            return Err(format!("the node uploads as {:?}, but its signer is {:?}", runner.node().uploader, signer.address()));
+        }
+        Ok(NodeDaemon { runner, queue, spool, delivery, signer, retry: RetryPolicy::default() })
+    }
+
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    pub fn spool(&self) -> &Spool {
+        &self.spool
+    }
+
+    /// Extracts the observation of every queued input and spools it.
+    ///
+    /// # Returns
+    /// - `io::Result<(usize, usize)>`: How many inputs were extracted and how many failed.
+    pub fn extract_pending(&self) -> io::Result<(usize, usize)> {
+        let (mut extracted, mut failed) = (0, 0);
+        for queued in self.queue.pending()? {
+            let (path, result) = match queued {
+                Ok(queued) => {
+                    let result = self.runner.observe(&queued.input).map_err(|err| err.to_string());
+                    (queued.path, result)
+                }
+                Err((path, reason)) => (path, Err(reason)),
+            };
+            match result {
+                Ok(observation) => {
+                    let spooled = self.spool.push(observation.to_json())?;
+                    tracing::info!(input = %path.display(), spooled = %spooled.display(), "observation extracted");
+                    self.queue.finish(&path, true)?;
+                    extracted += 1;
+                }
+                Err(reason) => {
+                    tracing::warn!(input = %path.display(), reason = %reason, "no observation extracted");
+                    self.queue.finish(&path, false)?;
+                    failed += 1;
+                }
+            }
+        }
+        Ok((extracted, failed))
+    }
+
+    /// Delivers every spooled observation that is due, oldest first.
+    ///
+    /// # Returns
+    /// - `io::Result<(usize, usize, usize)>`: How many were delivered, will be retried and were given up.
+    pub async fn deliver_due(&self) -> io::Result<(usize, usize, usize)> {
+        let (mut delivered, mut retrying, mut given_up) = (0, 0, 0);
+        for (path, spooled) in self.spool.due(unix_now())? {
+            match self.delivery.deliver(&spooled.observation, &self.signer).await {
+                Ok(receipt) => {
+                    tracing::info!(observation = %path.display(), receipt = %receipt, "observation delivered");
+                    self.spool.delivered(&path)?;
+                    delivered += 1;
+                }
+                Err(DeliveryError::Rejected(reason)) => {
+                    tracing::warn!(observation = %path.display(), reason = %reason, "observation rejected, given up");
+                    self.spool.give_up(&path, spooled)?;
+                    given_up += 1;
+                }
+                Err(err @ DeliveryError::Unavailable(_)) => {
+                    let attempts = spooled.attempts + 1;
+                    if self.spool.retry_later(&path, spooled, &err.to_string(), &self.retry)? {
+                        tracing::warn!(observation = %path.display(), attempts, reason = %err, "delivery failed, retrying later");
+                        retrying += 1;
+                    } else {
+                        tracing::warn!(observation = %path.display(), attempts, reason = %err, "delivery failed, given up");
+                        given_up += 1;
+                    }
+                }
+            }
+        }
+        Ok((delivered, retrying, given_up))
+    }
+
+    /// Extracts the queued inputs, then delivers the due observations.
+    pub async fn run_once(&self) -> io::Result<CycleReport> {
+        let (extracted, failed_inputs) = self.extract_pending()?;
+        let (delivered, retrying, given_up) = self.deliver_due().await?;
+        Ok(CycleReport { extracted, failed_inputs, delivered, retrying, given_up })
+    }
+
+    /// Runs a cycle every `poll_interval` until the process is interrupted.
+    pub async fn run(&self, poll_interval: Duration) -> io::Result<()> {
+        tracing::info!(node = %self.runner.node().node, uploader = ?self.signer.address(), "node daemon started");
+        loop {
+            let report = self.run_once().await?;
+            if report != CycleReport::default() {
+                tracing::info!(?report, "cycle finished");
+            }
+            tokio::select! {
+                _ = tokio::signal::ctrl_c() => {
+                    tracing::info!("node daemon stopped");
+                    return Ok(());
+                }
+                _ = tokio::time::sleep(poll_interval) => {}
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::inference::game_input::InputKind;
+    use crate::inference::observation::NodeInfo;
+    use crate::node::input_queue::InputMeta;
+    use crate::plugins::arena::ARENA_GAME;
+    use crate::plugins::game_plugin::GamePlugins;
+    use axum::http::StatusCode;
+    use axum::routing::post;
+    use axum::{Json, Router};
+    use ethers::signers::LocalWallet;
+    use json::envelope::observation_envelope::ObservationEnvelope;
+    use serde_json::{json, Value};
+    use std::fs;
+    use std::path::Path;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use tempfile::tempdir;
+
+    fn wallet() -> LocalWallet {
+        "4c0883a69102937d6231471b5dbb6204fe5129617082796e8e1a1e3b7a1e7e3e".parse().unwrap()
+    }
+
+    fn daemon(root: &Path, delivery: Delivery) -> NodeDaemon {
+        let node = NodeInfo::new("node-1").uploaded_by(wallet().address());
+        let runner = InferenceRunner::without_engine(node).with_plugins(GamePlugins::builtin());
+        let defaults = InputMeta { game: Some(ARENA_GAME.to_string()), kind: Some(InputKind::Log), ..InputMeta::default() };
+        let queue = InputQueue::open(root.join("queue"), defaults).unwrap();
+        fs::write(root.join("queue/match_01.log"), include_bytes!("../../fixtures/arena/match_01.log")).unwrap();
+        fs::write(root.join("queue/unreadable.log"), "nothing to see").unwrap();
+        let retry = RetryPolicy { max_attempts: 3, initial_backoff: Duration::ZERO, max_backoff: Duration::ZERO };
+        let signer = FingerprintSigner::Local(wallet());
+        NodeDaemon::new(runner, queue, Spool::open(root.join("spool")).unwrap(), delivery, signer).unwrap().with_retry(retry)
+    }
+
+    #[tokio::test]
+    async fn test_delivers_signed_envelopes_into_an_inbox() {
+        let root = tempdir().unwrap();
+        let inbox = root.path().join("inbox");
+        let daemon = daemon(root.path(), Delivery::Inbox(inbox.clone()));
+
+        // The coordinator's inbox is not there yet, the observation stays spooled
+        let report = daemon.run_once().await.unwrap();
+        assert_eq!(report, CycleReport { extracted: 1, failed_inputs: 1, retrying: 1, ..CycleReport::default() });
+        assert!(root.path().join("queue/done/match_01.log").exists() && root.path().join("queue/failed/unreadable.log").exists());
+
+        fs::create_dir(&inbox).unwrap();
+        let report = daemon.run_once().await.unwrap();
+        assert_eq!(report, CycleReport { delivered: 1, ..CycleReport::default() });
+        assert!(daemon.spool().entries().unwrap().is_empty());
+
+        let delivered = fs::read_dir(&inbox).unwrap().next().unwrap().unwrap().path();
+        let envelope = ObservationEnvelope::parse(&fs::read_to_string(delivered).unwrap()).unwrap();
+        assert_eq!(envelope.verify().unwrap(), wallet().address());
+        assert_eq!((envelope.payload["ainode"].as_str(), envelope.payload["character"].as_str()), (Some("node-1"), Some("Mage")));
+    }
+
+    #[tokio::test]
+    async fn test_posts_signed_envelopes_and_retries_until_accepted() {
+        // A coordinator that is unavailable once, then checks the envelope and accepts
+        let requests = Arc::new(AtomicUsize::new(0));
+        let counter = requests.clone();
+        let app = Router::new().route(
+            "/observations",
+            post(move |Json(body): Json<Value>| {
+                let counter = counter.clone();
+                async move {
+                    if counter.fetch_add(1, Ordering::SeqCst) == 0 {
+                        return (StatusCode::SERVICE_UNAVAILABLE, Json(json!({ "error": "starting" })));
+                    }
+                    let envelope = ObservationEnvelope::parse(&body.to_string()).unwrap();
+                    match (envelope.verify(), envelope.uploader()) {
+                        (Ok(signer), Ok(uploader)) if signer == uploader && signer == wallet().address() => {
+                            (StatusCode::ACCEPTED, Json(json!({ "submission_id": "sub-1", "round_id": "round-1", "observations": 1 })))
+                        }
+                        _ => (StatusCode::FORBIDDEN, Json(json!({ "error": "uploader did not sign the envelope" }))),
+                    }
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let root = tempdir().unwrap();
+        let daemon = daemon(root.path(), Delivery::http(&url, Duration::from_secs(5)).unwrap());
+
+        assert_eq!(daemon.run_once().await.unwrap().retrying, 1);
+        let (_, spooled) = daemon.spool().entries().unwrap().remove(0);
+        assert_eq!(spooled.last_error.as_deref(), Some("coordinator unavailable: 503 Service Unavailable starting"));
+
+        assert_eq!(daemon.run_once().await.unwrap().delivered, 1);
+        assert_eq!(requests.load(Ordering::SeqCst), 2);
+        assert!(daemon.spool().entries().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_observations_refused_by_the_open_round_wait_for_the_next() {
+        // A coordinator that takes one observation of the node per round
+        let round_taken = Arc::new(AtomicBool::new(false));
+        let taken = round_taken.clone();
+        let app = Router::new().route(
+            "/observations",
+            post(move || {
+                let taken = taken.clone();
+                async move {
+                    if taken.swap(true, Ordering::SeqCst) {
+                        return (StatusCode::CONFLICT, Json(json!({ "error": "uploader already submitted an observation to round round-1" })));
+                    }
+                    (StatusCode::ACCEPTED, Json(json!({ "submission_id": "sub-1", "round_id": "round-1", "observations": 1 })))
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let root = tempdir().unwrap();
+        let daemon = daemon(root.path(), Delivery::http(&url, Duration::from_secs(5)).unwrap());
+        fs::write(root.path().join("queue/match_02.log"), include_bytes!("../../fixtures/arena/match_02.log")).unwrap();
+
+        let report = daemon.run_once().await.unwrap();
+        assert_eq!((report.extracted, report.delivered, report.retrying, report.given_up), (2, 1, 1, 0));
+
+        // The round closed, the spooled observation joins the next one
+        round_taken.store(false, Ordering::SeqCst);
+        assert_eq!(daemon.run_once().await.unwrap(), CycleReport { delivered: 1, ..CycleReport::default() });
+        assert!(daemon.spool().entries().unwrap().is_empty());
+        assert!(!daemon.spool().dir().join("failed").exists());
+    }
+}
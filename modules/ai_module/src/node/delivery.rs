@@ -0,0 +1,93 @@
+use ethers::signers::Signer;
+use json::envelope::observation_envelope::ObservationEnvelope;
+use reqwest::StatusCode;
+use serde_json::Value;
+use std::fmt;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::node::spool::write_atomically;
+
+/// Why an observation was not delivered.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DeliveryError {
+    /// The coordinator turned the observation down; delivering it again would not help.
+    Rejected(String),
+    /// The coordinator could not be reached or could not take the observation now.
+    Unavailable(String),
+}
+
+impl fmt::Display for DeliveryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DeliveryError::Rejected(reason) => write!(f, "rejected: {}", reason),
+            DeliveryError::Unavailable(reason) => write!(f, "coordinator unavailable: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for DeliveryError {}
+
+/// Where a node delivers its observations, each sealed in a signed envelope.
+pub enum Delivery {
+    /// Envelopes written into the inbox directory a coordinator reads with `--require-envelopes`.
+    Inbox(PathBuf),
+    /// Envelopes posted to `POST /observations` of a coordinator's HTTP API.
+    Http { url: String, client: reqwest::Client },
+}
+
+impl Delivery {
+    /// Delivers to the HTTP API at `base_url`, giving up on a request after `timeout`.
+    pub fn http(base_url: &str, timeout: Duration) -> Result<Self, DeliveryError> {
+        let client = reqwest::Client::builder().timeout(timeout).build().map_err(|err| DeliveryError::Unavailable(err.to_string()))?;
+        Ok(Delivery::Http { url: format!("{}/observations", base_url.trim_end_matches('/')), client })
+    }
+
+    /// Seals one observation in an envelope signed by the node and delivers it.
+    ///
+    /// # Parameters
+    /// - `observation`: The observation, whose `uploader` is the signer's address.
+    /// - `signer`: The node's signer.
+    ///
+    /// # Returns
+    /// - `Result<String, DeliveryError>`: The inbox file or the submission id, or why the
+    ///   observation was not delivered.
+    pub async fn deliver<S: Signer>(&self, observation: &Value, signer: &S) -> Result<String, DeliveryError> {
+        if let Delivery::Inbox(dir) = self {
+            if !dir.is_dir() {
+                return Err(DeliveryError::Unavailable(format!("{} is not a directory", dir.display())));
+            }
+        }
+        // Sealed at delivery time, so retried envelopes are not stale when they arrive
+        let envelope = ObservationEnvelope::seal(observation.clone(), signer).await.map_err(|err| DeliveryError::Unavailable(format!("cannot sign: {}", err)))?;
+        match self {
+            Delivery::Inbox(dir) => {
+                let path = dir.join(format!("{}.json", envelope.nonce));
+                write_atomically(&path, &envelope).map_err(|err| DeliveryError::Unavailable(format!("cannot write {}: {}", path.display(), err)))?;
+                Ok(path.display().to_string())
+            }
+            Delivery::Http { url, client } => {
+                let response = client
+                    .post(url)
+                    .json(&envelope)
+                    .send()
+                    .await
+                    .map_err(|err| DeliveryError::Unavailable(err.to_string()))?;
+
+                let status = response.status();
+                let reply: Value = response.json().await.unwrap_or_default();
+                if status.is_success() {
+                    return Ok(reply["submission_id"].as_str().unwrap_or_default().to_string());
+                }
+                let reason = format!("{} {}", status, reply["error"].as_str().unwrap_or_default()).trim_end().to_string();
+                // A conflict means the node already has an observation in the open round, which
+                // the next round takes
+                match status {
+                    StatusCode::REQUEST_TIMEOUT | StatusCode::CONFLICT | StatusCode::TOO_MANY_REQUESTS => Err(DeliveryError::Unavailable(reason)),
+                    status if status.is_client_error() => Err(DeliveryError::Rejected(reason)),
+                    _ => Err(DeliveryError::Unavailable(reason)),
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,164 @@
+use json::timestamp::observation_time::ObservationTime;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use crate::inference::game_input::{GameInput, InputKind};
+
+/// The suffix of the file describing a queued input, next to the input itself.
+pub const META_SUFFIX: &str = ".meta.json";
+
+/// What a queued input's meta file can say about it; missing values take the queue's defaults.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct InputMeta {
+    #[serde(default)]
+    pub game: Option<String>,
+    #[serde(default)]
+    pub kind: Option<InputKind>,
+    #[serde(default)]
+    pub source: Option<u64>,
+    #[serde(default)]
+    pub captured_at: Option<ObservationTime>,
+}
+
+/// A raw input taken from the queue.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueuedInput {
+    pub path: PathBuf,
+    pub input: GameInput,
+}
+
+/// A directory of raw inputs waiting for inference.
+///
+/// Capture tools drop input files into the directory, optionally with a `<file>.meta.json`
+/// naming the input's `game`, `kind`, `source` and `captured_at`. Files are taken in name order;
+/// files ending in `.tmp` are still being written and are left alone. An input captured without
+/// a time was captured when its file was last written. Processed inputs move to `done/`, and
+/// inputs no observation could be extracted from to `failed/`.
+pub struct InputQueue {
+    dir: PathBuf,
+    defaults: InputMeta,
+}
+
+impl InputQueue {
+    /// # Parameters
+    /// - `dir`: The queue directory, created if missing.
+    /// - `defaults`: The game, kind and source of inputs whose meta file does not name them.
+    pub fn open(dir: impl Into<PathBuf>, defaults: InputMeta) -> io::Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        Ok(InputQueue { dir, defaults })
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Takes every input waiting in the queue, in name order.
+    ///
+    /// # Returns
+    /// - `io::Result<Vec<Result<QueuedInput, (PathBuf, String)>>>`: Each input, or its path and
+    ///   why it cannot be read, for example a missing game.
+    pub fn pending(&self) -> io::Result<Vec<Result<QueuedInput, (PathBuf, String)>>> {
+        let mut paths: Vec<PathBuf> = fs::read_dir(&self.dir)?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.is_file())
+            .filter(|path| {
+                let name = path.file_name().and_then(|name| name.to_str()).unwrap_or_default();
+                !name.ends_with(".tmp") && !name.ends_with(META_SUFFIX)
+            })
+            .collect();
+        paths.sort();
+
+        Ok(paths.into_iter().map(|path| self.read(&path).map_err(|reason| (path, reason))).collect())
+    }
+
+    fn read(&self, path: &Path) -> Result<QueuedInput, String> {
+        let meta_path = meta_path(path);
+        let meta: InputMeta = match fs::read_to_string(&meta_path) {
+            Ok(text) => serde_json::from_str(&text).map_err(|err| format!("invalid {}: {}", meta_path.display(), err))?,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => InputMeta::default(),
+            Err(err) => return Err(format!("cannot read {}: {}", meta_path.display(), err)),
+        };
+        let game = meta.game.or_else(|| self.defaults.game.clone()).ok_or("the input names no game")?;
+        let kind = meta.kind.or(self.defaults.kind).unwrap_or_default();
+        let source = meta.source.or(self.defaults.source).unwrap_or_default();
+        let captured_at = match meta.captured_at {
+            Some(captured_at) => Some(captured_at),
+            None => fs::metadata(path)
+                .and_then(|metadata| metadata.modified())
+                .ok()
+                .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+                .and_then(|elapsed| ObservationTime::from_unix_millis(elapsed.as_millis() as u64)),
+        };
+
+        let bytes = fs::read(path).map_err(|err| format!("cannot read {}: {}", path.display(), err))?;
+        let mut input = GameInput::new(game, kind, bytes).from_source(source);
+        if let Some(captured_at) = captured_at {
+            input = input.captured_at(captured_at);
+        }
+        Ok(QueuedInput { path: path.to_path_buf(), input })
+    }
+
+    /// Moves a taken input, and its meta file, out of the queue.
+    ///
+    /// # Parameters
+    /// - `path`: The input's path.
+    /// - `processed`: Whether an observation was extracted from it, moving it to `done/`
+    ///   rather than `failed/`.
+    pub fn finish(&self, path: &Path, processed: bool) -> io::Result<()> {
+        let target = self.dir.join(if processed { "done" } else { "failed" });
+        fs::create_dir_all(&target)?;
+        for file in [path.to_path_buf(), meta_path(path)] {
+            if let Some(name) = file.file_name().filter(|_| file.exists()) {
+                fs::rename(&file, target.join(name))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn meta_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(META_SUFFIX);
+    path.with_file_name(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_reads_inputs_with_their_meta_files_in_order() {
+        let dir = tempdir().unwrap();
+        let defaults = InputMeta { game: Some("arena".to_string()), kind: Some(InputKind::Log), ..InputMeta::default() };
+        let queue = InputQueue::open(dir.path(), defaults).unwrap();
+        fs::write(dir.path().join("b.log"), "Knight").unwrap();
+        fs::write(dir.path().join("a.png"), "frame").unwrap();
+        fs::write(dir.path().join("a.png.meta.json"), r#"{"kind": "frame", "source": 2, "captured_at": "2024-08-12 16:35:35 UTC"}"#).unwrap();
+        fs::write(dir.path().join("c.log.tmp"), "partial").unwrap();
+
+        let pending: Vec<QueuedInput> = queue.pending().unwrap().into_iter().map(Result::unwrap).collect();
+
+        assert_eq!(pending.len(), 2);
+        let frame = &pending[0].input;
+        assert_eq!((frame.game.as_str(), frame.kind, frame.source), ("arena", InputKind::Frame, 2));
+        assert_eq!(frame.captured_at, Some(ObservationTime::parse("2024-08-12 16:35:35 UTC").unwrap()));
+        // Without a meta file the input takes the defaults and its file's time
+        assert_eq!((pending[1].input.kind, pending[1].input.bytes.as_slice()), (InputKind::Log, &b"Knight"[..]));
+        assert!(pending[1].input.captured_at.is_some());
+
+        queue.finish(&pending[0].path, true).unwrap();
+        queue.finish(&pending[1].path, false).unwrap();
+        assert!(dir.path().join("done/a.png").exists() && dir.path().join("done/a.png.meta.json").exists());
+        assert!(dir.path().join("failed/b.log").exists());
+        assert!(queue.pending().unwrap().is_empty());
+
+        let anonymous = InputQueue::open(dir.path().join("other"), InputMeta::default()).unwrap();
+        fs::write(anonymous.dir().join("x.log"), "Mage").unwrap();
+        assert!(anonymous.pending().unwrap()[0].is_err());
+    }
+}
@@ -0,0 +1,4 @@
+pub mod daemon;
+pub mod delivery;
+pub mod input_queue;
+pub mod spool;
@@ -0,0 +1,191 @@
+use json::envelope::observation_envelope::unix_now;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// How often and how soon delivering a spooled observation is tried again.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RetryPolicy {
+    /// How many times delivery is tried before the observation is given up.
+    pub max_attempts: u32,
+    /// The wait after the first failed attempt, doubled after every further one.
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy { max_attempts: 8, initial_backoff: Duration::from_secs(2), max_backoff: Duration::from_secs(300) }
+    }
+}
+
+impl RetryPolicy {
+    /// The wait before the next attempt, after `attempts` failed ones.
+    pub fn backoff(&self, attempts: u32) -> Duration {
+        let doublings = attempts.saturating_sub(1).min(31);
+        self.initial_backoff.saturating_mul(1 << doublings).min(self.max_backoff)
+    }
+}
+
+/// An observation waiting in the spool to be delivered.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SpooledObservation {
+    pub observation: Value,
+    /// How many deliveries failed so far.
+    #[serde(default)]
+    pub attempts: u32,
+    /// When the next delivery is due, in seconds since the Unix epoch.
+    #[serde(default)]
+    pub next_attempt_at: u64,
+    /// Why the last delivery failed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+}
+
+/// A directory of observations extracted but not delivered yet, so none is lost when the
+/// coordinator is unreachable or the node restarts.
+///
+/// Each observation is one JSON file, written to a temporary name and renamed, so a crash never
+/// leaves a partial file. Observations the coordinator rejects, or that run out of attempts,
+/// move to `failed/` for the operator to inspect.
+pub struct Spool {
+    dir: PathBuf,
+}
+
+impl Spool {
+    /// # Parameters
+    /// - `dir`: The spool directory, created if missing.
+    pub fn open(dir: impl Into<PathBuf>) -> io::Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        Ok(Spool { dir })
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Adds an observation, due for delivery right away.
+    ///
+    /// # Returns
+    /// - `io::Result<PathBuf>`: The spooled observation's file.
+    pub fn push(&self, observation: Value) -> io::Result<PathBuf> {
+        // Named by the time and a counter, so observations are delivered in the order they were spooled
+        let millis = SystemTime::now().duration_since(UNIX_EPOCH).map(|elapsed| elapsed.as_millis()).unwrap_or(0);
+        let mut sequence = 0;
+        let mut path = self.dir.join(format!("{:020}-{:04}.json", millis, sequence));
+        while path.exists() {
+            sequence += 1;
+            path = self.dir.join(format!("{:020}-{:04}.json", millis, sequence));
+        }
+        let spooled = SpooledObservation { observation, attempts: 0, next_attempt_at: 0, last_error: None };
+        write_atomically(&path, &spooled)?;
+        Ok(path)
+    }
+
+    /// The spooled observations due for delivery at `now`, oldest first.
+    ///
+    /// # Parameters
+    /// - `now`: The current time, in seconds since the Unix epoch.
+    pub fn due(&self, now: u64) -> io::Result<Vec<(PathBuf, SpooledObservation)>> {
+        Ok(self.entries()?.into_iter().filter(|(_, spooled)| spooled.next_attempt_at <= now).collect())
+    }
+
+    /// Every spooled observation, oldest first.
+    pub fn entries(&self) -> io::Result<Vec<(PathBuf, SpooledObservation)>> {
+        let mut paths: Vec<PathBuf> = fs::read_dir(&self.dir)?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().and_then(|ext| ext.to_str()) == Some("json"))
+            .collect();
+        paths.sort();
+
+        let mut entries = Vec::with_capacity(paths.len());
+        for path in paths {
+            let text = fs::read_to_string(&path)?;
+            match serde_json::from_str(&text) {
+                Ok(spooled) => entries.push((path, spooled)),
+                Err(err) => tracing::warn!(path = %path.display(), reason = %err, "unreadable spool entry skipped"),
+            }
+        }
+        Ok(entries)
+    }
+
+    /// Removes a delivered observation.
+    pub fn delivered(&self, path: &Path) -> io::Result<()> {
+        fs::remove_file(path)
+    }
+
+    /// Records a failed delivery, and when to try again.
+    ///
+    /// # Parameters
+    /// - `path`: The spooled observation's file.
+    /// - `spooled`: The observation as it was spooled.
+    /// - `error`: Why the delivery failed.
+    /// - `retry`: The retry policy.
+    ///
+    /// # Returns
+    /// - `io::Result<bool>`: Whether the observation stays spooled; it is given up and moved to
+    ///   `failed/` once it ran out of attempts.
+    pub fn retry_later(&self, path: &Path, mut spooled: SpooledObservation, error: &str, retry: &RetryPolicy) -> io::Result<bool> {
+        spooled.attempts += 1;
+        spooled.last_error = Some(error.to_string());
+        if spooled.attempts >= retry.max_attempts {
+            self.give_up(path, spooled)?;
+            return Ok(false);
+        }
+        spooled.next_attempt_at = unix_now() + retry.backoff(spooled.attempts).as_secs();
+        write_atomically(path, &spooled)?;
+        Ok(true)
+    }
+
+    /// Moves an observation that will never be delivered to `failed/`.
+    pub fn give_up(&self, path: &Path, spooled: SpooledObservation) -> io::Result<()> {
+        let failed = self.dir.join("failed");
+        fs::create_dir_all(&failed)?;
+        write_atomically(&failed.join(path.file_name().unwrap_or_default()), &spooled)?;
+        fs::remove_file(path)
+    }
+}
+
+/// Writes JSON to a temporary file next to `path`, then renames it to `path`.
+pub fn write_atomically(path: &Path, value: &impl Serialize) -> io::Result<()> {
+    let mut temporary = path.as_os_str().to_os_string();
+    temporary.push(".tmp");
+    fs::write(&temporary, serde_json::to_vec_pretty(value)?)?;
+    fs::rename(&temporary, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_failed_deliveries_back_off_until_given_up() {
+        let dir = tempdir().unwrap();
+        let spool = Spool::open(dir.path()).unwrap();
+        let retry = RetryPolicy { max_attempts: 2, initial_backoff: Duration::from_secs(60), max_backoff: Duration::from_secs(90) };
+        assert_eq!((retry.backoff(1), retry.backoff(2), retry.backoff(5)), (Duration::from_secs(60), Duration::from_secs(90), Duration::from_secs(90)));
+
+        let first = spool.push(json!({ "character": "Mage" })).unwrap();
+        let second = spool.push(json!({ "character": "Knight" })).unwrap();
+        let due = spool.due(unix_now()).unwrap();
+        assert_eq!(due.iter().map(|(path, _)| path.clone()).collect::<Vec<_>>(), vec![first.clone(), second.clone()]);
+
+        let (path, spooled) = due[0].clone();
+        assert!(spool.retry_later(&path, spooled, "coordinator unavailable", &retry).unwrap());
+        // Not due again before its backoff
+        assert_eq!(spool.due(unix_now()).unwrap().len(), 1);
+        let (_, retried) = spool.entries().unwrap().remove(0);
+        assert_eq!((retried.attempts, retried.last_error.as_deref()), (1, Some("coordinator unavailable")));
+
+        assert!(!spool.retry_later(&path, retried, "coordinator unavailable", &retry).unwrap());
+        assert!(dir.path().join("failed").join(first.file_name().unwrap()).exists());
+        spool.delivered(&second).unwrap();
+        assert!(spool.entries().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,118 @@
+use crate::inference::engine::InferenceEngine;
+use crate::inference::observation::{ObservationField, OutputSchema};
+use crate::inference::rule_based::{Rule, RuleBasedEngine};
+use crate::plugins::game_plugin::{Aliases, GamePlugin};
+
+/// The sample game the plugin handles.
+pub const ARENA_GAME: &str = "arena";
+
+/// The rules extracting arena observations from its match logs.
+const ARENA_RULES: &str = include_str!("../../rules/arena_rules.json");
+
+/// A sample plugin for `arena`, a game whose match logs spell the same character, ability or
+/// place differently depending on the client version.
+///
+/// Observations are extracted from the match log by rules, then every spelling is mapped to
+/// its canonical name; values the plugin does not know are rejected.
+pub struct ArenaPlugin {
+    extractor: RuleBasedEngine,
+    aliases: Aliases,
+}
+
+impl Default for ArenaPlugin {
+    fn default() -> Self {
+        ArenaPlugin::new()
+    }
+}
+
+impl ArenaPlugin {
+    pub fn new() -> Self {
+        let rules: Vec<Rule> = serde_json::from_str(ARENA_RULES).expect("the arena rules are valid JSON");
+        let aliases = Aliases::new()
+            .with(ObservationField::Character, "Mage", &["magus"])
+            .with(ObservationField::Character, "Knight", &[])
+            .with(ObservationField::Ability, "Fireball", &["fire ball"])
+            .with(ObservationField::Ability, "Shield Bash", &["shield_bash"])
+            .with(ObservationField::Place, "Bridge", &[])
+            .with(ObservationField::Place, "Keep", &[])
+            .with(ObservationField::Place2, "North", &[])
+            .with(ObservationField::Place2, "South", &[]);
+        ArenaPlugin { extractor: RuleBasedEngine::new(rules), aliases }
+    }
+}
+
+impl GamePlugin for ArenaPlugin {
+    fn game(&self) -> &str {
+        ARENA_GAME
+    }
+
+    fn extractor(&self) -> Option<&dyn InferenceEngine> {
+        Some(&self.extractor)
+    }
+
+    fn schema(&self) -> OutputSchema {
+        self.aliases.schema()
+    }
+
+    fn normalize(&self, field: ObservationField, value: &str) -> String {
+        let value = value.trim();
+        self.aliases.canonical(field, value).unwrap_or(value).to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::inference::engine::{InferenceError, InferenceRunner};
+    use crate::inference::game_input::{GameInput, InputKind};
+    use crate::inference::observation::{Extraction, NodeInfo};
+    use crate::plugins::game_plugin::GamePlugins;
+
+    fn runner() -> InferenceRunner {
+        // The node has no engine of its own, arena inputs are extracted by the plugin
+        InferenceRunner::without_engine(NodeInfo::new("node-1")).with_plugins(GamePlugins::builtin())
+    }
+
+    fn canonical() -> Extraction {
+        Extraction {
+            character: "Mage".to_string(),
+            ability: "Fireball".to_string(),
+            place: "Bridge".to_string(),
+            place2: "North".to_string(),
+            confidence: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_recorded_matches_are_extracted_with_canonical_names() {
+        let runner = runner();
+        let first = GameInput::new(ARENA_GAME, InputKind::Log, include_bytes!("../../fixtures/arena/match_01.log").to_vec());
+        let second = GameInput::new(ARENA_GAME, InputKind::Log, include_bytes!("../../fixtures/arena/match_02.log").to_vec());
+
+        let first = runner.observe(&first).unwrap();
+        let second = runner.observe(&second).unwrap();
+
+        assert_eq!((first.character.as_str(), first.ability.as_str()), ("Mage", "Fireball"));
+        assert_eq!((first.place.as_str(), first.place2.as_str()), ("Bridge", "North"));
+        // Another client spells the same character and ability differently
+        assert_eq!((second.character.as_str(), second.ability.as_str()), ("Mage", "Fireball"));
+        assert_eq!((second.place.as_str(), second.place2.as_str()), ("Keep", "South"));
+        assert_eq!(first.aimodel, ArenaPlugin::new().extractor.model().model);
+    }
+
+    #[test]
+    fn test_unknown_values_and_games_without_a_plugin() {
+        let plugin = ArenaPlugin::new();
+        let mut unknown = canonical();
+        unknown.character = "DRAGON".to_string();
+        assert_eq!(
+            plugin.normalized(unknown),
+            Err(InferenceError::OutsideSchema { field: ObservationField::Character, value: "DRAGON".to_string() })
+        );
+        assert_eq!(plugin.normalized(canonical()).unwrap(), canonical());
+
+        // Games without a plugin need an engine of the node's own
+        let other = GameInput::new("chess", InputKind::Log, include_bytes!("../../fixtures/arena/match_01.log").to_vec());
+        assert!(matches!(runner().observe(&other), Err(InferenceError::UnsupportedInput(_))));
+    }
+}
@@ -0,0 +1,120 @@
+use std::collections::BTreeMap;
+
+use crate::inference::engine::{InferenceEngine, InferenceError};
+use crate::inference::observation::{Extraction, ObservationField, OutputSchema};
+
+/// Maps the spellings a game's inputs use to canonical field values, ignoring case.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Aliases {
+    aliases: BTreeMap<ObservationField, BTreeMap<String, String>>,
+}
+
+impl Aliases {
+    pub fn new() -> Self {
+        Aliases::default()
+    }
+
+    /// Adds the spellings of one canonical value.
+    ///
+    /// # Parameters
+    /// - `field`: The field the value belongs to.
+    /// - `canonical`: The value written into observations, itself also a spelling.
+    /// - `spellings`: The other spellings of the value.
+    pub fn with(mut self, field: ObservationField, canonical: &str, spellings: &[&str]) -> Self {
+        let aliases = self.aliases.entry(field).or_default();
+        for spelling in spellings.iter().chain([&canonical]) {
+            aliases.insert(spelling.to_lowercase(), canonical.to_string());
+        }
+        self
+    }
+
+    /// The canonical value of a spelling, if it has one.
+    pub fn canonical(&self, field: ObservationField, value: &str) -> Option<&str> {
+        self.aliases.get(&field)?.get(&value.to_lowercase()).map(String::as_str)
+    }
+
+    /// The canonical values of every field with aliases, the schema of a game whose values are all known.
+    pub fn schema(&self) -> OutputSchema {
+        self.aliases
+            .iter()
+            .map(|(field, aliases)| {
+                let mut values: Vec<String> = aliases.values().cloned().collect();
+                values.sort();
+                values.dedup();
+                (*field, values)
+            })
+            .collect()
+    }
+}
+
+/// What a node knows about one game: how to extract its observations and which values they can hold.
+pub trait GamePlugin: Send + Sync {
+    /// The `game` of the inputs the plugin handles.
+    fn game(&self) -> &str;
+
+    /// The engine that extracts the game's observations, or `None` to use the node's engine.
+    fn extractor(&self) -> Option<&dyn InferenceEngine> {
+        None
+    }
+
+    /// The values the game's observations can hold.
+    fn schema(&self) -> OutputSchema;
+
+    /// The canonical form of an extracted value, for example a character's full name.
+    fn normalize(&self, _field: ObservationField, value: &str) -> String {
+        value.trim().to_string()
+    }
+
+    /// Normalizes every extracted value and checks it against the game's schema.
+    ///
+    /// # Parameters
+    /// - `extraction`: The values as extracted.
+    ///
+    /// # Returns
+    /// - `Result<Extraction, InferenceError>`: The canonical values with their confidences, or the
+    ///   first value outside the schema.
+    fn normalized(&self, extraction: Extraction) -> Result<Extraction, InferenceError> {
+        let schema = self.schema();
+        let mut normalized = Extraction { confidence: extraction.confidence.clone(), ..Extraction::default() };
+        for field in ObservationField::ALL {
+            let value = self.normalize(field, extraction.get(field));
+            if schema.get(&field).is_some_and(|allowed| !allowed.contains(&value)) {
+                return Err(InferenceError::OutsideSchema { field, value });
+            }
+            normalized.set(field, value);
+        }
+        Ok(normalized)
+    }
+}
+
+/// The game plugins of a node, selected by the `game` of each input.
+#[derive(Default)]
+pub struct GamePlugins {
+    plugins: BTreeMap<String, Box<dyn GamePlugin>>,
+}
+
+impl GamePlugins {
+    pub fn new() -> Self {
+        GamePlugins::default()
+    }
+
+    /// The plugins that ship with the node.
+    pub fn builtin() -> Self {
+        GamePlugins::new().with(Box::new(crate::plugins::arena::ArenaPlugin::new()))
+    }
+
+    /// Adds a plugin, replacing any plugin of the same game.
+    pub fn with(mut self, plugin: Box<dyn GamePlugin>) -> Self {
+        self.plugins.insert(plugin.game().to_string(), plugin);
+        self
+    }
+
+    pub fn get(&self, game: &str) -> Option<&dyn GamePlugin> {
+        self.plugins.get(game).map(|plugin| plugin.as_ref())
+    }
+
+    /// The games that have a plugin, in order.
+    pub fn games(&self) -> Vec<&str> {
+        self.plugins.keys().map(String::as_str).collect()
+    }
+}
@@ -0,0 +1,175 @@
+use image::imageops::FilterType;
+use image::{DynamicImage, ImageFormat};
+use serde::{Deserialize, Serialize};
+
+use crate::inference::engine::InferenceError;
+use crate::preprocessing::hud_layout::HudRegion;
+
+/// The color channels a model reads.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ColorMode {
+    /// One luminance channel.
+    Gray,
+    /// Red, green and blue channels.
+    #[default]
+    Rgb,
+}
+
+impl ColorMode {
+    pub fn channels(&self) -> usize {
+        match self {
+            ColorMode::Gray => 1,
+            ColorMode::Rgb => 3,
+        }
+    }
+}
+
+/// How a frame is turned into model input: every HUD region is resized to `width` by `height`,
+/// and each channel scaled to `[0, 1]` and normalized with its `mean` and `std`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ImageSpec {
+    pub width: u32,
+    pub height: u32,
+    #[serde(default)]
+    pub color: ColorMode,
+    /// The mean of each channel, 0 for every channel when empty.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mean: Vec<f32>,
+    /// The standard deviation of each channel, 1 for every channel when empty.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub std: Vec<f32>,
+}
+
+/// Decodes a PNG or JPEG frame; other formats are unsupported.
+///
+/// # Parameters
+/// - `bytes`: The frame as captured.
+///
+/// # Returns
+/// - `Result<DynamicImage, InferenceError>`: The decoded frame, or why it cannot be decoded.
+pub fn decode_frame(bytes: &[u8]) -> Result<DynamicImage, InferenceError> {
+    let format = image::guess_format(bytes).map_err(|err| InferenceError::UnsupportedInput(format!("not an image: {}", err)))?;
+    if !matches!(format, ImageFormat::Png | ImageFormat::Jpeg) {
+        return Err(InferenceError::UnsupportedInput(format!("{:?} frames are not supported, only PNG and JPEG", format)));
+    }
+    image::load_from_memory_with_format(bytes, format).map_err(|err| InferenceError::UnsupportedInput(format!("cannot decode the frame: {}", err)))
+}
+
+impl ImageSpec {
+    /// Checks that the size is not empty and that `mean` and `std` are empty or have a non-zero
+    /// value for every channel.
+    pub fn check(&self) -> Result<(), InferenceError> {
+        if self.width == 0 || self.height == 0 {
+            return Err(InferenceError::Model("the image size is empty".to_string()));
+        }
+        let channels = self.color.channels();
+        for (name, values) in [("mean", &self.mean), ("std", &self.std)] {
+            if !values.is_empty() && values.len() != channels {
+                return Err(InferenceError::Model(format!("`{}` has {} values for {} channels", name, values.len(), channels)));
+            }
+        }
+        if self.std.contains(&0.0) {
+            return Err(InferenceError::Model("`std` cannot be 0".to_string()));
+        }
+        Ok(())
+    }
+
+    /// How many values the preprocessing of a frame with `regions` HUD regions yields.
+    pub fn len(&self, regions: usize) -> usize {
+        regions.max(1) * self.color.channels() * self.width as usize * self.height as usize
+    }
+
+    /// Decodes a frame and turns its HUD regions into model input.
+    ///
+    /// The frame is only read: the observation's `hash_inputdata` stays the hash of the bytes as
+    /// captured. Resizing uses a fixed bilinear filter, so every node gets the same values.
+    ///
+    /// # Parameters
+    /// - `bytes`: The frame as captured, PNG or JPEG.
+    /// - `regions`: The HUD regions to read, or none to read the whole frame.
+    ///
+    /// # Returns
+    /// - `Result<Vec<f32>, InferenceError>`: The values of every region in order, each laid out
+    ///   channel by channel, row by row; or why the frame cannot be read.
+    pub fn preprocess(&self, bytes: &[u8], regions: &[HudRegion]) -> Result<Vec<f32>, InferenceError> {
+        let frame = decode_frame(bytes)?;
+        let crops: Vec<DynamicImage> = if regions.is_empty() {
+            vec![frame]
+        } else {
+            regions
+                .iter()
+                .map(|region| {
+                    let (x, y, width, height) = region.pixels(frame.width(), frame.height());
+                    frame.crop_imm(x, y, width, height)
+                })
+                .collect()
+        };
+
+        let channels = self.color.channels();
+        let mut values = Vec::with_capacity(self.len(regions.len()));
+        for crop in crops {
+            let resized = crop.resize_exact(self.width, self.height, FilterType::Triangle);
+            let pixels = match self.color {
+                ColorMode::Gray => resized.to_luma8().into_raw(),
+                ColorMode::Rgb => resized.to_rgb8().into_raw(),
+            };
+            for channel in 0..channels {
+                let mean = self.mean.get(channel).copied().unwrap_or(0.0);
+                let std = self.std.get(channel).copied().unwrap_or(1.0);
+                values.extend(pixels.iter().skip(channel).step_by(channels).map(|value| (*value as f32 / 255.0 - mean) / std));
+            }
+        }
+        Ok(values)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PNG: &[u8] = include_bytes!("../../fixtures/frames/frame_01.png");
+    const JPEG: &[u8] = include_bytes!("../../fixtures/frames/frame_01.jpg");
+
+    fn region(name: &str, x: f32, y: f32) -> HudRegion {
+        HudRegion { name: name.to_string(), x, y, width: 0.25, height: 0.25 }
+    }
+
+    fn spec(width: u32, height: u32, color: ColorMode) -> ImageSpec {
+        ImageSpec { width, height, color, mean: Vec::new(), std: Vec::new() }
+    }
+
+    #[test]
+    fn test_crops_resizes_and_normalizes_hud_regions() {
+        let regions = [region("portrait", 0.0, 0.0), region("minimap", 0.75, 0.75)];
+
+        let gray = ImageSpec { mean: vec![0.5], std: vec![0.5], ..spec(2, 2, ColorMode::Gray) };
+        let values = gray.preprocess(PNG, &regions[..1]).unwrap();
+        // The portrait is white, 1 after normalizing around 0.5
+        assert_eq!(values, vec![1.0; 4]);
+
+        let rgb = spec(1, 1, ColorMode::Rgb);
+        let values = rgb.preprocess(PNG, &regions).unwrap();
+        assert_eq!(values.len(), rgb.len(regions.len()));
+        assert_eq!(values, vec![1.0, 1.0, 1.0, 1.0, 0.0, 0.0]);
+
+        // The whole frame is read without regions
+        assert_eq!(spec(8, 6, ColorMode::Rgb).preprocess(PNG, &[]).unwrap().len(), 3 * 8 * 6);
+    }
+
+    #[test]
+    fn test_decodes_jpeg_and_rejects_other_inputs() {
+        let regions = [region("minimap", 0.75, 0.75)];
+        let rgb = spec(2, 2, ColorMode::Rgb);
+
+        let png = rgb.preprocess(PNG, &regions).unwrap();
+        let jpeg = rgb.preprocess(JPEG, &regions).unwrap();
+        // JPEG is lossy, but the regions read the same colors
+        assert!(png.iter().zip(&jpeg).all(|(png, jpeg)| (png - jpeg).abs() < 0.1), "{:?} {:?}", png, jpeg);
+
+        assert!(matches!(rgb.preprocess(b"Mage casts Fireball", &regions), Err(InferenceError::UnsupportedInput(_))));
+        assert!(matches!(rgb.preprocess(b"GIF89a\x01\x00\x01\x00", &regions), Err(InferenceError::UnsupportedInput(_))));
+        assert!(ImageSpec { mean: vec![0.5], ..spec(2, 2, ColorMode::Rgb) }.check().is_err());
+        assert!(spec(0, 2, ColorMode::Gray).check().is_err());
+    }
+}
@@ -0,0 +1,117 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use crate::inference::engine::InferenceError;
+
+/// A region of a game's HUD, as fractions of the frame so it fits every screen resolution.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct HudRegion {
+    pub name: String,
+    /// The left edge, from 0 at the left of the frame to 1 at its right.
+    pub x: f32,
+    /// The top edge, from 0 at the top of the frame to 1 at its bottom.
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl HudRegion {
+    /// Checks that the region is not empty and lies within the frame.
+    pub fn check(&self) -> Result<(), InferenceError> {
+        let inside = |start: f32, size: f32| start >= 0.0 && size > 0.0 && start + size <= 1.0;
+        if !inside(self.x, self.width) || !inside(self.y, self.height) {
+            return Err(InferenceError::Model(format!("HUD region `{}` does not lie within the frame", self.name)));
+        }
+        Ok(())
+    }
+
+    /// The region in pixels of a frame, at least one pixel wide and high.
+    ///
+    /// # Parameters
+    /// - `width`: The width of the frame in pixels.
+    /// - `height`: The height of the frame in pixels.
+    ///
+    /// # Returns
+    /// - `(u32, u32, u32, u32)`: The left edge, top edge, width and height in pixels.
+    pub fn pixels(&self, width: u32, height: u32) -> (u32, u32, u32, u32) {
+        let span = |start: f32, size: f32, total: u32| {
+            let first = ((start * total as f32).floor() as u32).min(total.saturating_sub(1));
+            let last = ((start + size) * total as f32).ceil() as u32;
+            (first, last.clamp(first + 1, total.max(first + 1)) - first)
+        };
+        let (x, region_width) = span(self.x, self.width, width);
+        let (y, region_height) = span(self.y, self.height, height);
+        (x, y, region_width, region_height)
+    }
+}
+
+/// The HUD regions a model reads in each game's frames.
+///
+/// ```json
+/// {
+///   "arena": [
+///     { "name": "portrait", "x": 0.0, "y": 0.0, "width": 0.25, "height": 0.25 },
+///     { "name": "minimap", "x": 0.75, "y": 0.75, "width": 0.25, "height": 0.25 }
+///   ]
+/// }
+/// ```
+///
+/// Frames of games without a layout are read whole.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(transparent)]
+pub struct HudLayouts {
+    pub games: BTreeMap<String, Vec<HudRegion>>,
+}
+
+impl HudLayouts {
+    /// Loads and checks HUD layouts.
+    ///
+    /// # Parameters
+    /// - `path`: The layouts file.
+    ///
+    /// # Returns
+    /// - `Result<HudLayouts, InferenceError>`: The layouts, or why they cannot be used.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, InferenceError> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path).map_err(|err| InferenceError::Model(format!("cannot read {}: {}", path.display(), err)))?;
+        let layouts: HudLayouts =
+            serde_json::from_str(&text).map_err(|err| InferenceError::Model(format!("invalid HUD layouts {}: {}", path.display(), err)))?;
+        layouts.check()?;
+        Ok(layouts)
+    }
+
+    /// Checks every region of every game.
+    pub fn check(&self) -> Result<(), InferenceError> {
+        self.games.values().flatten().try_for_each(HudRegion::check)
+    }
+
+    /// The regions of a game's frames, empty if the game has no layout.
+    pub fn regions(&self, game: &str) -> &[HudRegion] {
+        self.games.get(game).map(Vec::as_slice).unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn region(x: f32, y: f32, width: f32, height: f32) -> HudRegion {
+        HudRegion { name: "portrait".to_string(), x, y, width, height }
+    }
+
+    #[test]
+    fn test_regions_map_to_pixels_within_the_frame() {
+        assert_eq!(region(0.0, 0.0, 0.25, 0.25).pixels(64, 48), (0, 0, 16, 12));
+        assert_eq!(region(0.75, 0.75, 0.25, 0.25).pixels(64, 48), (48, 36, 16, 12));
+        // Regions smaller than a pixel still read one
+        assert_eq!(region(0.5, 0.5, 0.001, 0.001).pixels(10, 10), (5, 5, 1, 1));
+
+        assert!(region(0.8, 0.0, 0.25, 0.25).check().is_err());
+        assert!(region(0.0, 0.0, 0.0, 0.25).check().is_err());
+        let layouts = HudLayouts { games: BTreeMap::from([("arena".to_string(), vec![region(0.0, 0.0, 0.25, 0.25)])]) };
+        assert_eq!(layouts.regions("arena").len(), 1);
+        assert!(layouts.regions("chess").is_empty());
+    }
+}
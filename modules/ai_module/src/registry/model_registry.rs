@@ -0,0 +1,279 @@
+use ethers::types::H256;
+use json::envelope::observation_envelope::canonical_json;
+use json::input_data::input_reference::InputHashAlgorithm;
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashSet;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+use crate::inference::observation::{ModelInfo, ObservationField, OutputSchema};
+
+/// Why an observation's model is not accepted, or a registry cannot be used.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RegistryError {
+    /// The registry file cannot be read or is invalid.
+    Invalid(String),
+    /// No registered model has the observation's `aimodel` hash.
+    UnknownModel(String),
+    /// The observation's `aiversion` is not the version registered for its model.
+    VersionMismatch { id: String, registered: Version, declared: String },
+    Deprecated { id: String, version: Version },
+    UnsupportedGame { id: String, game: String },
+    /// An extracted value the model's output schema does not allow.
+    OutsideSchema { id: String, field: ObservationField, value: String },
+}
+
+impl fmt::Display for RegistryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RegistryError::Invalid(reason) => write!(f, "invalid model registry: {}", reason),
+            RegistryError::UnknownModel(model) => write!(f, "unknown model {}", model),
+            RegistryError::VersionMismatch { id, registered, declared } => {
+                write!(f, "model {} is registered as version {}, not {}", id, registered, declared)
+            }
+            RegistryError::Deprecated { id, version } => write!(f, "model {} {} is deprecated", id, version),
+            RegistryError::UnsupportedGame { id, game } => write!(f, "model {} does not support game `{}`", id, game),
+            RegistryError::OutsideSchema { id, field, value } => {
+                write!(f, "`{}` of model {} cannot be `{}`", field, id, value)
+            }
+        }
+    }
+}
+
+impl std::error::Error for RegistryError {}
+
+/// Models whose observations can be compared with each other: the same model id and a
+/// semver-compatible version, the same major version or, before 1.0.0, the same minor version.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ModelFamily {
+    pub id: String,
+    pub major: u64,
+    /// The minor version of 0.x models, 0 otherwise.
+    pub minor: u64,
+}
+
+impl fmt::Display for ModelFamily {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.major {
+            0 => write!(f, "{} 0.{}", self.id, self.minor),
+            major => write!(f, "{} {}.x", self.id, major),
+        }
+    }
+}
+
+/// A model nodes may extract observations with.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RegisteredModel {
+    /// The model's name, shared by all its versions.
+    pub id: String,
+    /// The hash observations carry in `aimodel`, the keccak256 hash of the model file.
+    pub hash: H256,
+    /// The version observations carry in `aiversion`.
+    pub version: Version,
+    /// The `game` values the model extracts observations for.
+    pub games: Vec<String>,
+    /// The values the model can extract for each field; fields missing here can take any value.
+    #[serde(default)]
+    pub schema: OutputSchema,
+    /// Deprecated models are kept to audit old rounds, their new observations are rejected.
+    #[serde(default)]
+    pub deprecated: bool,
+}
+
+impl RegisteredModel {
+    pub fn family(&self) -> ModelFamily {
+        let minor = if self.version.major == 0 { self.version.minor } else { 0 };
+        ModelFamily { id: self.id.clone(), major: self.version.major, minor }
+    }
+
+    /// Whether observations of the two models can be compared.
+    pub fn compatible_with(&self, other: &RegisteredModel) -> bool {
+        self.family() == other.family()
+    }
+}
+
+/// The registry file: every model nodes may use, identified by the hash of its model file.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct ModelRegistry {
+    pub models: Vec<RegisteredModel>,
+}
+
+/// Reads a text field of an observation, accepting the numbers older nodes wrote.
+fn text_field(observation: &Value, field: &str) -> Option<String> {
+    match &observation[field] {
+        Value::String(text) => Some(text.clone()),
+        Value::Number(number) => Some(number.to_string()),
+        _ => None,
+    }
+}
+
+impl ModelRegistry {
+    /// Checks a list of models and builds the registry.
+    ///
+    /// # Parameters
+    /// - `models`: The registered models.
+    ///
+    /// # Returns
+    /// - `Result<ModelRegistry, RegistryError>`: The registry, or why two entries conflict.
+    pub fn new(models: Vec<RegisteredModel>) -> Result<Self, RegistryError> {
+        let mut hashes = HashSet::new();
+        let mut versions = HashSet::new();
+        for model in &models {
+            if !hashes.insert(model.hash) {
+                return Err(RegistryError::Invalid(format!("model hash {:?} is registered twice", model.hash)));
+            }
+            if !versions.insert((model.id.clone(), model.version.clone())) {
+                return Err(RegistryError::Invalid(format!("model {} {} is registered twice", model.id, model.version)));
+            }
+        }
+        Ok(ModelRegistry { models })
+    }
+
+    /// Loads a registry file.
+    ///
+    /// # Parameters
+    /// - `path`: The registry JSON file, `{"models": [...]}`.
+    ///
+    /// # Returns
+    /// - `Result<ModelRegistry, RegistryError>`: The registry, or why it cannot be used.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, RegistryError> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path).map_err(|err| RegistryError::Invalid(format!("cannot read {}: {}", path.display(), err)))?;
+        let registry: ModelRegistry =
+            serde_json::from_str(&text).map_err(|err| RegistryError::Invalid(format!("{}: {}", path.display(), err)))?;
+        ModelRegistry::new(registry.models)
+    }
+
+    /// The keccak256 hash of the registry's canonical JSON, identifying this exact set of models.
+    pub fn digest(&self) -> H256 {
+        let registry = serde_json::to_value(self).unwrap_or_default();
+        InputHashAlgorithm::Keccak256.digest(canonical_json(&registry).as_bytes())
+    }
+
+    /// The model with this `aimodel` hash.
+    pub fn model(&self, hash: &H256) -> Option<&RegisteredModel> {
+        self.models.iter().find(|model| model.hash == *hash)
+    }
+
+    /// The registered model an engine runs, whether or not it is deprecated.
+    ///
+    /// # Parameters
+    /// - `info`: The engine's model.
+    ///
+    /// # Returns
+    /// - `Result<&RegisteredModel, RegistryError>`: The model, or why the engine's model is not registered.
+    pub fn resolve(&self, info: &ModelInfo) -> Result<&RegisteredModel, RegistryError> {
+        let model = info
+            .model
+            .parse::<H256>()
+            .ok()
+            .and_then(|hash| self.model(&hash))
+            .ok_or_else(|| RegistryError::UnknownModel(info.model.clone()))?;
+        if model.version.to_string() != info.version {
+            return Err(RegistryError::VersionMismatch {
+                id: model.id.clone(),
+                registered: model.version.clone(),
+                declared: info.version.clone(),
+            });
+        }
+        Ok(model)
+    }
+
+    /// Checks that an observation was extracted by a registered, current model of its game,
+    /// within the model's output schema.
+    ///
+    /// # Parameters
+    /// - `observation`: The observation.
+    ///
+    /// # Returns
+    /// - `Result<&RegisteredModel, RegistryError>`: The observation's model, or why it is rejected.
+    pub fn check(&self, observation: &Value) -> Result<&RegisteredModel, RegistryError> {
+        let info = ModelInfo {
+            model: text_field(observation, "aimodel").unwrap_or_default(),
+            version: text_field(observation, "aiversion").unwrap_or_default(),
+        };
+        let model = self.resolve(&info)?;
+        if model.deprecated {
+            return Err(RegistryError::Deprecated { id: model.id.clone(), version: model.version.clone() });
+        }
+        let game = text_field(observation, "game").unwrap_or_default();
+        if !model.games.contains(&game) {
+            return Err(RegistryError::UnsupportedGame { id: model.id.clone(), game });
+        }
+        for (field, values) in &model.schema {
+            let value = observation[field.name()].as_str().unwrap_or_default();
+            if !values.iter().any(|allowed| allowed == value) {
+                return Err(RegistryError::OutsideSchema { id: model.id.clone(), field: *field, value: value.to_string() });
+            }
+        }
+        Ok(model)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn registry() -> ModelRegistry {
+        serde_json::from_value(json!({
+            "models": [
+                {
+                    "id": "arena-classifier",
+                    "hash": format!("{:?}", H256::repeat_byte(1)),
+                    "version": "1.2.0",
+                    "games": ["arena"],
+                    "schema": { "character": ["Mage", "Knight"] }
+                },
+                { "id": "arena-classifier", "hash": format!("{:?}", H256::repeat_byte(2)), "version": "1.3.1", "games": ["arena"] },
+                { "id": "arena-classifier", "hash": format!("{:?}", H256::repeat_byte(3)), "version": "2.0.0", "games": ["arena"] },
+                { "id": "arena-classifier", "hash": format!("{:?}", H256::repeat_byte(4)), "version": "0.9.0", "games": ["arena"], "deprecated": true }
+            ]
+        }))
+        .unwrap()
+    }
+
+    fn observation(model: u8, version: &str, character: &str) -> Value {
+        json!({
+            "game": "arena",
+            "character": character,
+            "aimodel": format!("{:?}", H256::repeat_byte(model)),
+            "aiversion": version
+        })
+    }
+
+    #[test]
+    fn test_check_rejects_unknown_deprecated_or_out_of_schema_models() {
+        let registry = registry();
+
+        assert_eq!(registry.check(&observation(1, "1.2.0", "Mage")).unwrap().version.to_string(), "1.2.0");
+        assert!(matches!(registry.check(&observation(9, "1.2.0", "Mage")), Err(RegistryError::UnknownModel(_))));
+        assert!(matches!(registry.check(&observation(1, "1.2.1", "Mage")), Err(RegistryError::VersionMismatch { .. })));
+        assert!(matches!(registry.check(&observation(4, "0.9.0", "Mage")), Err(RegistryError::Deprecated { .. })));
+        assert!(matches!(registry.check(&observation(1, "1.2.0", "Dragon")), Err(RegistryError::OutsideSchema { .. })));
+        let mut other_game = observation(2, "1.3.1", "Dragon");
+        other_game["game"] = json!("chess");
+        assert!(matches!(registry.check(&other_game), Err(RegistryError::UnsupportedGame { .. })));
+        // The sample observations predate the registry
+        let sample: Value = serde_json::from_str(include_str!("../../../coordination_module/json/src/json_objects/json1.json")).unwrap();
+        assert!(matches!(registry.check(&sample), Err(RegistryError::UnknownModel(_))));
+    }
+
+    #[test]
+    fn test_only_semver_compatible_versions_are_compatible() {
+        let registry = registry();
+        let [v1_2, v1_3, v2, v0_9] = [1, 2, 3, 4].map(|byte| registry.model(&H256::repeat_byte(byte)).unwrap());
+
+        assert!(v1_2.compatible_with(v1_3));
+        assert!(!v1_3.compatible_with(v2));
+        assert!(!v0_9.compatible_with(v1_2));
+        assert_eq!(v1_3.family().to_string(), "arena-classifier 1.x");
+
+        let mut duplicated = registry.models.clone();
+        duplicated.push(duplicated[0].clone());
+        assert!(ModelRegistry::new(duplicated).is_err());
+    }
+}
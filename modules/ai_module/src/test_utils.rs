@@ -0,0 +1,87 @@
+use prost::Message;
+use tract_onnx::pb::tensor_proto::DataType;
+use tract_onnx::pb::tensor_shape_proto::{dimension, Dimension};
+use tract_onnx::pb::{type_proto, GraphProto, ModelProto, NodeProto, OperatorSetIdProto, TensorProto, TensorShapeProto, TypeProto, ValueInfoProto};
+
+use crate::inference::label_map::{FieldLabels, InputSpec, LabelMap};
+use crate::inference::observation::ObservationField;
+
+fn tensor_value(name: &str, shape: &[usize]) -> ValueInfoProto {
+    let dim = shape.iter().map(|size| Dimension { value: Some(dimension::Value::DimValue(*size as i64)), ..Default::default() }).collect();
+    ValueInfoProto {
+        name: name.to_string(),
+        r#type: Some(TypeProto {
+            value: Some(type_proto::Value::TensorType(type_proto::Tensor {
+                elem_type: DataType::Float as i32,
+                shape: Some(TensorShapeProto { dim }),
+            })),
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+}
+
+/// An ONNX model with one `[1, inputs]` input and one `MatMul` output per field, scoring the
+/// field's labels with `weights[field]`, a `[inputs, labels]` matrix in row-major order.
+///
+/// # Parameters
+/// - `inputs`: The size of the input.
+/// - `weights`: The weight matrix of each output, with the number of labels it scores.
+///
+/// # Returns
+/// - `Vec<u8>`: The model file.
+pub fn matmul_model(inputs: usize, weights: &[(Vec<f32>, usize)]) -> Vec<u8> {
+    let mut graph = GraphProto { name: "fixture".to_string(), input: vec![tensor_value("input", &[1, inputs])], ..Default::default() };
+    for (index, (matrix, labels)) in weights.iter().enumerate() {
+        let weight = format!("weight_{}", index);
+        let output = format!("output_{}", index);
+        graph.initializer.push(TensorProto {
+            name: weight.clone(),
+            dims: vec![inputs as i64, *labels as i64],
+            data_type: DataType::Float as i32,
+            float_data: matrix.clone(),
+            ..Default::default()
+        });
+        graph.node.push(NodeProto {
+            name: format!("scores_{}", index),
+            op_type: "MatMul".to_string(),
+            input: vec!["input".to_string(), weight],
+            output: vec![output.clone()],
+            ..Default::default()
+        });
+        graph.output.push(tensor_value(&output, &[1, *labels]));
+    }
+    let model = ModelProto {
+        ir_version: 7,
+        opset_import: vec![OperatorSetIdProto { domain: String::new(), version: 13 }],
+        producer_name: "ai_module tests".to_string(),
+        graph: Some(graph),
+        ..Default::default()
+    };
+    model.encode_to_vec()
+}
+
+/// A model reading a 4-byte input whose first byte picks the first label of every field and
+/// whose other bytes pick the second label, with its label map.
+pub fn two_label_model() -> (Vec<u8>, LabelMap) {
+    let picks = vec![1.0, 0.0, 0.0, 1.0, 0.0, 1.0, 0.0, 1.0];
+    let model = matmul_model(4, &vec![(picks, 2); 4]);
+    let values = [("Mage", "Knight"), ("Fireball", "Shield Bash"), ("Bridge", "Keep"), ("North", "South")];
+    let fields = ObservationField::ALL
+        .into_iter()
+        .zip(values)
+        .enumerate()
+        .map(|(output, (field, (first, second)))| FieldLabels {
+            field,
+            output,
+            labels: vec![first.to_string(), second.to_string()],
+            scores: Default::default(),
+        })
+        .collect();
+    let labels = LabelMap {
+        version: "1.2.0".parse().unwrap(),
+        input: InputSpec { shape: vec![1, 4], encoding: Default::default(), image: None },
+        fields,
+    };
+    (model, labels)
+}
@@ -0,0 +1,262 @@
+use ethers::abi::{ParamType, Token};
+use ethers::prelude::*;
+use ethers::types::{transaction::eip2718::TypedTransaction, Address};
+use ethers::utils::keccak256;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::encoding::encode::encode_function;
+
+/// The maximum number of hashes checked in a single round trip.
+pub const MAX_BATCH_SIZE: usize = 500;
+
+/// Checks which of many fingerprint hashes have already been appended to the blockchain.
+///
+/// Each chunk of up to `MAX_BATCH_SIZE` hashes costs a single round trip: one `aggregate3` call
+/// when a Multicall3 contract is configured, otherwise one JSON-RPC batch of `eth_call`s.
+///
+/// # Parameters
+/// - `client`: The ether client connected to the blockchain. No signer is needed.
+/// - `contract_address`: The address of the smart contract.
+/// - `multicall_address`: The address of the Multicall3 contract, if the network has one.
+/// - `fingerprints`: The fingerprint hashes to be checked.
+///
+/// # Returns
+/// - `Result<HashMap<String, bool>, Box<dyn std::error::Error>>`: Whether each hash is already appended, keyed by hash.
+pub async fn check_fingerprints<M: Middleware<Provider = Http> + 'static>(
+    client: Arc<M>,
+    contract_address: Address,
+    multicall_address: Option<Address>,
+    fingerprints: &[String],
+) -> Result<HashMap<String, bool>, Box<dyn std::error::Error>> {
+    let mut appended = HashMap::with_capacity(fingerprints.len());
+
+    for chunk in fingerprints.chunks(MAX_BATCH_SIZE) {
+        let results = match multicall_address {
+            Some(multicall_address) => check_with_multicall(&client, contract_address, multicall_address, chunk).await?,
+            None => check_with_rpc_batch(&client, contract_address, chunk).await?,
+        };
+        appended.extend(chunk.iter().cloned().zip(results));
+    }
+
+    Ok(appended)
+}
+
+/// Checks a chunk of hashes with a single Multicall3 `aggregate3` call.
+async fn check_with_multicall<M: Middleware<Provider = Http> + 'static>(
+    client: &M,
+    contract_address: Address,
+    multicall_address: Address,
+    fingerprints: &[String],
+) -> Result<Vec<bool>, Box<dyn std::error::Error>> {
+    let mut calls = Vec::with_capacity(fingerprints.len());
+    for fingerprint in fingerprints {
+        calls.push(Token::Tuple(vec![
+            Token::Address(contract_address),
+            Token::Bool(false),
+            Token::Bytes(encode_function(fingerprint, "isHashAppended(bytes32)")?),
+        ]));
+    }
+
+    let mut data = keccak256("aggregate3((address,bool,bytes)[])".as_bytes())[0..4].to_vec();
+    data.extend(ethers::abi::encode(&[Token::Array(calls)]));
+
+    let call = client
+        .call(&TypedTransaction::Legacy(TransactionRequest::new().to(multicall_address).data(data)), None)
+        .await?;
+    let results = ethers::abi::decode(
+        &[ParamType::Array(Box::new(ParamType::Tuple(vec![ParamType::Bool, ParamType::Bytes])))],
+        &call,
+    )?
+    .pop()
+    .and_then(|token| token.into_array())
+    .ok_or("Unexpected aggregate3 response")?;
+
+    results
+        .into_iter()
+        .map(|result| {
+            let return_data = result
+                .into_tuple()
+                .and_then(|mut fields| fields.pop())
+                .and_then(|field| field.into_bytes())
+                .ok_or("Unexpected aggregate3 result")?;
+            decode_bool(&return_data)
+        })
+        .collect()
+}
+
+/// Checks a chunk of hashes with a single JSON-RPC batch request.
+async fn check_with_rpc_batch<M: Middleware<Provider = Http> + 'static>(
+    client: &M,
+    contract_address: Address,
+    fingerprints: &[String],
+) -> Result<Vec<bool>, Box<dyn std::error::Error>> {
+    let mut requests = Vec::with_capacity(fingerprints.len());
+    for (id, fingerprint) in fingerprints.iter().enumerate() {
+        let data = encode_function(fingerprint, "isHashAppended(bytes32)")?;
+        requests.push(json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": "eth_call",
+            "params": [{ "to": contract_address, "data": Bytes::from(data) }, "latest"],
+        }));
+    }
+
+    let responses = match reqwest::Client::new()
+        .post(client.provider().url().clone())
+        .json(&requests)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?
+    {
+        Value::Array(responses) => responses,
+        // A node that rejects the whole batch answers with a single response, usually an error
+        Value::Object(response) => match response.get("error") {
+            Some(error) => return Err(format!("JSON-RPC batch failed: {}", error).into()),
+            None => return Err("JSON-RPC batch answered with a single response, the node may not support batches".into()),
+        },
+        other => return Err(format!("Unexpected JSON-RPC batch response: {}", other).into()),
+    };
+
+    // Batch responses may come back in any order
+    let mut results = vec![None; fingerprints.len()];
+    for response in responses {
+        let id = response["id"].as_u64().ok_or("Batch response without id")? as usize;
+        let fingerprint = fingerprints.get(id).ok_or("Batch response with unknown id")?;
+        if let Some(error) = response.get("error") {
+            return Err(format!("eth_call for {} failed: {}", fingerprint, error).into());
+        }
+        let result: Bytes = serde_json::from_value(response["result"].clone())?;
+        results[id] = Some(decode_bool(&result)?);
+    }
+
+    results
+        .into_iter()
+        .map(|result| result.ok_or_else(|| "Missing batch response".into()))
+        .collect()
+}
+
+/// Decodes the ABI-encoded return value of `isHashAppended`.
+fn decode_bool(data: &[u8]) -> Result<bool, Box<dyn std::error::Error>> {
+    ethers::abi::decode(&[ParamType::Bool], data)?
+        .pop()
+        .and_then(|token| token.into_bool())
+        .ok_or_else(|| "Unexpected isHashAppended result".into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::signer::fingerprint_signer::FingerprintSigner;
+    use crate::FingerprintClient;
+    use crate::test_utils::spawn_stub_server;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    const APPENDED: &str = "0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa";
+    const MISSING: &str = "0xbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb";
+
+    fn client(url: &str) -> Arc<FingerprintClient> {
+        let provider = Provider::<Http>::try_from(url).unwrap();
+        let wallet: LocalWallet = "4c0883a69102937d6231471b5dbb6204fe5129617082796e8e1a1e3b7a1e7e3e".parse().unwrap();
+        Arc::new(SignerMiddleware::new(provider, FingerprintSigner::Local(wallet)))
+    }
+
+    /// Answers `isHashAppended` calls for the hashes in `data`, as the fingerprint contract would.
+    fn is_appended(data: &[u8]) -> bool {
+        data.ends_with(&hex::decode(&APPENDED[2..]).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_check_fingerprints_with_rpc_batch() {
+        let requests = Arc::new(AtomicUsize::new(0));
+        let counter = requests.clone();
+        let url = spawn_stub_server(move |_, body| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            let batch: Vec<Value> = serde_json::from_str(body).unwrap();
+            let responses: Vec<Value> = batch
+                .iter()
+                .rev()
+                .map(|request| {
+                    let data: Bytes = serde_json::from_value(request["params"][0]["data"].clone()).unwrap();
+                    let result = Bytes::from(ethers::abi::encode(&[Token::Bool(is_appended(&data))]));
+                    json!({ "jsonrpc": "2.0", "id": request["id"], "result": result })
+                })
+                .collect();
+            serde_json::to_string(&responses).unwrap()
+        })
+        .await;
+
+        let hashes = vec![APPENDED.to_string(), MISSING.to_string()];
+        let appended = check_fingerprints(client(&url), Address::repeat_byte(1), None, &hashes).await.unwrap();
+
+        assert!(appended[APPENDED]);
+        assert!(!appended[MISSING]);
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_check_fingerprints_rejects_malformed_batch_responses() {
+        let out_of_range = spawn_stub_server(|_, _| {
+            json!([{ "jsonrpc": "2.0", "id": 7, "error": { "code": -32000, "message": "boom" } }]).to_string()
+        })
+        .await;
+        let single_error = spawn_stub_server(|_, _| {
+            json!({ "jsonrpc": "2.0", "id": null, "error": { "code": -32600, "message": "batches disabled" } }).to_string()
+        })
+        .await;
+        let hashes = vec![APPENDED.to_string()];
+
+        let err = check_fingerprints(client(&out_of_range), Address::repeat_byte(1), None, &hashes).await.unwrap_err();
+        assert_eq!(err.to_string(), "Batch response with unknown id");
+
+        let err = check_fingerprints(client(&single_error), Address::repeat_byte(1), None, &hashes).await.unwrap_err();
+        assert!(err.to_string().contains("batches disabled"));
+    }
+
+    #[tokio::test]
+    async fn test_check_fingerprints_with_multicall() {
+        let requests = Arc::new(AtomicUsize::new(0));
+        let counter = requests.clone();
+        let url = spawn_stub_server(move |_, body| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            let request: Value = serde_json::from_str(body).unwrap();
+            let data: Bytes = serde_json::from_value(request["params"][0]["data"].clone()).unwrap();
+            let calls = ethers::abi::decode(
+                &[ParamType::Array(Box::new(ParamType::Tuple(vec![
+                    ParamType::Address,
+                    ParamType::Bool,
+                    ParamType::Bytes,
+                ])))],
+                &data[4..],
+            )
+            .unwrap()
+            .pop()
+            .unwrap()
+            .into_array()
+            .unwrap();
+            let results: Vec<Token> = calls
+                .into_iter()
+                .map(|call| {
+                    let call_data = call.into_tuple().unwrap().pop().unwrap().into_bytes().unwrap();
+                    let result = ethers::abi::encode(&[Token::Bool(is_appended(&call_data))]);
+                    Token::Tuple(vec![Token::Bool(true), Token::Bytes(result)])
+                })
+                .collect();
+            let result = Bytes::from(ethers::abi::encode(&[Token::Array(results)]));
+            json!({ "jsonrpc": "2.0", "id": request["id"], "result": result }).to_string()
+        })
+        .await;
+
+        let hashes = vec![MISSING.to_string(), APPENDED.to_string()];
+        let appended = check_fingerprints(client(&url), Address::repeat_byte(1), Some(Address::repeat_byte(2)), &hashes)
+            .await
+            .unwrap();
+
+        assert!(appended[APPENDED]);
+        assert!(!appended[MISSING]);
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+    }
+}
@@ -2,6 +2,7 @@ use ethers::prelude::*;
 use ethers::types::{transaction::eip2718::TypedTransaction, Address};
 use std::sync::Arc;
 use crate::encoding::encode::encode_function;
+use crate::FingerprintClient;
 
 /// Checks if a given fingerprint hash has already been appended to the blockchain.
 ///
@@ -13,7 +14,7 @@ use crate::encoding::encode::encode_function;
 /// # Returns
 /// - `Result<bool, Box<dyn std::error::Error>>`: Returns `true` if the hash is already appended, otherwise `false`.
 pub async fn check_fingerprint(
-    client: Arc<SignerMiddleware<Provider<Http>, LocalWallet>>,
+    client: Arc<FingerprintClient>,
     contract_address: Address,
     fingerprint: &str,
 ) -> Result<bool, Box<dyn std::error::Error>> {
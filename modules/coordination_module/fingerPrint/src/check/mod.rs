@@ -1 +1,2 @@
+pub mod check_batch;
 pub mod check_hash;
\ No newline at end of file
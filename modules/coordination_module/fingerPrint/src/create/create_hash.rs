@@ -9,10 +9,10 @@ use serde_json;
 ///
 /// # Returns
 /// - `Result<String, Box<dyn std::error::Error>>`: Returns the created fingerprint hash as a string.
+#[tracing::instrument(name = "hash", skip_all)]
 pub fn create_fingerprint_hash(fingerprint: &crate::Fingerprint) -> Result<String, Box<dyn std::error::Error>> {
     let json = serde_json::to_string(fingerprint)?;
-    let hash = keccak256(json.as_bytes());
-    println!("Fingerprint hash:");
-    println!("\x1b[32;1m0x{}\x1b[0m", hex::encode(hash));
-    Ok(format!("0x{}", hex::encode(hash)))
+    let hash = format!("0x{}", hex::encode(keccak256(json.as_bytes())));
+    tracing::info!(hash = %hash, "fingerprint hash computed");
+    Ok(hash)
 }
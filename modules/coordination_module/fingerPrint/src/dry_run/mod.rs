@@ -0,0 +1 @@
+pub mod submission_plan;
@@ -0,0 +1,176 @@
+use ethers::prelude::*;
+use ethers::types::transaction::eip2718::TypedTransaction;
+use serde::Serialize;
+use std::sync::Arc;
+
+use crate::check::check_batch::check_fingerprints;
+use crate::create::create_hash::create_fingerprint_hash;
+use crate::encoding::encode::encode_function;
+use crate::network::profile::NetworkProfile;
+use crate::Fingerprint;
+
+/// Everything a fingerprint submission would send, computed without sending it.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct SubmissionPlan {
+    pub fingerprint: Fingerprint,
+    /// The exact JSON that is hashed into the fingerprint hash.
+    pub fingerprint_json: String,
+    pub hash: String,
+    /// The calldata of the `appendData(bytes32)` transaction.
+    pub calldata: Bytes,
+    /// What the configured node reported, if one was reachable.
+    pub estimate: Option<SubmissionEstimate>,
+}
+
+/// What a node reports about a planned submission.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct SubmissionEstimate {
+    pub contract_address: Address,
+    /// The account the transaction would be sent from, if a signer could be loaded.
+    pub from: Option<Address>,
+    /// Whether the contract already reports the hash as appended, in which case nothing would be sent.
+    pub already_appended: bool,
+    /// The gas the transaction would use. `None` when the hash is already appended.
+    pub gas: Option<U256>,
+    pub gas_price: U256,
+    /// `gas * gas_price`, in wei.
+    pub cost: Option<U256>,
+}
+
+/// Computes the fingerprint hash and calldata a submission would send.
+///
+/// # Parameters
+/// - `fingerprint`: The Fingerprint object that would be submitted.
+///
+/// # Returns
+/// - `Result<SubmissionPlan, Box<dyn std::error::Error>>`: The plan, without an estimate.
+pub fn plan_submission(fingerprint: &Fingerprint) -> Result<SubmissionPlan, Box<dyn std::error::Error>> {
+    let fingerprint_json = serde_json::to_string(fingerprint)?;
+    let hash = create_fingerprint_hash(fingerprint)?;
+    let calldata = Bytes::from(encode_function(&hash, "appendData(bytes32)")?);
+
+    Ok(SubmissionPlan { fingerprint: fingerprint.clone(), fingerprint_json, hash, calldata, estimate: None })
+}
+
+/// Asks a node what a planned submission would cost, without sending it.
+///
+/// # Parameters
+/// - `plan`: The submission plan.
+/// - `client`: The ether client connected to the blockchain. No signer is needed.
+/// - `profile`: The validated network profile.
+/// - `from`: The account the transaction would be sent from, if known.
+///
+/// # Returns
+/// - `Result<SubmissionEstimate, Box<dyn std::error::Error>>`: What the node reported.
+pub async fn estimate_submission<M: Middleware<Provider = Http> + 'static>(
+    plan: &SubmissionPlan,
+    client: Arc<M>,
+    profile: &NetworkProfile,
+    from: Option<Address>,
+) -> Result<SubmissionEstimate, Box<dyn std::error::Error>> {
+    let contract_address = profile.contracts.fingerprint_proxy;
+    let already_appended = check_fingerprints(
+        client.clone(),
+        contract_address,
+        profile.contracts.multicall,
+        std::slice::from_ref(&plan.hash),
+    )
+    .await?[&plan.hash];
+    let gas_price = client.get_gas_price().await?;
+
+    let gas = if already_appended {
+        None
+    } else {
+        let mut tx = TransactionRequest::new().to(contract_address).data(plan.calldata.clone());
+        if let Some(from) = from {
+            tx = tx.from(from);
+        }
+        Some(client.estimate_gas(&TypedTransaction::Legacy(tx), None).await?)
+    };
+
+    Ok(SubmissionEstimate {
+        contract_address,
+        from,
+        already_appended,
+        gas,
+        gas_price,
+        cost: gas.map(|gas| gas * gas_price),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::profile::{ConfirmationSettings, ContractAddresses, IndexerSettings};
+    use crate::test_utils::spawn_stub_server;
+    use ethers::abi::Token;
+    use ethers::utils::keccak256;
+    use serde_json::{json, Value};
+
+    fn fingerprint() -> Fingerprint {
+        Fingerprint {
+            gamer: "kqiyqnihok".to_string(),
+            strikes: 0,
+            place: "hnntcgutwg".to_string(),
+            weapon: "rbmonlrehd".to_string(),
+            place2: "ynyxqjdmim".to_string(),
+        }
+    }
+
+    fn profile() -> NetworkProfile {
+        NetworkProfile {
+            chain_id: 300,
+            rpc_urls: vec![],
+            contracts: ContractAddresses { fingerprint_proxy: Address::repeat_byte(1), multicall: None },
+            confirmations: ConfirmationSettings::default(),
+            indexer: IndexerSettings::default(),
+            signer: None,
+        }
+    }
+
+    /// Starts a node that reports nothing as appended, 50_000 gas and a gas price of 2 wei.
+    async fn provider() -> Arc<Provider<Http>> {
+        let url = spawn_stub_server(|_, body| {
+            let answer = |request: &Value| {
+                let result = match request["method"].as_str().unwrap() {
+                    "eth_call" => json!(Bytes::from(ethers::abi::encode(&[Token::Bool(false)]))),
+                    "eth_estimateGas" => json!(U256::from(50_000)),
+                    "eth_gasPrice" => json!(U256::from(2)),
+                    method => panic!("unexpected method {}", method),
+                };
+                json!({ "jsonrpc": "2.0", "id": request["id"], "result": result })
+            };
+            match serde_json::from_str::<Value>(body).unwrap() {
+                Value::Array(batch) => Value::Array(batch.iter().map(answer).collect()).to_string(),
+                request => answer(&request).to_string(),
+            }
+        })
+        .await;
+        Arc::new(Provider::<Http>::try_from(url.as_str()).unwrap())
+    }
+
+    #[test]
+    fn test_plan_submission_encodes_append_calldata() {
+        let plan = plan_submission(&fingerprint()).unwrap();
+
+        assert_eq!(plan.hash, create_fingerprint_hash(&fingerprint()).unwrap());
+        assert_eq!(format!("0x{}", hex::encode(keccak256(plan.fingerprint_json.as_bytes()))), plan.hash);
+        assert_eq!(&plan.calldata[..4], &keccak256("appendData(bytes32)".as_bytes())[..4]);
+        assert_eq!(hex::encode(&plan.calldata[4..]), plan.hash[2..]);
+        assert_eq!(plan.estimate, None);
+    }
+
+    #[tokio::test]
+    async fn test_estimate_submission_reports_gas_and_cost() {
+        let plan = plan_submission(&fingerprint()).unwrap();
+
+        let estimate = estimate_submission(&plan, provider().await, &profile(), Some(Address::repeat_byte(2)))
+            .await
+            .unwrap();
+
+        assert!(!estimate.already_appended);
+        assert_eq!(estimate.gas, Some(U256::from(50_000)));
+        assert_eq!(estimate.cost, Some(U256::from(100_000)));
+        assert_eq!(estimate.from, Some(Address::repeat_byte(2)));
+    }
+}
@@ -0,0 +1,326 @@
+use ethers::types::H256;
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use crate::indexer::index_store::IndexStore;
+use crate::indexer::log_source::{AppendLog, LogSource, ReorgedRange};
+use crate::network::profile::IndexerSettings;
+
+/// How often a block range is read again when it is reorged while it is read.
+const MAX_RANGE_ATTEMPTS: usize = 5;
+
+/// What a single indexer pass did.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SyncReport {
+    /// The block the store was rolled back to because of a reorg, if one was detected.
+    /// `Some(None)` means the whole index was discarded.
+    pub rolled_back_to: Option<Option<u64>>,
+    /// The last block scanned, if any.
+    pub scanned_to: Option<u64>,
+    /// The number of appends added to the store.
+    pub new_records: usize,
+}
+
+/// Indexes fingerprint appends from a log source into a local store.
+pub struct FingerprintIndexer<S: LogSource> {
+    source: S,
+    store: IndexStore,
+    settings: IndexerSettings,
+}
+
+impl<S: LogSource> FingerprintIndexer<S> {
+    /// Creates an indexer that resumes from an existing store.
+    ///
+    /// # Parameters
+    /// - `source`: Where to read fingerprint appends from.
+    /// - `store`: The store to resume from. Pass `IndexStore::default()` to index from `settings.start_block`.
+    /// - `settings`: The indexer settings of the network profile.
+    pub fn new(source: S, store: IndexStore, settings: IndexerSettings) -> Self {
+        Self { source, store, settings }
+    }
+
+    /// Returns the indexed data.
+    pub fn store(&self) -> &IndexStore {
+        &self.store
+    }
+
+    /// Brings the store up to date with the latest block, undoing any blocks that were reorged out.
+    ///
+    /// # Returns
+    /// - `Result<SyncReport, Box<dyn std::error::Error + Send + Sync>>`: What the pass did.
+    pub async fn sync_once(&mut self) -> Result<SyncReport, Box<dyn std::error::Error + Send + Sync>> {
+        let mut report = SyncReport { rolled_back_to: self.detect_reorg().await?, ..SyncReport::default() };
+        if let Some(block) = report.rolled_back_to {
+            self.store.rollback_to(block);
+        }
+
+        let latest = self.source.latest_block().await?;
+        let batch_size = self.settings.batch_size.max(1);
+        let reorg_window_start = latest.saturating_sub(self.settings.reorg_depth.saturating_sub(1));
+        let mut from = self.store.last_block.map(|block| block + 1).unwrap_or(self.settings.start_block);
+
+        while from <= latest {
+            let to = (from + batch_size - 1).min(latest);
+            let (appends, block_hashes) = self.scan_range(from, to, reorg_window_start).await?;
+            report.new_records += appends.len();
+            self.store.records.extend(appends);
+            self.store.block_hashes.extend(block_hashes);
+
+            self.store.last_block = Some(to);
+            report.scanned_to = Some(to);
+            from = to + 1;
+        }
+
+        self.store.block_hashes.retain(|number, _| *number >= reorg_window_start);
+        Ok(report)
+    }
+
+    /// Keeps the store in sync with the chain, saving it after every pass. Never returns unless an error occurs.
+    ///
+    /// # Parameters
+    /// - `store_path`: Where to persist the store.
+    /// - `poll_interval`: How long to wait between passes.
+    pub async fn follow(&mut self, store_path: &str, poll_interval: Duration) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        loop {
+            let report = self.sync_once().await?;
+            if let Some(block) = report.rolled_back_to {
+                tracing::warn!(block = ?block, "reorg detected, rolled back");
+            }
+            if report.new_records > 0 {
+                tracing::info!(appends = report.new_records, block = ?report.scanned_to, "indexed fingerprint appends");
+            }
+            self.store.save(store_path).map_err(|err| err.to_string())?;
+            tokio::time::sleep(poll_interval).await;
+        }
+    }
+
+    /// Reads the appends of a block range, with the hashes of its blocks close enough to the tip to
+    /// still be reorged.
+    ///
+    /// The hashes are read before the logs, and every log must carry the hash read for its block,
+    /// so a reorg between the two reads cannot file logs under another block's hash. A range that
+    /// is reorged while it is read, or whose logs the node marks `removed`, is read again.
+    ///
+    /// # Returns
+    /// - `Result<(Vec<AppendLog>, BTreeMap<u64, H256>), Box<dyn std::error::Error + Send + Sync>>`:
+    ///   The appends and the block hashes, or an error if the range kept changing.
+    async fn scan_range(
+        &self,
+        from: u64,
+        to: u64,
+        reorg_window_start: u64,
+    ) -> Result<(Vec<AppendLog>, BTreeMap<u64, H256>), Box<dyn std::error::Error + Send + Sync>> {
+        for _ in 0..MAX_RANGE_ATTEMPTS {
+            let mut block_hashes = BTreeMap::new();
+            for number in from.max(reorg_window_start)..=to {
+                if let Some(hash) = self.source.block_hash(number).await? {
+                    block_hashes.insert(number, hash);
+                }
+            }
+
+            let appends = match self.source.append_logs(from, to).await {
+                Ok(appends) => appends,
+                Err(err) if err.downcast_ref::<ReorgedRange>().is_some() => {
+                    tracing::warn!(from, to, "logs removed while reading, reading the blocks again");
+                    continue;
+                }
+                Err(err) => return Err(err),
+            };
+            let consistent = appends.iter().all(|append| {
+                append.block_number < reorg_window_start || block_hashes.get(&append.block_number) == Some(&append.block_hash)
+            });
+            if consistent {
+                return Ok((appends, block_hashes));
+            }
+            tracing::warn!(from, to, "blocks reorged while reading their logs, reading them again");
+        }
+        Err(Box::new(ReorgedRange { from, to }))
+    }
+
+    /// Compares the stored block hashes against the chain.
+    ///
+    /// # Returns
+    /// - `Ok(None)` if the stored blocks are still canonical, otherwise `Ok(Some(block))` with the last block to keep.
+    async fn detect_reorg(&self) -> Result<Option<Option<u64>>, Box<dyn std::error::Error + Send + Sync>> {
+        let mut reorged = false;
+        for (number, hash) in self.store.block_hashes.iter().rev() {
+            if self.source.block_hash(*number).await? == Some(*hash) {
+                return Ok(if reorged { Some(Some(*number)) } else { None });
+            }
+            reorged = true;
+        }
+
+        if !reorged {
+            return Ok(None);
+        }
+
+        // The reorg is deeper than the blocks we kept hashes for, so rescan from before the oldest of them
+        let oldest = *self.store.block_hashes.keys().next().unwrap_or(&0);
+        Ok(Some(oldest.checked_sub(1).filter(|block| *block >= self.settings.start_block)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indexer::log_source::{RecordedBlock, RecordedLogSource};
+    use async_trait::async_trait;
+    use ethers::types::Address;
+    use std::sync::{Arc, Mutex};
+
+    /// A recorded chain that tests can reorg while the indexer holds it.
+    #[derive(Clone, Default)]
+    struct SharedChain(Arc<Mutex<RecordedLogSource>>);
+
+    #[async_trait]
+    impl LogSource for SharedChain {
+        async fn latest_block(&self) -> Result<u64, Box<dyn std::error::Error + Send + Sync>> {
+            let chain = self.0.lock().unwrap().clone();
+            chain.latest_block().await
+        }
+
+        async fn block_hash(&self, number: u64) -> Result<Option<H256>, Box<dyn std::error::Error + Send + Sync>> {
+            let chain = self.0.lock().unwrap().clone();
+            chain.block_hash(number).await
+        }
+
+        async fn append_logs(&self, from: u64, to: u64) -> Result<Vec<AppendLog>, Box<dyn std::error::Error + Send + Sync>> {
+            let chain = self.0.lock().unwrap().clone();
+            chain.append_logs(from, to).await
+        }
+    }
+
+    /// A chain that switches to a fork the first time its logs are read, after the indexer has
+    /// read the block hashes, optionally failing that read as a node marking logs `removed` does.
+    struct ForkingChain {
+        chain: SharedChain,
+        fork: Mutex<Option<Vec<RecordedBlock>>>,
+        removed: bool,
+    }
+
+    #[async_trait]
+    impl LogSource for ForkingChain {
+        async fn latest_block(&self) -> Result<u64, Box<dyn std::error::Error + Send + Sync>> {
+            self.chain.latest_block().await
+        }
+
+        async fn block_hash(&self, number: u64) -> Result<Option<H256>, Box<dyn std::error::Error + Send + Sync>> {
+            self.chain.block_hash(number).await
+        }
+
+        async fn append_logs(&self, from: u64, to: u64) -> Result<Vec<AppendLog>, Box<dyn std::error::Error + Send + Sync>> {
+            if let Some(fork) = self.fork.lock().unwrap().take() {
+                self.chain.0.lock().unwrap().blocks = fork;
+                if self.removed {
+                    return Err(Box::new(ReorgedRange { from, to }));
+                }
+            }
+            self.chain.append_logs(from, to).await
+        }
+    }
+
+    /// Builds a block whose hash is derived from `fork`, with one append per `(hash, sender)` pair.
+    fn block(number: u64, fork: u8, appends: &[(u8, u8)]) -> RecordedBlock {
+        let block_hash = H256::from_low_u64_be(number * 256 + fork as u64);
+        RecordedBlock {
+            number,
+            hash: block_hash,
+            appends: appends
+                .iter()
+                .enumerate()
+                .map(|(i, (hash, sender))| AppendLog {
+                    hash: H256::repeat_byte(*hash),
+                    block_number: number,
+                    block_hash,
+                    tx_hash: H256::from_low_u64_be(number * 1000 + i as u64),
+                    log_index: i as u64,
+                    sender: Address::repeat_byte(*sender),
+                })
+                .collect(),
+        }
+    }
+
+    fn settings() -> IndexerSettings {
+        IndexerSettings { start_block: 1, batch_size: 2, reorg_depth: 3, ..IndexerSettings::default() }
+    }
+
+    #[tokio::test]
+    async fn test_indexer_answers_queries() {
+        let chain = RecordedLogSource {
+            blocks: vec![block(1, 0, &[(0xaa, 1)]), block(2, 0, &[]), block(3, 0, &[(0xbb, 2), (0xcc, 1)])],
+        };
+        let mut indexer = FingerprintIndexer::new(chain, IndexStore::default(), settings());
+
+        let report = indexer.sync_once().await.unwrap();
+
+        assert_eq!(report.new_records, 3);
+        assert_eq!(report.scanned_to, Some(3));
+        let store = indexer.store();
+        assert_eq!(store.since_block(2).len(), 2);
+        assert_eq!(store.who_appended(&H256::repeat_byte(0xbb)), vec![Address::repeat_byte(2)]);
+        assert!(store.who_appended(&H256::repeat_byte(0xdd)).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_indexer_rolls_back_reorged_blocks() {
+        let chain = SharedChain::default();
+        chain.0.lock().unwrap().blocks = vec![
+            block(1, 0, &[(0xaa, 1)]),
+            block(2, 0, &[]),
+            block(3, 0, &[(0xbb, 2)]),
+            block(4, 0, &[(0xcc, 3)]),
+        ];
+        let mut indexer = FingerprintIndexer::new(chain.clone(), IndexStore::default(), settings());
+        indexer.sync_once().await.unwrap();
+
+        // Blocks 3 and 4 are replaced by a fork that appends different fingerprints
+        chain.0.lock().unwrap().blocks.truncate(2);
+        chain.0.lock().unwrap().blocks.extend([block(3, 1, &[(0xdd, 4)]), block(4, 1, &[]), block(5, 1, &[])]);
+        let report = indexer.sync_once().await.unwrap();
+
+        assert_eq!(report.rolled_back_to, Some(Some(2)));
+        let store = indexer.store();
+        assert!(store.appends_of(&H256::repeat_byte(0xbb)).is_empty());
+        assert!(store.appends_of(&H256::repeat_byte(0xcc)).is_empty());
+        assert_eq!(store.who_appended(&H256::repeat_byte(0xdd)), vec![Address::repeat_byte(4)]);
+        assert_eq!(store.last_block, Some(5));
+    }
+
+    #[tokio::test]
+    async fn test_indexer_reads_a_range_again_when_it_is_reorged_while_read() {
+        for removed in [false, true] {
+            let chain = SharedChain::default();
+            chain.0.lock().unwrap().blocks = vec![block(1, 0, &[(0xaa, 1)]), block(2, 0, &[(0xbb, 2)])];
+            let fork = vec![block(1, 0, &[(0xaa, 1)]), block(2, 1, &[(0xcc, 3)])];
+            let source = ForkingChain { chain, fork: Mutex::new(Some(fork)), removed };
+            let mut indexer = FingerprintIndexer::new(source, IndexStore::default(), settings());
+
+            indexer.sync_once().await.unwrap();
+
+            let store = indexer.store();
+            assert!(store.appends_of(&H256::repeat_byte(0xbb)).is_empty());
+            assert_eq!(store.appends_of(&H256::repeat_byte(0xcc))[0].block_hash, block(2, 1, &[]).hash);
+            assert_eq!(store.block_hashes[&2], block(2, 1, &[]).hash);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_indexer_resumes_from_saved_store() {
+        let dir = tempfile::tempdir().unwrap();
+        let store_path = dir.path().join("index.json");
+        let store_path = store_path.to_str().unwrap();
+        let chain = SharedChain::default();
+        chain.0.lock().unwrap().blocks = vec![block(1, 0, &[(0xaa, 1)])];
+
+        let mut indexer = FingerprintIndexer::new(chain.clone(), IndexStore::default(), settings());
+        indexer.sync_once().await.unwrap();
+        indexer.store().save(store_path).unwrap();
+
+        chain.0.lock().unwrap().blocks.push(block(2, 0, &[(0xbb, 2)]));
+        let mut resumed = FingerprintIndexer::new(chain, IndexStore::load(store_path).unwrap(), settings());
+        let report = resumed.sync_once().await.unwrap();
+
+        assert_eq!(report.new_records, 1);
+        assert_eq!(resumed.store().records.len(), 2);
+    }
+}
@@ -0,0 +1,87 @@
+use ethers::types::{Address, H256};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use crate::indexer::log_source::AppendLog;
+
+/// The local store of indexed fingerprint appends, persisted as a JSON file.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct IndexStore {
+    /// The last block that has been fully scanned, if any.
+    pub last_block: Option<u64>,
+    /// Hashes of the most recently scanned blocks, used to detect reorgs.
+    pub block_hashes: BTreeMap<u64, H256>,
+    /// Every indexed append, in chain order.
+    pub records: Vec<AppendLog>,
+}
+
+impl IndexStore {
+    /// Loads the store from a file, or returns an empty store if the file does not exist yet.
+    ///
+    /// # Parameters
+    /// - `path`: The path of the store file.
+    ///
+    /// # Returns
+    /// - `Result<IndexStore, Box<dyn std::error::Error>>`: The loaded store.
+    pub fn load(path: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        if !Path::new(path).exists() {
+            return Ok(Self::default());
+        }
+        Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    /// Writes the store to a file, replacing it atomically.
+    ///
+    /// # Parameters
+    /// - `path`: The path of the store file.
+    pub fn save(&self, path: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let tmp_path = format!("{}.tmp", path);
+        fs::write(&tmp_path, serde_json::to_string_pretty(self)?)?;
+        fs::rename(&tmp_path, path)?;
+        Ok(())
+    }
+
+    /// Drops everything recorded after `block`, so that it can be scanned again.
+    ///
+    /// # Parameters
+    /// - `block`: The last block to keep, or `None` to clear the store.
+    pub fn rollback_to(&mut self, block: Option<u64>) {
+        match block {
+            Some(block) => {
+                self.records.retain(|record| record.block_number <= block);
+                self.block_hashes.retain(|number, _| *number <= block);
+            }
+            None => {
+                self.records.clear();
+                self.block_hashes.clear();
+            }
+        }
+        self.last_block = block;
+    }
+
+    /// Returns all fingerprints appended at or after a block.
+    ///
+    /// # Parameters
+    /// - `block`: The first block to include.
+    pub fn since_block(&self, block: u64) -> Vec<&AppendLog> {
+        self.records.iter().filter(|record| record.block_number >= block).collect()
+    }
+
+    /// Returns the appends of a given fingerprint hash.
+    ///
+    /// # Parameters
+    /// - `hash`: The fingerprint hash.
+    pub fn appends_of(&self, hash: &H256) -> Vec<&AppendLog> {
+        self.records.iter().filter(|record| &record.hash == hash).collect()
+    }
+
+    /// Returns the addresses that appended a given fingerprint hash.
+    ///
+    /// # Parameters
+    /// - `hash`: The fingerprint hash.
+    pub fn who_appended(&self, hash: &H256) -> Vec<Address> {
+        self.appends_of(hash).iter().map(|record| record.sender).collect()
+    }
+}
@@ -0,0 +1,173 @@
+use async_trait::async_trait;
+use ethers::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+
+/// A fingerprint append observed on chain.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AppendLog {
+    pub hash: H256,
+    pub block_number: u64,
+    pub block_hash: H256,
+    pub tx_hash: H256,
+    pub log_index: u64,
+    pub sender: Address,
+}
+
+/// Returned by a log source when the logs of a block range changed while they were read, e.g.
+/// because the node marked some of them `removed` after a reorg. The range should be read again.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReorgedRange {
+    pub from: u64,
+    pub to: u64,
+}
+
+impl fmt::Display for ReorgedRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "blocks {} to {} were reorged while their logs were read", self.from, self.to)
+    }
+}
+
+impl std::error::Error for ReorgedRange {}
+
+/// A source of fingerprint append events, e.g. a live node or a recording of one.
+#[async_trait]
+pub trait LogSource: Send + Sync {
+    /// Returns the number of the latest block.
+    async fn latest_block(&self) -> Result<u64, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Returns the hash of a block, or `None` if the block does not exist.
+    async fn block_hash(&self, number: u64) -> Result<Option<H256>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Returns the fingerprint appends in the inclusive block range `[from, to]`, in chain order,
+    /// each with the hash of the block its log was read from.
+    ///
+    /// Fails with `ReorgedRange` if the range was reorged while it was read.
+    async fn append_logs(&self, from: u64, to: u64) -> Result<Vec<AppendLog>, Box<dyn std::error::Error + Send + Sync>>;
+}
+
+/// Reads fingerprint appends from the proxy contract's logs through a JSON-RPC node.
+pub struct ProviderLogSource {
+    provider: Provider<Http>,
+    contract_address: Address,
+    event_signature: String,
+}
+
+impl ProviderLogSource {
+    /// Creates a log source for a contract.
+    ///
+    /// # Parameters
+    /// - `provider`: The provider connected to the node.
+    /// - `contract_address`: The address of the fingerprint proxy contract.
+    /// - `event_signature`: The signature of the event emitted on append, e.g. `DataAppended(bytes32)`.
+    pub fn new(provider: Provider<Http>, contract_address: Address, event_signature: &str) -> Self {
+        Self { provider, contract_address, event_signature: event_signature.to_string() }
+    }
+}
+
+#[async_trait]
+impl LogSource for ProviderLogSource {
+    async fn latest_block(&self) -> Result<u64, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(self.provider.get_block_number().await?.as_u64())
+    }
+
+    async fn block_hash(&self, number: u64) -> Result<Option<H256>, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(self.provider.get_block(number).await?.and_then(|block| block.hash))
+    }
+
+    async fn append_logs(&self, from: u64, to: u64) -> Result<Vec<AppendLog>, Box<dyn std::error::Error + Send + Sync>> {
+        let filter = Filter::new()
+            .address(self.contract_address)
+            .event(&self.event_signature)
+            .from_block(from)
+            .to_block(to);
+        let logs = self.provider.get_logs(&filter).await?;
+
+        // Logs carry no sender, so look it up once per transaction
+        let mut senders: HashMap<H256, Address> = HashMap::new();
+        let mut appends = Vec::with_capacity(logs.len());
+        for log in logs {
+            if log.removed == Some(true) {
+                return Err(Box::new(ReorgedRange { from, to }));
+            }
+            let tx_hash = log.transaction_hash.ok_or("log without transaction hash")?;
+            let sender = match senders.get(&tx_hash) {
+                Some(sender) => *sender,
+                None => {
+                    let tx = self.provider.get_transaction(tx_hash).await?.ok_or("transaction not found")?;
+                    senders.insert(tx_hash, tx.from);
+                    tx.from
+                }
+            };
+            appends.push(AppendLog {
+                hash: appended_hash(&log).ok_or("append log carries no hash")?,
+                block_number: log.block_number.ok_or("log without block number")?.as_u64(),
+                block_hash: log.block_hash.ok_or("log without block hash")?,
+                tx_hash,
+                log_index: log.log_index.unwrap_or_default().as_u64(),
+                sender,
+            });
+        }
+
+        Ok(appends)
+    }
+}
+
+/// Extracts the appended hash from a log, whether the event indexes it or not.
+fn appended_hash(log: &Log) -> Option<H256> {
+    match log.topics.get(1) {
+        Some(topic) => Some(*topic),
+        None if log.data.len() >= 32 => Some(H256::from_slice(&log.data[..32])),
+        None => None,
+    }
+}
+
+/// A block in a recorded chain.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RecordedBlock {
+    pub number: u64,
+    pub hash: H256,
+    #[serde(default)]
+    pub appends: Vec<AppendLog>,
+}
+
+/// A log source backed by recorded blocks, used to replay captured chain history and in tests.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct RecordedLogSource {
+    pub blocks: Vec<RecordedBlock>,
+}
+
+impl RecordedLogSource {
+    /// Loads recorded blocks from a JSON file.
+    ///
+    /// # Parameters
+    /// - `path`: The path of a JSON file holding `{"blocks": [...]}`.
+    ///
+    /// # Returns
+    /// - `Result<RecordedLogSource, Box<dyn std::error::Error>>`: The recorded log source.
+    pub fn from_file(path: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+    }
+}
+
+#[async_trait]
+impl LogSource for RecordedLogSource {
+    async fn latest_block(&self) -> Result<u64, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(self.blocks.last().map(|block| block.number).unwrap_or_default())
+    }
+
+    async fn block_hash(&self, number: u64) -> Result<Option<H256>, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(self.blocks.iter().find(|block| block.number == number).map(|block| block.hash))
+    }
+
+    async fn append_logs(&self, from: u64, to: u64) -> Result<Vec<AppendLog>, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(self
+            .blocks
+            .iter()
+            .filter(|block| block.number >= from && block.number <= to)
+            .flat_map(|block| block.appends.iter().cloned())
+            .collect())
+    }
+}
@@ -0,0 +1,3 @@
+pub mod fingerprint_indexer;
+pub mod index_store;
+pub mod log_source;
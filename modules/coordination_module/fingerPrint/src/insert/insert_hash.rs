@@ -1,45 +1,80 @@
 use ethers::prelude::*;
 use ethers::types::{transaction::eip2718::TypedTransaction, Address};
+use std::collections::HashMap;
 use std::sync::Arc;
-use std::process;
+use tracing::Instrument;
+use crate::check::check_batch::check_fingerprints;
 use crate::encoding::encode::encode_function;
+use crate::FingerprintClient;
 
-/// Inserts a given fingerprint hash into the blockchain.
+/// Inserts the given fingerprint hashes that are not on the blockchain yet.
+///
+/// Which hashes are already appended is checked up front in one batch, see `check_fingerprints`.
 ///
 /// # Parameters
 /// - `client`: The ether client connected to the blockchain.
 /// - `contract_address`: The address of the smart contract.
-/// - `fingerprint`: The fingerprint hash to be inserted.
+/// - `multicall_address`: The address of the Multicall3 contract, if the network has one.
+/// - `fingerprints`: The fingerprint hashes to be inserted.
+/// - `confirmations`: The number of blocks to wait for before each transaction counts as confirmed.
 ///
 /// # Returns
-/// - `Result<(), Box<dyn std::error::Error>>`: Returns `Ok(())` if the insertion is successful.
-pub async fn insert_fingerprint(
-    client: Arc<SignerMiddleware<Provider<Http>, LocalWallet>>,
+/// - `Result<HashMap<String, Option<TransactionReceipt>>, Box<dyn std::error::Error>>`: For each hash, the receipt of
+///   the transaction that appended it, or `None` if it was already appended.
+pub async fn insert_fingerprints(
+    client: Arc<FingerprintClient>,
     contract_address: Address,
-    fingerprint: &str,
-) -> Result<(), Box<dyn std::error::Error>> {
-   // Define the function signature for checking if a hash is appended
-   let function_signature_check = "isHashAppended(bytes32)";
-   let data_check = encode_function(fingerprint, function_signature_check)?;
+    multicall_address: Option<Address>,
+    fingerprints: &[String],
+    confirmations: usize,
+) -> Result<HashMap<String, Option<TransactionReceipt>>, Box<dyn std::error::Error>> {
+    let appended = check_fingerprints(client.clone(), contract_address, multicall_address, fingerprints).await?;
 
-    // Call the smart contract to check if the hash is appended
-    let call = client.call(&TypedTransaction::Legacy(TransactionRequest::new().to(contract_address).data(data_check).from(client.address())), None).await?;
-    let is_appended: bool = ethers::abi::decode(&[ethers::abi::ParamType::Bool], &call)?.pop().unwrap().into_bool().unwrap();
-    if is_appended {
-        println!("\x1b[31;1mFingerprint Hash already inserted. Operation stopped\x1b[0m");
-        process::exit(0);    
+    let mut inserted = HashMap::with_capacity(fingerprints.len());
+    for fingerprint in fingerprints {
+        let receipt = if appended.get(fingerprint).copied().unwrap_or(false) {
+            None
+        } else {
+            Some(append_fingerprint(client.clone(), contract_address, fingerprint, confirmations).await?)
+        };
+        inserted.insert(fingerprint.clone(), receipt);
     }
 
+    Ok(inserted)
+}
+
+/// Sends the transaction appending a fingerprint hash and waits for it to be confirmed.
+///
+/// # Parameters
+/// - `client`: The ether client connected to the blockchain.
+/// - `contract_address`: The address of the smart contract.
+/// - `fingerprint`: The fingerprint hash to be appended.
+/// - `confirmations`: The number of blocks to wait for before the transaction counts as confirmed.
+///
+/// # Returns
+/// - `Result<TransactionReceipt, Box<dyn std::error::Error>>`: The receipt of the confirmed transaction.
+async fn append_fingerprint(
+    client: Arc<FingerprintClient>,
+    contract_address: Address,
+    fingerprint: &str,
+    confirmations: usize,
+) -> Result<TransactionReceipt, Box<dyn std::error::Error>> {
     // Define the function signature for appending a hash
     let function_signature = "appendData(bytes32)";
     let data = encode_function(fingerprint, function_signature)?;
 
     // Send the transaction to append the hash
-    let tx = client.send_transaction(TypedTransaction::Legacy(TransactionRequest::new().to(contract_address).data(data).from(client.address())), None).await?;
-    println!("Transaction sent:");
-    println!("\x1b[32;1m{:?}\x1b[0m", tx.tx_hash());
-    tx.await?.ok_or("Failed to fetch transaction receipt")?;
-    println!("\x1b[32;1mTransaction confirmed\x1b[0m");
+    let tx = client
+        .send_transaction(TypedTransaction::Legacy(TransactionRequest::new().to(contract_address).data(data).from(client.address())), None)
+        .instrument(tracing::info_span!("submit", hash = %fingerprint))
+        .await?;
+    let tx_hash = tx.tx_hash();
+    tracing::info!(hash = %fingerprint, tx = ?tx_hash, "transaction sent");
+
+    let confirm_span = tracing::info_span!("confirm", hash = %fingerprint, tx = ?tx_hash, confirmations);
+    let receipt = tx.confirmations(confirmations).instrument(confirm_span.clone()).await?;
+    let receipt = receipt.ok_or("Failed to fetch transaction receipt")?;
+    confirm_span.in_scope(|| tracing::info!(gas_used = ?receipt.gas_used, "transaction confirmed"));
 
-    Ok(())
+    Ok(receipt)
 }
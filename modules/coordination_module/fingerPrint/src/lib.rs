@@ -1,18 +1,31 @@
 pub mod insert;
 pub mod check;
+pub mod dry_run;
 pub mod create;
 pub mod encoding;
+pub mod indexer;
+pub mod network;
+pub mod signer;
+pub mod verify;
+
+#[cfg(test)]
+mod test_utils;
 
 use ethers::prelude::*;
 use ethers::providers::{Http, Provider};
-use ethers::signers::LocalWallet;
-use ethers::types::Address;
-use std::env;
 use std::sync::Arc;
 use serde::{Serialize, Deserialize};
+use serde_json::Value;
+use network::profile::NetworkProfile;
+use signer::fingerprint_signer::FingerprintSigner;
+
+/// The client used to read from and submit to the fingerprint contract.
+pub type FingerprintClient = SignerMiddleware<Provider<Http>, FingerprintSigner>;
 
 /// Represents a Fingerprint object.
-#[derive(Serialize, Deserialize, Debug)]
+///
+/// The field order is part of the fingerprint hash, see `create::create_hash::create_fingerprint_hash`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Fingerprint {
     pub gamer: String,
     pub strikes: u64,
@@ -21,41 +34,87 @@ pub struct Fingerprint {
     pub place2: String,
 }
 
+impl Fingerprint {
+    /// Builds the Fingerprint of an observation chosen by the JSON comparator.
+    ///
+    /// # Parameters
+    /// - `observation`: The observation JSON object.
+    ///
+    /// # Returns
+    /// - `Fingerprint`: The fingerprint, with missing fields left empty.
+    pub fn from_observation(observation: &Value) -> Self {
+        Fingerprint {
+            gamer: observation["character"].as_str().unwrap_or_default().to_string(),
+            strikes: 0,
+            place: observation["place"].as_str().unwrap_or_default().to_string(),
+            weapon: observation["ability"].as_str().unwrap_or_default().to_string(),
+            place2: observation["place2"].as_str().unwrap_or_default().to_string(),
+        }
+    }
+}
+
+/// The outcome of submitting a fingerprint.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct FingerprintSubmission {
+    /// The fingerprint hash.
+    pub hash: String,
+    /// The transaction that appended the hash, or `None` if it was already appended.
+    pub tx_hash: Option<H256>,
+    /// Whether the contract reports the hash as appended after the submission.
+    pub appended: bool,
+    /// The gas the transaction used, if one was sent.
+    pub gas_used: Option<U256>,
+    /// What the transaction cost, `gas_used * effective_gas_price`, in wei.
+    pub cost: Option<U256>,
+}
+
 /// Runs the entire fingerprinting process.
 ///
 /// # Parameters
 /// - `fingerprint`: The Fingerprint object to be processed.
+/// - `client`: The client connected to the profile's network, see `network::connect::connect_client`.
+/// - `profile`: The validated network profile.
 ///
 /// # Returns
-/// - `Result<(), Box<dyn std::error::Error>>`: Returns `Ok(())` if the process is successful.
-pub async fn run_fingerprint(fingerprint: Fingerprint) -> Result<(), Box<dyn std::error::Error>> {
-    dotenv::dotenv().ok();
-
-    let zksync_url = env::var("ZKSYNC_URL")?;
-    let chain_id: u64 = env::var("CHAIN_ID")?.parse()?;
-    let provider = Provider::<Http>::try_from(zksync_url)?;
-    let contract_address_str = env::var("FINGERPRINT_PROXY_SC")?;
-    let private_key = env::var("ZKSYNC_SEPOLIA_PRIVATE_KEY")?;
-
-    let contract_address: Address = contract_address_str.parse()?;
-    let wallet: LocalWallet = private_key.parse()?;
-    let wallet = wallet.with_chain_id(chain_id);
-    let client = Arc::new(SignerMiddleware::new(provider, wallet));
+/// - `Result<FingerprintSubmission, Box<dyn std::error::Error>>`: The submitted hash and transaction.
+pub async fn run_fingerprint(
+    fingerprint: Fingerprint,
+    client: Arc<FingerprintClient>,
+    profile: &NetworkProfile,
+) -> Result<FingerprintSubmission, Box<dyn std::error::Error>> {
+    let contract_address = profile.contracts.fingerprint_proxy;
 
     let fingerprint_hash = create::create_hash::create_fingerprint_hash(&fingerprint)?;
 
-    insert::insert_hash::insert_fingerprint(client.clone(), contract_address, &fingerprint_hash).await?;
+    let inserted = insert::insert_hash::insert_fingerprints(
+        client.clone(),
+        contract_address,
+        profile.contracts.multicall,
+        std::slice::from_ref(&fingerprint_hash),
+        profile.confirmations.blocks,
+    )
+    .await?;
+    let receipt = inserted.get(&fingerprint_hash).cloned().flatten();
+    if receipt.is_none() {
+        tracing::warn!(hash = %fingerprint_hash, "fingerprint hash already appended");
+    }
+
     let is_appended = check::check_hash::check_fingerprint(client.clone(), contract_address, &fingerprint_hash).await?;
-    println!("Fingerprint appended: ");
-    println!("\x1b[32;1m{}\x1b[0m", is_appended);
+    tracing::info!(hash = %fingerprint_hash, appended = is_appended, "fingerprint hash checked");
 
-    Ok(())
+    let tx_hash = receipt.as_ref().map(|receipt| receipt.transaction_hash);
+    let gas_used = receipt.as_ref().and_then(|receipt| receipt.gas_used);
+    let cost = receipt
+        .as_ref()
+        .and_then(|receipt| Some(receipt.gas_used? * receipt.effective_gas_price?));
+
+    Ok(FingerprintSubmission { hash: fingerprint_hash, tx_hash, appended: is_appended, gas_used, cost })
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use ethers::types::H256;
+    use ethers::types::{Address, H256};
     use std::error::Error;
 
     fn mock_create_fingerprint_hash(_fingerprint: &Fingerprint) -> Result<H256, Box<dyn Error>> {
@@ -63,15 +122,17 @@ mod tests {
     }
 
     async fn mock_insert_fingerprint(
-        _client: Arc<SignerMiddleware<Provider<Http>, LocalWallet>>,
+        _client: Arc<FingerprintClient>,
         _contract_address: Address,
+        _multicall_address: Option<Address>,
         _fingerprint_hash: &H256,
+        _confirmations: usize,
     ) -> Result<(), Box<dyn Error>> {
         Ok(())
     }
 
     async fn mock_check_fingerprint(
-        _client: Arc<SignerMiddleware<Provider<Http>, LocalWallet>>,
+        _client: Arc<FingerprintClient>,
         _contract_address: Address,
         _fingerprint_hash: &H256,
     ) -> Result<bool, Box<dyn Error>> {
@@ -88,21 +149,16 @@ mod tests {
             place2: "test_place2".to_string(),
         };
 
-        env::set_var("ZKSYNC_URL", "http://localhost:8545");
-        env::set_var("CHAIN_ID", "1");
-        env::set_var("FINGERPRINT_PROXY_SC", "0000000000000000000000000000000000000000"); // Removed '0x'
-        env::set_var("ZKSYNC_SEPOLIA_PRIVATE_KEY", "4c0883a69102937d6231471b5dbb6204fe5129617082796e8e1a1e3b7a1e7e3e"); // Valid private key
-
         let provider = Provider::<Http>::try_from("http://localhost:8545").unwrap();
         let wallet: LocalWallet = "4c0883a69102937d6231471b5dbb6204fe5129617082796e8e1a1e3b7a1e7e3e".parse().unwrap(); // Valid private key
         let wallet = wallet.with_chain_id(1u64); // Convert i32 to u64
-        let client = Arc::new(SignerMiddleware::new(provider, wallet));
+        let client = Arc::new(SignerMiddleware::new(provider, FingerprintSigner::Local(wallet)));
 
         let contract_address: Address = "0000000000000000000000000000000000000000".parse().unwrap(); // Removed '0x'
 
         let fingerprint_hash = mock_create_fingerprint_hash(&fingerprint).unwrap();
 
-        mock_insert_fingerprint(client.clone(), contract_address, &fingerprint_hash).await.unwrap();
+        mock_insert_fingerprint(client.clone(), contract_address, None, &fingerprint_hash, 1).await.unwrap();
 
         let is_appended = mock_check_fingerprint(client.clone(), contract_address, &fingerprint_hash).await.unwrap();
 
@@ -0,0 +1,120 @@
+use ethers::prelude::*;
+use ethers::providers::{Http, Provider};
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::network::profile::NetworkProfile;
+use crate::signer::signer_config::SignerConfig;
+use crate::FingerprintClient;
+
+/// Returned by `connect_provider` when a node reports a different chain id than its profile.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChainIdMismatch {
+    pub rpc_url: String,
+    pub reported: U256,
+    pub expected: u64,
+}
+
+impl fmt::Display for ChainIdMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "RPC {} reports chain id {}, but the profile expects {}",
+            self.rpc_url, self.reported, self.expected
+        )
+    }
+}
+
+impl std::error::Error for ChainIdMismatch {}
+
+/// Connects to the first reachable RPC endpoint of a profile.
+///
+/// Endpoints are tried in order. An endpoint that cannot be reached is skipped, but one that
+/// reports a different chain id than the profile is a configuration error and stops the search.
+///
+/// # Parameters
+/// - `profile`: The validated network profile.
+///
+/// # Returns
+/// - `Result<Provider<Http>, Box<dyn std::error::Error>>`: A provider for the first healthy endpoint.
+pub async fn connect_provider(profile: &NetworkProfile) -> Result<Provider<Http>, Box<dyn std::error::Error>> {
+    let mut failures = Vec::new();
+
+    for rpc_url in &profile.rpc_urls {
+        let provider = Provider::<Http>::try_from(rpc_url.as_str())?
+            .interval(Duration::from_millis(profile.confirmations.poll_interval_ms));
+
+        match provider.get_chainid().await {
+            Ok(chain_id) if chain_id == U256::from(profile.chain_id) => return Ok(provider),
+            Ok(chain_id) => {
+                return Err(Box::new(ChainIdMismatch {
+                    rpc_url: rpc_url.clone(),
+                    reported: chain_id,
+                    expected: profile.chain_id,
+                }))
+            }
+            Err(err) => failures.push(format!("{}: {}", rpc_url, err)),
+        }
+    }
+
+    Err(format!("No reachable RPC endpoint ({})", failures.join("; ")).into())
+}
+
+/// Connects to a profile's network and loads its signer.
+///
+/// # Parameters
+/// - `profile`: The validated network profile.
+///
+/// # Returns
+/// - `Result<Arc<FingerprintClient>, Box<dyn std::error::Error>>`: The client used to talk to the fingerprint contract.
+pub async fn connect_client(profile: &NetworkProfile) -> Result<Arc<FingerprintClient>, Box<dyn std::error::Error>> {
+    let provider = connect_provider(profile).await?;
+    let signer_config = match &profile.signer {
+        Some(signer_config) => signer_config.clone(),
+        None => SignerConfig::from_env()?,
+    };
+    let signer = signer_config.load(profile.chain_id).await?;
+    Ok(Arc::new(SignerMiddleware::new(provider, signer)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::profile::{ConfirmationSettings, ContractAddresses, IndexerSettings};
+    use crate::test_utils::spawn_stub_rpc;
+    use serde_json::json;
+
+    fn profile(chain_id: u64, rpc_urls: Vec<String>) -> NetworkProfile {
+        NetworkProfile {
+            chain_id,
+            rpc_urls,
+            contracts: ContractAddresses { fingerprint_proxy: Address::repeat_byte(1), multicall: None },
+            confirmations: ConfirmationSettings::default(),
+            indexer: IndexerSettings::default(),
+            signer: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_connect_falls_back_to_next_endpoint() {
+        let rpc_url = spawn_stub_rpc(|_, _| json!("0x12c")).await;
+        let profile = profile(300, vec!["http://127.0.0.1:1".to_string(), rpc_url]);
+
+        let provider = connect_provider(&profile).await.unwrap();
+
+        assert_eq!(provider.get_chainid().await.unwrap(), U256::from(300));
+    }
+
+    #[tokio::test]
+    async fn test_connect_rejects_chain_id_mismatch() {
+        let rpc_url = spawn_stub_rpc(|_, _| json!("0x1")).await;
+        let profile = profile(300, vec![rpc_url]);
+
+        let err = connect_provider(&profile).await.unwrap_err();
+
+        let mismatch = err.downcast_ref::<ChainIdMismatch>().unwrap();
+        assert_eq!(mismatch.reported, U256::from(1));
+        assert!(err.to_string().contains("reports chain id 1"));
+    }
+}
@@ -0,0 +1,260 @@
+use ethers::types::Address;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use url::Url;
+
+use crate::signer::signer_config::SignerConfig;
+
+/// The network configuration file used when none is given.
+pub const DEFAULT_NETWORK_CONFIG: &str = "networks.toml";
+
+/// The network profile used when none is given.
+pub const DEFAULT_NETWORK_PROFILE: &str = "sepolia";
+
+/// The environment variable overriding the selected profile's `contracts.fingerprint_proxy`.
+pub const FINGERPRINT_PROXY_ENV: &str = "FINGERPRINT_PROXY_SC";
+
+/// The set of named network profiles read from the network configuration file.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct NetworkConfig {
+    pub profiles: BTreeMap<String, NetworkProfile>,
+}
+
+/// Everything needed to talk to the fingerprint contract on one network.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct NetworkProfile {
+    /// The chain id the RPC endpoints must report.
+    pub chain_id: u64,
+    /// RPC endpoints in order of preference. Later entries are only used when earlier ones are unreachable.
+    pub rpc_urls: Vec<String>,
+    pub contracts: ContractAddresses,
+    #[serde(default)]
+    pub confirmations: ConfirmationSettings,
+    #[serde(default)]
+    pub indexer: IndexerSettings,
+    /// The signer backend for this network. When absent, the signer is read from the environment.
+    #[serde(default)]
+    pub signer: Option<SignerConfig>,
+}
+
+/// The contract addresses deployed on a network.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ContractAddresses {
+    pub fingerprint_proxy: Address,
+    /// The Multicall3 contract used to batch existence checks. Without it, checks use JSON-RPC batches.
+    #[serde(default)]
+    pub multicall: Option<Address>,
+}
+
+/// How long to wait for fingerprint transactions to be confirmed.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ConfirmationSettings {
+    /// The number of blocks a transaction must be buried under before it counts as confirmed.
+    pub blocks: usize,
+    /// How often to poll the node for receipts and new blocks, in milliseconds.
+    pub poll_interval_ms: u64,
+}
+
+impl Default for ConfirmationSettings {
+    fn default() -> Self {
+        Self { blocks: 1, poll_interval_ms: 1000 }
+    }
+}
+
+/// Where and how the fingerprint indexer scans the proxy contract's logs.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct IndexerSettings {
+    /// The first block to scan, usually the block the proxy contract was deployed in.
+    pub start_block: u64,
+    /// The signature of the event the proxy contract emits when a hash is appended.
+    pub event_signature: String,
+    /// How many blocks below the tip can still be reorged.
+    pub reorg_depth: u64,
+    /// The maximum number of blocks requested in a single `eth_getLogs` call.
+    pub batch_size: u64,
+    /// Where the indexed appends are stored.
+    pub store_path: String,
+}
+
+impl Default for IndexerSettings {
+    fn default() -> Self {
+        Self {
+            start_block: 0,
+            event_signature: "DataAppended(bytes32)".to_string(),
+            reorg_depth: 64,
+            batch_size: 1000,
+            store_path: "fingerprint_index.json".to_string(),
+        }
+    }
+}
+
+impl NetworkConfig {
+    /// Loads the network configuration from a TOML file.
+    ///
+    /// # Parameters
+    /// - `path`: The path of the network configuration file.
+    ///
+    /// # Returns
+    /// - `Result<NetworkConfig, Box<dyn std::error::Error>>`: The parsed configuration.
+    pub fn load(path: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let contents = fs::read_to_string(path)
+            .map_err(|err| format!("Failed to read network config {}: {}", path, err))?;
+        Self::parse(&contents)
+    }
+
+    /// Parses the network configuration from a TOML string.
+    ///
+    /// # Parameters
+    /// - `contents`: The TOML document.
+    ///
+    /// # Returns
+    /// - `Result<NetworkConfig, Box<dyn std::error::Error>>`: The parsed configuration.
+    pub fn parse(contents: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        Ok(toml::from_str(contents)?)
+    }
+
+    /// Looks up a profile by name, applies the fingerprint proxy override and validates it.
+    ///
+    /// # Parameters
+    /// - `name`: The profile name, e.g. `local`, `sepolia` or `mainnet`.
+    /// - `proxy_override`: The fingerprint proxy address replacing the profile's, usually read from
+    ///   `FINGERPRINT_PROXY_SC`. A blank value leaves the profile's address in place.
+    ///
+    /// # Returns
+    /// - `Result<NetworkProfile, Box<dyn std::error::Error>>`: The validated profile.
+    pub fn profile(&self, name: &str, proxy_override: Option<&str>) -> Result<NetworkProfile, Box<dyn std::error::Error>> {
+        let profile = self.profiles.get(name).ok_or_else(|| {
+            let known: Vec<&str> = self.profiles.keys().map(|k| k.as_str()).collect();
+            format!("Unknown network profile '{}' (known profiles: {})", name, known.join(", "))
+        })?;
+        let mut profile = profile.clone();
+        if let Some(proxy) = proxy_override.filter(|proxy| !proxy.trim().is_empty()) {
+            profile.override_proxy(proxy)?;
+        }
+        profile
+            .validate()
+            .map_err(|err| format!("Invalid network profile '{}': {}", name, err))?;
+        Ok(profile)
+    }
+}
+
+impl NetworkProfile {
+    /// Replaces the fingerprint proxy address of the profile.
+    ///
+    /// # Parameters
+    /// - `proxy`: The address, with or without a `0x` prefix.
+    ///
+    /// # Returns
+    /// - `Result<(), String>`: `Ok(())`, or an error if `proxy` is not an address.
+    pub fn override_proxy(&mut self, proxy: &str) -> Result<(), String> {
+        self.contracts.fingerprint_proxy = proxy
+            .trim()
+            .parse()
+            .map_err(|err| format!("{} '{}' is not an address: {}", FINGERPRINT_PROXY_ENV, proxy, err))?;
+        Ok(())
+    }
+
+    /// Checks that the profile is complete and internally consistent.
+    ///
+    /// # Returns
+    /// - `Result<(), String>`: `Ok(())` if the profile is usable, otherwise a description of the first problem found.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.chain_id == 0 {
+            return Err("chain_id must not be 0".to_string());
+        }
+        if self.rpc_urls.is_empty() {
+            return Err("rpc_urls must list at least one endpoint".to_string());
+        }
+        for rpc_url in &self.rpc_urls {
+            let parsed = Url::parse(rpc_url).map_err(|err| format!("rpc url '{}' is invalid: {}", rpc_url, err))?;
+            if parsed.scheme() != "http" && parsed.scheme() != "https" {
+                return Err(format!("rpc url '{}' must use http or https", rpc_url));
+            }
+        }
+        if self.contracts.fingerprint_proxy.is_zero() {
+            return Err(format!("contracts.fingerprint_proxy is not set, fill it in or set {}", FINGERPRINT_PROXY_ENV));
+        }
+        if self.confirmations.blocks == 0 {
+            return Err("confirmations.blocks must be at least 1".to_string());
+        }
+        if self.confirmations.poll_interval_ms == 0 {
+            return Err("confirmations.poll_interval_ms must not be 0".to_string());
+        }
+        if self.indexer.batch_size == 0 {
+            return Err("indexer.batch_size must be at least 1".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"
+        [profiles.local]
+        chain_id = 270
+        rpc_urls = ["http://localhost:3050"]
+        contracts = { fingerprint_proxy = "0x1111111111111111111111111111111111111111" }
+
+        [profiles.sepolia]
+        chain_id = 300
+        rpc_urls = ["https://sepolia.era.zksync.dev", "https://backup.example.org"]
+        contracts = { fingerprint_proxy = "0x0000000000000000000000000000000000000000" }
+        confirmations = { blocks = 3, poll_interval_ms = 500 }
+    "#;
+
+    #[test]
+    fn test_profile_defaults_confirmations() {
+        let config = NetworkConfig::parse(CONFIG).unwrap();
+
+        let profile = config.profile("local", None).unwrap();
+
+        assert_eq!(profile.chain_id, 270);
+        assert_eq!(profile.confirmations, ConfirmationSettings::default());
+        assert_eq!(profile.signer, None);
+    }
+
+    #[test]
+    fn test_profile_rejects_unset_contract() {
+        let config = NetworkConfig::parse(CONFIG).unwrap();
+
+        let err = config.profile("sepolia", None).unwrap_err().to_string();
+
+        assert!(err.contains("contracts.fingerprint_proxy is not set"));
+    }
+
+    #[test]
+    fn test_proxy_override_sets_the_unset_contract() {
+        let config = NetworkConfig::parse(CONFIG).unwrap();
+
+        let profile = config.profile("sepolia", Some("2222222222222222222222222222222222222222")).unwrap();
+
+        assert_eq!(profile.contracts.fingerprint_proxy, Address::repeat_byte(0x22));
+        // A blank override leaves the profile's address in place
+        assert!(config.profile("sepolia", Some(" ")).is_err());
+        assert!(config.profile("sepolia", Some("your_contract_address_here")).is_err());
+    }
+
+    #[test]
+    fn test_unknown_profile_lists_known_profiles() {
+        let config = NetworkConfig::parse(CONFIG).unwrap();
+
+        let err = config.profile("mainnet", None).unwrap_err().to_string();
+
+        assert!(err.contains("local, sepolia"));
+    }
+
+    #[test]
+    fn test_validate_rejects_bad_rpc_urls() {
+        let mut profile = NetworkConfig::parse(CONFIG).unwrap().profiles["local"].clone();
+
+        profile.rpc_urls = vec![];
+        assert!(profile.validate().is_err());
+
+        profile.rpc_urls = vec!["ws://localhost:3051".to_string()];
+        assert!(profile.validate().is_err());
+    }
+}
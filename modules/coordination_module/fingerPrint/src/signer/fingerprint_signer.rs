@@ -0,0 +1,94 @@
+use async_trait::async_trait;
+use ethers::prelude::*;
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::transaction::eip712::Eip712;
+use std::fmt;
+
+use crate::signer::remote_signer::{RemoteSigner, RemoteSignerError};
+
+/// The signer used by the fingerprint wallet, whichever backend it was loaded from.
+///
+/// Keystore and mnemonic backends both end up as a `LocalWallet` once decrypted or derived,
+/// so only the remote backend needs its own variant.
+#[derive(Debug, Clone)]
+pub enum FingerprintSigner {
+    Local(LocalWallet),
+    Remote(RemoteSigner),
+}
+
+/// Errors returned by a `FingerprintSigner`.
+#[derive(Debug)]
+pub enum FingerprintSignerError {
+    Wallet(WalletError),
+    Remote(RemoteSignerError),
+}
+
+impl fmt::Display for FingerprintSignerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FingerprintSignerError::Wallet(err) => write!(f, "local signer error: {}", err),
+            FingerprintSignerError::Remote(err) => write!(f, "remote signer error: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for FingerprintSignerError {}
+
+impl From<WalletError> for FingerprintSignerError {
+    fn from(err: WalletError) -> Self {
+        FingerprintSignerError::Wallet(err)
+    }
+}
+
+impl From<RemoteSignerError> for FingerprintSignerError {
+    fn from(err: RemoteSignerError) -> Self {
+        FingerprintSignerError::Remote(err)
+    }
+}
+
+#[async_trait]
+impl Signer for FingerprintSigner {
+    type Error = FingerprintSignerError;
+
+    async fn sign_message<S: Send + Sync + AsRef<[u8]>>(&self, message: S) -> Result<Signature, Self::Error> {
+        match self {
+            FingerprintSigner::Local(wallet) => Ok(wallet.sign_message(message).await?),
+            FingerprintSigner::Remote(remote) => Ok(remote.sign_message(message).await?),
+        }
+    }
+
+    async fn sign_transaction(&self, tx: &TypedTransaction) -> Result<Signature, Self::Error> {
+        match self {
+            FingerprintSigner::Local(wallet) => Ok(wallet.sign_transaction(tx).await?),
+            FingerprintSigner::Remote(remote) => Ok(remote.sign_transaction(tx).await?),
+        }
+    }
+
+    async fn sign_typed_data<T: Eip712 + Send + Sync>(&self, payload: &T) -> Result<Signature, Self::Error> {
+        match self {
+            FingerprintSigner::Local(wallet) => Ok(wallet.sign_typed_data(payload).await?),
+            FingerprintSigner::Remote(remote) => Ok(remote.sign_typed_data(payload).await?),
+        }
+    }
+
+    fn address(&self) -> Address {
+        match self {
+            FingerprintSigner::Local(wallet) => wallet.address(),
+            FingerprintSigner::Remote(remote) => remote.address(),
+        }
+    }
+
+    fn chain_id(&self) -> u64 {
+        match self {
+            FingerprintSigner::Local(wallet) => wallet.chain_id(),
+            FingerprintSigner::Remote(remote) => remote.chain_id(),
+        }
+    }
+
+    fn with_chain_id<T: Into<u64>>(self, chain_id: T) -> Self {
+        match self {
+            FingerprintSigner::Local(wallet) => FingerprintSigner::Local(wallet.with_chain_id(chain_id)),
+            FingerprintSigner::Remote(remote) => FingerprintSigner::Remote(remote.with_chain_id(chain_id)),
+        }
+    }
+}
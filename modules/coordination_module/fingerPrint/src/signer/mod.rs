@@ -0,0 +1,3 @@
+pub mod fingerprint_signer;
+pub mod remote_signer;
+pub mod signer_config;
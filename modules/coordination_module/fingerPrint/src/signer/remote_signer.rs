@@ -0,0 +1,252 @@
+use async_trait::async_trait;
+use ethers::prelude::*;
+use ethers::signers::to_eip155_v;
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::transaction::eip712::Eip712;
+use ethers::utils::hash_message;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Signs digests by delegating to a remote signing service over HTTP.
+///
+/// The service keeps the private key; this client only ever sends 32-byte digests and
+/// receives 65-byte `r || s || v` signatures back. The protocol is two endpoints:
+/// - `GET {url}/address` returns `{"address": "0x.."}`.
+/// - `POST {url}/sign` with `{"address": "0x..", "digest": "0x.."}` returns `{"signature": "0x.."}`.
+#[derive(Debug, Clone)]
+pub struct RemoteSigner {
+    url: String,
+    address: Address,
+    chain_id: u64,
+    client: reqwest::Client,
+}
+
+/// Errors returned by the remote signer.
+#[derive(Debug)]
+pub enum RemoteSignerError {
+    Http(reqwest::Error),
+    InvalidSignature(String),
+    AddressMismatch { expected: Address, actual: Address },
+    Eip712(String),
+}
+
+impl fmt::Display for RemoteSignerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RemoteSignerError::Http(err) => write!(f, "request to remote signer failed: {}", err),
+            RemoteSignerError::InvalidSignature(msg) => write!(f, "remote signer returned an invalid signature: {}", msg),
+            RemoteSignerError::AddressMismatch { expected, actual } => {
+                write!(f, "remote signer holds {:?}, expected {:?}", actual, expected)
+            }
+            RemoteSignerError::Eip712(msg) => write!(f, "failed to encode typed data: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for RemoteSignerError {}
+
+impl From<reqwest::Error> for RemoteSignerError {
+    fn from(err: reqwest::Error) -> Self {
+        RemoteSignerError::Http(err)
+    }
+}
+
+#[derive(Serialize)]
+struct SignRequest {
+    address: Address,
+    digest: H256,
+}
+
+#[derive(Deserialize)]
+struct SignResponse {
+    signature: String,
+}
+
+#[derive(Deserialize)]
+struct AddressResponse {
+    address: Address,
+}
+
+impl RemoteSigner {
+    /// Connects to a remote signer.
+    ///
+    /// # Parameters
+    /// - `url`: The base URL of the remote signing service.
+    /// - `address`: The expected signer address. When `None`, the address reported by the service is used.
+    /// - `chain_id`: The chain id used for EIP-155 transaction signatures.
+    ///
+    /// # Returns
+    /// - `Result<RemoteSigner, RemoteSignerError>`: The connected signer, or an error if the service is unreachable
+    ///   or holds a different key than expected.
+    pub async fn connect(url: &str, address: Option<Address>, chain_id: u64) -> Result<Self, RemoteSignerError> {
+        let url = url.trim_end_matches('/').to_string();
+        let client = reqwest::Client::new();
+        let response: AddressResponse = client
+            .get(format!("{}/address", url))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        if let Some(expected) = address {
+            if expected != response.address {
+                return Err(RemoteSignerError::AddressMismatch { expected, actual: response.address });
+            }
+        }
+
+        Ok(Self { url, address: response.address, chain_id, client })
+    }
+
+    /// Asks the remote service to sign a digest.
+    ///
+    /// # Parameters
+    /// - `digest`: The 32-byte digest to be signed.
+    ///
+    /// # Returns
+    /// - `Result<Signature, RemoteSignerError>`: The signature with `v` normalized to 27/28.
+    pub async fn sign_hash(&self, digest: H256) -> Result<Signature, RemoteSignerError> {
+        let response: SignResponse = self
+            .client
+            .post(format!("{}/sign", self.url))
+            .json(&SignRequest { address: self.address, digest })
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        let bytes = hex::decode(response.signature.trim_start_matches("0x"))
+            .map_err(|err| RemoteSignerError::InvalidSignature(err.to_string()))?;
+        let mut signature = Signature::try_from(bytes.as_slice())
+            .map_err(|err| RemoteSignerError::InvalidSignature(err.to_string()))?;
+        // Services answer with `v` as 0/1, 27/28 or in EIP-155 form; keep only the recovery id
+        let recovery_id = signature
+            .recovery_id()
+            .map_err(|err| RemoteSignerError::InvalidSignature(err.to_string()))?;
+        signature.v = u8::from(recovery_id) as u64 + 27;
+
+        let recovered = signature
+            .recover(digest)
+            .map_err(|err| RemoteSignerError::InvalidSignature(err.to_string()))?;
+        if recovered != self.address {
+            return Err(RemoteSignerError::AddressMismatch { expected: self.address, actual: recovered });
+        }
+
+        Ok(signature)
+    }
+}
+
+#[async_trait]
+impl Signer for RemoteSigner {
+    type Error = RemoteSignerError;
+
+    async fn sign_message<S: Send + Sync + AsRef<[u8]>>(&self, message: S) -> Result<Signature, Self::Error> {
+        self.sign_hash(hash_message(message.as_ref())).await
+    }
+
+    async fn sign_transaction(&self, tx: &TypedTransaction) -> Result<Signature, Self::Error> {
+        // Mirror `Wallet::sign_transaction_sync`: the sighash and `v` must use the same chain id
+        let chain_id = tx.chain_id().map(|id| id.as_u64()).unwrap_or(self.chain_id);
+        let mut tx = tx.clone();
+        tx.set_chain_id(chain_id);
+
+        let mut signature = self.sign_hash(tx.sighash()).await?;
+        signature.v = to_eip155_v(signature.v as u8 - 27, chain_id);
+        Ok(signature)
+    }
+
+    async fn sign_typed_data<T: Eip712 + Send + Sync>(&self, payload: &T) -> Result<Signature, Self::Error> {
+        let encoded = payload
+            .encode_eip712()
+            .map_err(|err| RemoteSignerError::Eip712(err.to_string()))?;
+        self.sign_hash(H256::from(encoded)).await
+    }
+
+    fn address(&self) -> Address {
+        self.address
+    }
+
+    fn chain_id(&self) -> u64 {
+        self.chain_id
+    }
+
+    fn with_chain_id<T: Into<u64>>(mut self, chain_id: T) -> Self {
+        self.chain_id = chain_id.into();
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::spawn_stub_server;
+
+    const TEST_KEY: &str = "4c0883a69102937d6231471b5dbb6204fe5129617082796e8e1a1e3b7a1e7e3e";
+
+    /// Starts a minimal remote signer that signs with `wallet`, returning its base URL.
+    ///
+    /// With `eip155_chain_id`, the signer answers with `v` in EIP-155 form for that chain.
+    async fn spawn_stub_signer(wallet: LocalWallet, eip155_chain_id: Option<u64>) -> String {
+        spawn_stub_server(move |request_line, body| {
+            if request_line.starts_with("GET /address") {
+                serde_json::json!({ "address": wallet.address() }).to_string()
+            } else {
+                let request: serde_json::Value = serde_json::from_str(body).unwrap();
+                let digest: H256 = serde_json::from_value(request["digest"].clone()).unwrap();
+                let mut signature = wallet.sign_hash(digest);
+                if let Some(chain_id) = eip155_chain_id {
+                    signature.v = to_eip155_v(signature.v as u8 - 27, chain_id);
+                }
+                serde_json::json!({ "signature": format!("0x{}", hex::encode(signature.to_vec())) }).to_string()
+            }
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_remote_signer_matches_local_wallet() {
+        let wallet: LocalWallet = TEST_KEY.parse::<LocalWallet>().unwrap().with_chain_id(300u64);
+        let url = spawn_stub_signer(wallet.clone(), None).await;
+
+        let remote = RemoteSigner::connect(&url, Some(wallet.address()), 300).await.unwrap();
+        assert_eq!(remote.address(), wallet.address());
+
+        let tx: TypedTransaction = TransactionRequest::new()
+            .to(Address::zero())
+            .data(vec![1u8, 2, 3])
+            .nonce(7)
+            .gas(21000)
+            .gas_price(1)
+            .into();
+        let expected = wallet.sign_transaction(&tx).await.unwrap();
+        let actual = remote.sign_transaction(&tx).await.unwrap();
+        assert_eq!(actual, expected);
+
+        let signature = remote.sign_message("fingerprint").await.unwrap();
+        assert_eq!(signature.recover("fingerprint").unwrap(), wallet.address());
+    }
+
+    #[tokio::test]
+    async fn test_remote_signer_normalizes_eip155_v() {
+        let wallet: LocalWallet = TEST_KEY.parse::<LocalWallet>().unwrap().with_chain_id(1u64);
+        let url = spawn_stub_signer(wallet.clone(), Some(1)).await;
+        let remote = RemoteSigner::connect(&url, Some(wallet.address()), 1).await.unwrap();
+
+        let signature = remote.sign_hash(H256::repeat_byte(7)).await.unwrap();
+        assert!(signature.v == 27 || signature.v == 28);
+
+        let tx: TypedTransaction = TransactionRequest::new().to(Address::zero()).nonce(1).gas(21000).gas_price(1).into();
+        assert_eq!(remote.sign_transaction(&tx).await.unwrap(), wallet.sign_transaction(&tx).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_remote_signer_rejects_unexpected_address() {
+        let wallet: LocalWallet = TEST_KEY.parse().unwrap();
+        let url = spawn_stub_signer(wallet, None).await;
+
+        let result = RemoteSigner::connect(&url, Some(Address::repeat_byte(1)), 300).await;
+
+        assert!(matches!(result, Err(RemoteSignerError::AddressMismatch { .. })));
+    }
+}
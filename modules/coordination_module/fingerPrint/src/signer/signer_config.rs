@@ -0,0 +1,156 @@
+use ethers::prelude::*;
+use ethers::signers::coins_bip39::English;
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fs;
+
+use crate::signer::fingerprint_signer::FingerprintSigner;
+use crate::signer::remote_signer::RemoteSigner;
+
+/// The derivation path used for mnemonic signers when none is configured.
+pub const DEFAULT_DERIVATION_PATH: &str = "m/44'/60'/0'/0/0";
+
+/// Describes where the fingerprint wallet gets its signing key from.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SignerConfig {
+    /// A raw hex private key read from `ZKSYNC_SEPOLIA_PRIVATE_KEY`. Intended for local development only.
+    PrivateKey,
+    /// An encrypted JSON keystore, unlocked with the passphrase stored in `passphrase_file`.
+    Keystore { path: String, passphrase_file: String },
+    /// A BIP-39 mnemonic read from `phrase_file`, derived at `derivation_path`.
+    Mnemonic { phrase_file: String, derivation_path: String },
+    /// A remote signing service holding the key for `address`.
+    Remote { url: String, address: Option<Address> },
+}
+
+impl SignerConfig {
+    /// Reads the signer configuration from the environment.
+    ///
+    /// `FINGERPRINT_SIGNER` selects the backend (`private_key`, `keystore`, `mnemonic` or `remote`)
+    /// and defaults to `private_key`. Each backend then reads its own variables.
+    ///
+    /// # Returns
+    /// - `Result<SignerConfig, Box<dyn std::error::Error>>`: The signer configuration.
+    pub fn from_env() -> Result<Self, Box<dyn std::error::Error>> {
+        let kind = env::var("FINGERPRINT_SIGNER").unwrap_or_else(|_| "private_key".to_string());
+
+        match kind.as_str() {
+            "private_key" => Ok(SignerConfig::PrivateKey),
+            "keystore" => Ok(SignerConfig::Keystore {
+                path: env::var("FINGERPRINT_KEYSTORE_PATH")?,
+                passphrase_file: env::var("FINGERPRINT_KEYSTORE_PASSPHRASE_FILE")?,
+            }),
+            "mnemonic" => Ok(SignerConfig::Mnemonic {
+                phrase_file: env::var("FINGERPRINT_MNEMONIC_FILE")?,
+                derivation_path: env::var("FINGERPRINT_DERIVATION_PATH")
+                    .unwrap_or_else(|_| DEFAULT_DERIVATION_PATH.to_string()),
+            }),
+            "remote" => Ok(SignerConfig::Remote {
+                url: env::var("FINGERPRINT_REMOTE_SIGNER_URL")?,
+                address: match env::var("FINGERPRINT_REMOTE_SIGNER_ADDRESS") {
+                    Ok(address) => Some(address.parse()?),
+                    Err(_) => None,
+                },
+            }),
+            other => Err(format!("Unknown FINGERPRINT_SIGNER backend: {}", other).into()),
+        }
+    }
+
+    /// Loads the signer described by this configuration.
+    ///
+    /// # Parameters
+    /// - `chain_id`: The chain id the signer will sign transactions for.
+    ///
+    /// # Returns
+    /// - `Result<FingerprintSigner, Box<dyn std::error::Error>>`: The ready-to-use signer.
+    pub async fn load(&self, chain_id: u64) -> Result<FingerprintSigner, Box<dyn std::error::Error>> {
+        let signer = match self {
+            SignerConfig::PrivateKey => {
+                let private_key = env::var("ZKSYNC_SEPOLIA_PRIVATE_KEY")?;
+                FingerprintSigner::Local(private_key.parse::<LocalWallet>()?)
+            }
+            SignerConfig::Keystore { path, passphrase_file } => {
+                let passphrase = read_secret(passphrase_file)?;
+                FingerprintSigner::Local(LocalWallet::decrypt_keystore(path, passphrase)?)
+            }
+            SignerConfig::Mnemonic { phrase_file, derivation_path } => {
+                let phrase = read_secret(phrase_file)?;
+                let wallet = MnemonicBuilder::<English>::default()
+                    .phrase(phrase.as_str())
+                    .derivation_path(derivation_path)?
+                    .build()?;
+                FingerprintSigner::Local(wallet)
+            }
+            SignerConfig::Remote { url, address } => {
+                FingerprintSigner::Remote(RemoteSigner::connect(url, *address, chain_id).await?)
+            }
+        };
+
+        Ok(signer.with_chain_id(chain_id))
+    }
+}
+
+/// Reads a secret from a file, ignoring surrounding whitespace and the trailing newline.
+fn read_secret(path: &str) -> Result<String, Box<dyn std::error::Error>> {
+    let secret = fs::read_to_string(path).map_err(|err| format!("Failed to read secret file {}: {}", path, err))?;
+    Ok(secret.trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::core::rand::thread_rng;
+    use tempfile::tempdir;
+
+    const TEST_MNEMONIC: &str = "test test test test test test test test test test test junk";
+
+    #[tokio::test]
+    async fn test_load_keystore_signer() {
+        let dir = tempdir().unwrap();
+        let (wallet, name) = LocalWallet::new_keystore(dir.path(), &mut thread_rng(), "hunter2", None).unwrap();
+        let passphrase_file = dir.path().join("passphrase");
+        fs::write(&passphrase_file, "hunter2\n").unwrap();
+
+        let config = SignerConfig::Keystore {
+            path: dir.path().join(name).to_str().unwrap().to_string(),
+            passphrase_file: passphrase_file.to_str().unwrap().to_string(),
+        };
+        let signer = config.load(300).await.unwrap();
+
+        assert_eq!(signer.address(), wallet.address());
+        assert_eq!(signer.chain_id(), 300);
+    }
+
+    #[tokio::test]
+    async fn test_load_mnemonic_signer() {
+        let dir = tempdir().unwrap();
+        let phrase_file = dir.path().join("mnemonic");
+        fs::write(&phrase_file, TEST_MNEMONIC).unwrap();
+
+        let config = SignerConfig::Mnemonic {
+            phrase_file: phrase_file.to_str().unwrap().to_string(),
+            derivation_path: "m/44'/60'/0'/0/1".to_string(),
+        };
+        let signer = config.load(300).await.unwrap();
+
+        let expected: Address = "0x70997970C51812dc3A010C7d01b50e0d17dc79C8".parse().unwrap();
+        assert_eq!(signer.address(), expected);
+    }
+
+    #[test]
+    fn test_signer_config_deserializes_by_kind() {
+        let config: SignerConfig = serde_json::from_str(
+            r#"{"kind": "keystore", "path": "/keys/node.json", "passphrase_file": "/keys/passphrase"}"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            config,
+            SignerConfig::Keystore {
+                path: "/keys/node.json".to_string(),
+                passphrase_file: "/keys/passphrase".to_string(),
+            }
+        );
+    }
+}
@@ -0,0 +1,75 @@
+use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+/// Starts a minimal HTTP server for tests, returning its base URL.
+///
+/// Every request is answered with `200 OK` and the JSON produced by `handler`, which receives the
+/// request line (e.g. `POST /sign HTTP/1.1`) and the request body.
+pub async fn spawn_stub_server<F>(handler: F) -> String
+where
+    F: Fn(&str, &str) -> String + Send + Sync + 'static,
+{
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let handler = Arc::new(handler);
+
+    tokio::spawn(async move {
+        loop {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let handler = handler.clone();
+            tokio::spawn(async move {
+                let mut buf = Vec::new();
+                let mut chunk = [0u8; 4096];
+                let (head, body) = loop {
+                    let n = socket.read(&mut chunk).await.unwrap();
+                    if n == 0 {
+                        return;
+                    }
+                    buf.extend_from_slice(&chunk[..n]);
+                    let text = String::from_utf8_lossy(&buf).to_string();
+                    if let Some(pos) = text.find("\r\n\r\n") {
+                        let head = text[..pos].to_string();
+                        let length = head
+                            .lines()
+                            .find_map(|line| {
+                                line.to_lowercase()
+                                    .strip_prefix("content-length:")
+                                    .map(|value| value.trim().parse::<usize>().unwrap())
+                            })
+                            .unwrap_or(0);
+                        if buf.len() >= pos + 4 + length {
+                            break (head, text[pos + 4..pos + 4 + length].to_string());
+                        }
+                    }
+                };
+
+                let request_line = head.lines().next().unwrap_or_default().to_string();
+                let reply = handler(&request_line, &body);
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    reply.len(),
+                    reply
+                );
+                socket.write_all(response.as_bytes()).await.unwrap();
+            });
+        }
+    });
+
+    url
+}
+
+/// Starts a stub JSON-RPC node, returning its URL.
+///
+/// `handler` receives the method name and params of each call and returns the JSON `result`.
+pub async fn spawn_stub_rpc<F>(handler: F) -> String
+where
+    F: Fn(&str, &serde_json::Value) -> serde_json::Value + Send + Sync + 'static,
+{
+    spawn_stub_server(move |_, body| {
+        let request: serde_json::Value = serde_json::from_str(body).unwrap();
+        let result = handler(request["method"].as_str().unwrap_or_default(), &request["params"]);
+        serde_json::json!({ "jsonrpc": "2.0", "id": request["id"], "result": result }).to_string()
+    })
+    .await
+}
@@ -0,0 +1,259 @@
+use ethers::prelude::*;
+use serde::Serialize;
+use std::sync::Arc;
+
+use crate::check::check_batch::check_fingerprints;
+use crate::create::create_hash::create_fingerprint_hash;
+use crate::indexer::index_store::IndexStore;
+use crate::indexer::log_source::{AppendLog, LogSource};
+use crate::network::profile::NetworkProfile;
+use crate::Fingerprint;
+
+/// The outcome of verifying an observation against the chain.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct VerificationReport {
+    pub fingerprint: Fingerprint,
+    /// The fingerprint hash recomputed from the observation.
+    pub hash: String,
+    /// The hash the observation was claimed to produce, if one was given.
+    pub expected_hash: Option<String>,
+    /// Whether the recomputed hash equals `expected_hash`, if one was given.
+    pub matches_expected: Option<bool>,
+    /// Whether the contract reports the hash as appended.
+    pub appended: bool,
+    /// Where the hash was appended, if the append could be located.
+    pub appends: Vec<AppendLog>,
+}
+
+impl VerificationReport {
+    /// Whether the observation checks out: its hash is on chain and matches the expected hash, if any.
+    pub fn is_verified(&self) -> bool {
+        self.appended && self.matches_expected.unwrap_or(true)
+    }
+}
+
+/// Recomputes the fingerprint hash of an observation exactly as the coordinator does.
+///
+/// # Parameters
+/// - `observation`: The observation JSON string.
+///
+/// # Returns
+/// - `Result<(Fingerprint, String), Box<dyn std::error::Error>>`: The fingerprint and its hash.
+pub fn fingerprint_observation(observation: &str) -> Result<(Fingerprint, String), Box<dyn std::error::Error>> {
+    let observation: serde_json::Value = serde_json::from_str(observation)?;
+    let fingerprint = Fingerprint::from_observation(&observation);
+    let hash = create_fingerprint_hash(&fingerprint)?;
+    Ok((fingerprint, hash))
+}
+
+/// Verifies an observation against the fingerprint contract, see `locate_hash`.
+///
+/// # Parameters
+/// - `observation`: The observation JSON string.
+/// - `expected_hash`: The fingerprint hash the observation is claimed to produce, if any.
+/// - `client`: The ether client connected to the blockchain.
+/// - `profile`: The validated network profile.
+/// - `source`: Where to scan for the append when the index does not know it.
+/// - `index`: The local fingerprint index, which may be empty.
+///
+/// # Returns
+/// - `Result<VerificationReport, Box<dyn std::error::Error>>`: What was found.
+pub async fn verify_observation<M, S>(
+    observation: &str,
+    expected_hash: Option<&str>,
+    client: Arc<M>,
+    profile: &NetworkProfile,
+    source: &S,
+    index: &IndexStore,
+) -> Result<VerificationReport, Box<dyn std::error::Error>>
+where
+    M: Middleware<Provider = Http> + 'static,
+    S: LogSource,
+{
+    let (fingerprint, hash) = fingerprint_observation(observation)?;
+    // Compare the hashes as bytes, so the prefix and case the expected hash is written in do not matter
+    let matches_expected = match expected_hash {
+        Some(expected) => {
+            let expected: H256 = expected
+                .trim()
+                .parse()
+                .map_err(|err| format!("expected hash '{}' is not a 32-byte hex hash: {}", expected, err))?;
+            Some(expected == hash.parse::<H256>()?)
+        }
+        None => None,
+    };
+    let (appended, appends) = locate_hash(&hash, client, profile, source, index).await?;
+
+    Ok(VerificationReport {
+        fingerprint,
+        hash,
+        expected_hash: expected_hash.map(|expected| expected.to_string()),
+        matches_expected,
+        appended,
+        appends,
+    })
+}
+
+/// Checks whether a fingerprint hash is appended and locates where.
+///
+/// The append is located in the local index first. When the index does not know the hash but the
+/// contract does, the contract's logs are scanned from the profile's `indexer.start_block`.
+///
+/// # Parameters
+/// - `hash`: The fingerprint hash.
+/// - `client`: The ether client connected to the blockchain.
+/// - `profile`: The validated network profile.
+/// - `source`: Where to scan for the append when the index does not know it.
+/// - `index`: The local fingerprint index, which may be empty.
+///
+/// # Returns
+/// - `Result<(bool, Vec<AppendLog>), Box<dyn std::error::Error>>`: Whether the contract reports the hash as
+///   appended, and the appends that were found.
+pub async fn locate_hash<M, S>(
+    hash: &str,
+    client: Arc<M>,
+    profile: &NetworkProfile,
+    source: &S,
+    index: &IndexStore,
+) -> Result<(bool, Vec<AppendLog>), Box<dyn std::error::Error>>
+where
+    M: Middleware<Provider = Http> + 'static,
+    S: LogSource,
+{
+    let hash_bytes: H256 = hash.parse()?;
+    let hash = format!("{:?}", hash_bytes);
+    let appended = check_fingerprints(
+        client,
+        profile.contracts.fingerprint_proxy,
+        profile.contracts.multicall,
+        std::slice::from_ref(&hash),
+    )
+    .await?[&hash];
+
+    let mut appends: Vec<AppendLog> = index.appends_of(&hash_bytes).into_iter().cloned().collect();
+    if appended && appends.is_empty() {
+        appends = find_appends(source, profile, hash_bytes).await.map_err(|err| err.to_string())?;
+    }
+
+    Ok((appended, appends))
+}
+
+/// Scans the contract's logs for the appends of one hash.
+async fn find_appends<S: LogSource>(
+    source: &S,
+    profile: &NetworkProfile,
+    hash: H256,
+) -> Result<Vec<AppendLog>, Box<dyn std::error::Error + Send + Sync>> {
+    let latest = source.latest_block().await?;
+    let batch_size = profile.indexer.batch_size.max(1);
+    let mut appends = Vec::new();
+    let mut from = profile.indexer.start_block;
+
+    while from <= latest {
+        let to = (from + batch_size - 1).min(latest);
+        appends.extend(source.append_logs(from, to).await?.into_iter().filter(|append| append.hash == hash));
+        from = to + 1;
+    }
+
+    Ok(appends)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indexer::log_source::{RecordedBlock, RecordedLogSource};
+    use crate::network::profile::{ConfirmationSettings, ContractAddresses, IndexerSettings};
+    use crate::test_utils::spawn_stub_server;
+    use ethers::abi::Token;
+    use serde_json::json;
+
+    const OBSERVATION: &str = r#"{"game": "g", "character": "kqiyqnihok", "ability": "rbmonlrehd", "place": "hnntcgutwg", "place2": "ynyxqjdmim"}"#;
+
+    fn profile() -> NetworkProfile {
+        NetworkProfile {
+            chain_id: 300,
+            rpc_urls: vec![],
+            contracts: ContractAddresses { fingerprint_proxy: Address::repeat_byte(1), multicall: None },
+            confirmations: ConfirmationSettings::default(),
+            indexer: IndexerSettings { start_block: 1, batch_size: 2, ..IndexerSettings::default() },
+            signer: None,
+        }
+    }
+
+    /// Starts a node whose contract reports every hash as `appended`.
+    async fn provider(appended: bool) -> Arc<Provider<Http>> {
+        let url = spawn_stub_server(move |_, body| {
+            let batch: Vec<serde_json::Value> = serde_json::from_str(body).unwrap();
+            let result = Bytes::from(ethers::abi::encode(&[Token::Bool(appended)]));
+            let responses: Vec<_> = batch
+                .iter()
+                .map(|request| json!({ "jsonrpc": "2.0", "id": request["id"], "result": result }))
+                .collect();
+            serde_json::to_string(&responses).unwrap()
+        })
+        .await;
+        Arc::new(Provider::<Http>::try_from(url.as_str()).unwrap())
+    }
+
+    fn append(hash: H256, block_number: u64) -> AppendLog {
+        AppendLog {
+            hash,
+            block_number,
+            block_hash: H256::from_low_u64_be(block_number),
+            tx_hash: H256::from_low_u64_be(block_number * 1000),
+            log_index: 0,
+            sender: Address::repeat_byte(9),
+        }
+    }
+
+    #[test]
+    fn test_fingerprint_observation_matches_coordinator() {
+        let (fingerprint, hash) = fingerprint_observation(OBSERVATION).unwrap();
+
+        assert_eq!(fingerprint.gamer, "kqiyqnihok");
+        assert_eq!(fingerprint.weapon, "rbmonlrehd");
+        assert_eq!(hash, create_fingerprint_hash(&fingerprint).unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_verify_observation_locates_append_in_logs() {
+        let (_, hash) = fingerprint_observation(OBSERVATION).unwrap();
+        let hash_bytes: H256 = hash.parse().unwrap();
+        let source = RecordedLogSource {
+            blocks: vec![
+                RecordedBlock { number: 1, hash: H256::from_low_u64_be(1), appends: vec![append(H256::repeat_byte(7), 1)] },
+                RecordedBlock { number: 2, hash: H256::from_low_u64_be(2), appends: vec![] },
+                RecordedBlock { number: 3, hash: H256::from_low_u64_be(3), appends: vec![append(hash_bytes, 3)] },
+            ],
+        };
+
+        let expected_hash = hash.trim_start_matches("0x").to_uppercase();
+
+        let report = verify_observation(OBSERVATION, Some(&expected_hash), provider(true).await, &profile(), &source, &IndexStore::default())
+            .await
+            .unwrap();
+
+        assert!(report.is_verified());
+        assert_eq!(report.matches_expected, Some(true));
+        assert_eq!(report.appends, vec![append(hash_bytes, 3)]);
+    }
+
+    #[tokio::test]
+    async fn test_verify_observation_reports_mismatch() {
+        let other_hash = format!("{:?}", H256::repeat_byte(7));
+
+        let report = verify_observation(OBSERVATION, Some(&other_hash), provider(false).await, &profile(), &RecordedLogSource::default(), &IndexStore::default())
+            .await
+            .unwrap();
+
+        assert!(!report.is_verified());
+        assert_eq!(report.matches_expected, Some(false));
+        assert!(!report.appended);
+        assert!(report.appends.is_empty());
+
+        let err = verify_observation(OBSERVATION, Some("0x1234"), provider(false).await, &profile(), &RecordedLogSource::default(), &IndexStore::default())
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("not a 32-byte hex hash"));
+    }
+}
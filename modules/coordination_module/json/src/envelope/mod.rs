@@ -0,0 +1,2 @@
+pub mod observation_envelope;
+pub mod replay_guard;
@@ -0,0 +1,271 @@
+use ethers::core::rand::{thread_rng, RngCore};
+use ethers::signers::Signer;
+use ethers::types::{Address, Signature};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::fmt;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// The envelope format version this crate signs and accepts.
+pub const ENVELOPE_VERSION: u64 = 1;
+
+/// An observation signed by its uploader.
+///
+/// The signature is an EIP-191 personal message signature over `signing_bytes`, the
+/// canonical JSON encoding of every field but the signature itself, so neither the
+/// payload nor the nonce and timestamp can be changed without invalidating it.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ObservationEnvelope {
+    pub version: u64,
+    /// The observation. Its `uploader` field names the address that must have signed it.
+    pub payload: Value,
+    /// A value the uploader never signs twice, so an envelope can only be accepted once.
+    pub nonce: String,
+    /// When the envelope was signed, in seconds since the Unix epoch.
+    pub timestamp: u64,
+    /// The 65-byte signature, as hex.
+    pub signature: String,
+}
+
+/// Why an envelope was rejected.
+#[derive(Debug, Clone, PartialEq)]
+pub enum EnvelopeError {
+    Malformed(String),
+    UnsupportedVersion(u64),
+    InvalidSignature(String),
+    InvalidUploader(String),
+    UploaderMismatch { uploader: Address, signer: Address },
+    Stale { timestamp: u64, now: u64 },
+    FromFuture { timestamp: u64, now: u64 },
+    Replayed { signer: Address, nonce: String },
+    /// The envelope's nonce could not be written to the nonce log.
+    Unrecorded(String),
+}
+
+impl fmt::Display for EnvelopeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EnvelopeError::Malformed(msg) => write!(f, "malformed envelope: {}", msg),
+            EnvelopeError::UnsupportedVersion(version) => write!(f, "unsupported envelope version {}", version),
+            EnvelopeError::InvalidSignature(msg) => write!(f, "invalid signature: {}", msg),
+            EnvelopeError::InvalidUploader(msg) => write!(f, "invalid uploader: {}", msg),
+            EnvelopeError::UploaderMismatch { uploader, signer } => {
+                write!(f, "uploader {:?} did not sign the envelope, {:?} did", uploader, signer)
+            }
+            EnvelopeError::Stale { timestamp, now } => {
+                write!(f, "envelope signed at {} is too old to accept at {}", timestamp, now)
+            }
+            EnvelopeError::FromFuture { timestamp, now } => {
+                write!(f, "envelope signed at {} is ahead of the clock at {}", timestamp, now)
+            }
+            EnvelopeError::Replayed { signer, nonce } => {
+                write!(f, "nonce {} of {:?} was already used", nonce, signer)
+            }
+            EnvelopeError::Unrecorded(msg) => write!(f, "could not record the nonce: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for EnvelopeError {}
+
+impl ObservationEnvelope {
+    /// Signs an observation with the given nonce and timestamp.
+    ///
+    /// # Parameters
+    /// - `payload`: The observation, whose `uploader` should be the signer's address.
+    /// - `nonce`: A value the signer never used before.
+    /// - `timestamp`: The signing time, in seconds since the Unix epoch.
+    /// - `signer`: The uploader's key.
+    ///
+    /// # Returns
+    /// - `Result<ObservationEnvelope, S::Error>`: The signed envelope.
+    pub async fn sign<S: Signer>(
+        payload: Value,
+        nonce: impl Into<String>,
+        timestamp: u64,
+        signer: &S,
+    ) -> Result<Self, S::Error> {
+        let mut envelope = ObservationEnvelope {
+            version: ENVELOPE_VERSION,
+            payload,
+            nonce: nonce.into(),
+            timestamp,
+            signature: String::new(),
+        };
+        let signature = signer.sign_message(envelope.signing_bytes()).await?;
+        envelope.signature = signature.to_string();
+        Ok(envelope)
+    }
+
+    /// Signs an observation with a random nonce and the current time.
+    ///
+    /// # Parameters
+    /// - `payload`: The observation, whose `uploader` should be the signer's address.
+    /// - `signer`: The uploader's key.
+    ///
+    /// # Returns
+    /// - `Result<ObservationEnvelope, S::Error>`: The signed envelope.
+    pub async fn seal<S: Signer>(payload: Value, signer: &S) -> Result<Self, S::Error> {
+        let mut nonce = [0u8; 16];
+        thread_rng().fill_bytes(&mut nonce);
+        let nonce: String = nonce.iter().map(|byte| format!("{:02x}", byte)).collect();
+        Self::sign(payload, nonce, unix_now(), signer).await
+    }
+
+    /// Parses an envelope from JSON.
+    ///
+    /// # Parameters
+    /// - `json`: The envelope JSON.
+    ///
+    /// # Returns
+    /// - `Result<ObservationEnvelope, EnvelopeError>`: The envelope, not yet verified.
+    pub fn parse(json: &str) -> Result<Self, EnvelopeError> {
+        let value: Value = serde_json::from_str(json).map_err(|err| EnvelopeError::Malformed(err.to_string()))?;
+        if !Self::is_envelope(&value) {
+            return Err(EnvelopeError::Malformed("not an envelope, the observation is unsigned".to_string()));
+        }
+        serde_json::from_value(value).map_err(|err| EnvelopeError::Malformed(err.to_string()))
+    }
+
+    /// Tells whether a JSON value looks like an envelope rather than a bare observation.
+    pub fn is_envelope(value: &Value) -> bool {
+        value.get("payload").is_some() && value.get("signature").is_some()
+    }
+
+    /// The bytes the uploader signs: the canonical JSON of every field but the signature.
+    pub fn signing_bytes(&self) -> Vec<u8> {
+        let unsigned = json!({
+            "version": self.version,
+            "payload": self.payload,
+            "nonce": self.nonce,
+            "timestamp": self.timestamp,
+        });
+        canonical_json(&unsigned).into_bytes()
+    }
+
+    /// The address the payload claims uploaded it.
+    pub fn uploader(&self) -> Result<Address, EnvelopeError> {
+        let uploader = self.payload["uploader"]
+            .as_str()
+            .ok_or_else(|| EnvelopeError::InvalidUploader("missing string field `uploader`".to_string()))?;
+        uploader.parse().map_err(|_| EnvelopeError::InvalidUploader(uploader.to_string()))
+    }
+
+    /// Checks that the envelope was signed by the uploader its payload names.
+    ///
+    /// This does not protect against replays; see `ReplayGuard`.
+    ///
+    /// # Returns
+    /// - `Result<Address, EnvelopeError>`: The uploader's address, or why the envelope is rejected.
+    pub fn verify(&self) -> Result<Address, EnvelopeError> {
+        if self.version != ENVELOPE_VERSION {
+            return Err(EnvelopeError::UnsupportedVersion(self.version));
+        }
+        let signature: Signature = self
+            .signature
+            .trim()
+            .parse()
+            .map_err(|err| EnvelopeError::InvalidSignature(format!("{}", err)))?;
+        let signer = signature
+            .recover(self.signing_bytes())
+            .map_err(|err| EnvelopeError::InvalidSignature(err.to_string()))?;
+        let uploader = self.uploader()?;
+        if uploader != signer {
+            return Err(EnvelopeError::UploaderMismatch { uploader, signer });
+        }
+        Ok(uploader)
+    }
+
+    /// The observation JSON, as the coordinator loads it.
+    pub fn payload_json(&self) -> String {
+        self.payload.to_string()
+    }
+}
+
+/// Encodes a JSON value compactly, with the keys of every object sorted.
+///
+/// Signers and verifiers must agree on the bytes regardless of how the JSON was
+/// formatted or in which order its keys were written.
+///
+/// # Parameters
+/// - `value`: The value to encode.
+///
+/// # Returns
+/// - `String`: The canonical encoding.
+pub fn canonical_json(value: &Value) -> String {
+    match value {
+        Value::Object(map) => {
+            let mut keys: Vec<&String> = map.keys().collect();
+            keys.sort();
+            let fields: Vec<String> = keys
+                .into_iter()
+                .map(|key| format!("{}:{}", Value::String(key.clone()), canonical_json(&map[key])))
+                .collect();
+            format!("{{{}}}", fields.join(","))
+        }
+        Value::Array(items) => {
+            let items: Vec<String> = items.iter().map(canonical_json).collect();
+            format!("[{}]", items.join(","))
+        }
+        other => other.to_string(),
+    }
+}
+
+/// The current time, in seconds since the Unix epoch.
+pub fn unix_now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|elapsed| elapsed.as_secs()).unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::signers::LocalWallet;
+
+    fn wallet() -> LocalWallet {
+        "4c0883a69102937d6231471b5dbb6204fe5129617082796e8e1a1e3b7a1e7e3e".parse().unwrap()
+    }
+
+    fn observation(uploader: Address) -> Value {
+        json!({ "game": 1, "character": "Mage", "uploader": format!("{:?}", uploader) })
+    }
+
+    #[test]
+    fn test_canonical_json_ignores_key_order_and_whitespace() {
+        let left: Value = serde_json::from_str(r#"{"b": [1, {"d": 2, "c": 3}], "a": "x"}"#).unwrap();
+        let right: Value = serde_json::from_str(r#"{"a":"x","b":[1,{"c":3,"d":2}]}"#).unwrap();
+
+        assert_eq!(canonical_json(&left), r#"{"a":"x","b":[1,{"c":3,"d":2}]}"#);
+        assert_eq!(canonical_json(&left), canonical_json(&right));
+    }
+
+    #[tokio::test]
+    async fn test_verify_accepts_the_uploader_and_survives_a_round_trip() {
+        let wallet = wallet();
+        let envelope = ObservationEnvelope::seal(observation(wallet.address()), &wallet).await.unwrap();
+
+        let parsed = ObservationEnvelope::parse(&serde_json::to_string_pretty(&envelope).unwrap()).unwrap();
+
+        assert_eq!(parsed.verify().unwrap(), wallet.address());
+        assert!(ObservationEnvelope::is_envelope(&serde_json::to_value(&parsed).unwrap()));
+        assert!(!ObservationEnvelope::is_envelope(&parsed.payload));
+    }
+
+    #[tokio::test]
+    async fn test_verify_rejects_tampering_and_other_uploaders() {
+        let wallet = wallet();
+
+        let mut tampered = ObservationEnvelope::sign(observation(wallet.address()), "n-1", 100, &wallet).await.unwrap();
+        tampered.payload["character"] = json!("Warrior");
+        assert!(tampered.verify().is_err());
+
+        let mut renonced = ObservationEnvelope::sign(observation(wallet.address()), "n-1", 100, &wallet).await.unwrap();
+        renonced.nonce = "n-2".to_string();
+        assert!(renonced.verify().is_err());
+
+        let impersonation = ObservationEnvelope::sign(observation(Address::zero()), "n-1", 100, &wallet).await.unwrap();
+        assert_eq!(
+            impersonation.verify(),
+            Err(EnvelopeError::UploaderMismatch { uploader: Address::zero(), signer: wallet.address() })
+        );
+    }
+}
@@ -0,0 +1,264 @@
+use ethers::types::Address;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::envelope::observation_envelope::{unix_now, EnvelopeError, ObservationEnvelope};
+
+/// How long after signing an envelope is still accepted, by default.
+pub const DEFAULT_MAX_AGE: Duration = Duration::from_secs(3600);
+
+/// How far ahead of the local clock an envelope may be signed, by default.
+pub const DEFAULT_MAX_SKEW: Duration = Duration::from_secs(60);
+
+/// The default path of the nonce log.
+pub const DEFAULT_NONCE_LOG: &str = "nonces.jsonl";
+
+/// One accepted envelope, as a line of the nonce log.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct SeenNonce {
+    uploader: Address,
+    nonce: String,
+    timestamp: u64,
+}
+
+/// Rejects envelopes that were already accepted once, or that are too old to tell.
+///
+/// Every accepted `(uploader, nonce)` pair is remembered for as long as its envelope could
+/// still be accepted; older envelopes are rejected by their timestamp instead, so the
+/// guard's memory stays bounded by the acceptance window.
+///
+/// A guard only remembers nonces across restarts when it is `persisted` to a nonce log;
+/// otherwise an envelope accepted before a restart is accepted again until it is too old.
+pub struct ReplayGuard {
+    max_age: Duration,
+    max_skew: Duration,
+    /// The timestamp of every accepted envelope, by uploader and nonce.
+    seen: HashMap<(Address, String), u64>,
+    /// The nonce log every accepted envelope is appended to, and how many lines it holds.
+    log: Option<(PathBuf, usize)>,
+}
+
+impl Default for ReplayGuard {
+    fn default() -> Self {
+        ReplayGuard::new(DEFAULT_MAX_AGE, DEFAULT_MAX_SKEW)
+    }
+}
+
+impl ReplayGuard {
+    /// Creates a guard accepting envelopes signed between `max_age` ago and `max_skew` from now.
+    pub fn new(max_age: Duration, max_skew: Duration) -> Self {
+        ReplayGuard { max_age, max_skew, seen: HashMap::new(), log: None }
+    }
+
+    /// Remembers accepted nonces in an append-only log, and loads the nonces it already holds.
+    ///
+    /// Entries too old to matter are dropped from the log when it is loaded, and whenever it
+    /// grows to twice the entries still remembered.
+    ///
+    /// # Parameters
+    /// - `path`: The nonce log. It is created if it does not exist.
+    ///
+    /// # Returns
+    /// - `Result<ReplayGuard, Box<dyn Error + Send + Sync>>`: The guard, or why the log could not be read or written.
+    pub fn persisted(mut self, path: impl Into<PathBuf>) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let path = path.into();
+        if path.exists() {
+            for (index, line) in BufReader::new(File::open(&path)?).lines().enumerate() {
+                let line = line?;
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let entry: SeenNonce = serde_json::from_str(&line)
+                    .map_err(|err| format!("{}:{}: invalid nonce log entry: {}", path.display(), index + 1, err))?;
+                self.seen.insert((entry.uploader, entry.nonce), entry.timestamp);
+            }
+        }
+        self.forget_stale(unix_now());
+        self.log = Some((path.clone(), 0));
+        self.compact()?;
+        Ok(self)
+    }
+
+    /// Drops the nonces of envelopes too old to be accepted anymore.
+    fn forget_stale(&mut self, now: u64) {
+        let max_age = self.max_age.as_secs();
+        self.seen.retain(|_, timestamp| timestamp.saturating_add(max_age) >= now);
+    }
+
+    /// Rewrites the nonce log with only the nonces still remembered.
+    fn compact(&mut self) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let Some((path, lines)) = &mut self.log else {
+            return Ok(());
+        };
+        let mut contents = String::new();
+        for ((uploader, nonce), timestamp) in &self.seen {
+            let entry = SeenNonce { uploader: *uploader, nonce: nonce.clone(), timestamp: *timestamp };
+            contents.push_str(&serde_json::to_string(&entry)?);
+            contents.push('\n');
+        }
+        // Written aside and renamed, so a crash never leaves a truncated log
+        let staged = path.with_extension("jsonl.tmp");
+        fs::write(&staged, contents)?;
+        fs::rename(&staged, &*path)?;
+        *lines = self.seen.len();
+        Ok(())
+    }
+
+    /// Appends an accepted envelope to the nonce log, if there is one.
+    fn append(&mut self, uploader: Address, nonce: &str, timestamp: u64) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let Some((path, lines)) = &mut self.log else {
+            return Ok(());
+        };
+        let entry = SeenNonce { uploader, nonce: nonce.to_string(), timestamp };
+        let mut file = OpenOptions::new().create(true).append(true).open(&*path)?;
+        writeln!(file, "{}", serde_json::to_string(&entry)?)?;
+        file.sync_data()?;
+        *lines += 1;
+        if *lines > 2 * self.seen.len() {
+            self.compact()?;
+        }
+        Ok(())
+    }
+
+    /// Checks that an envelope is inside the acceptance window and was not seen before, without
+    /// recording it.
+    ///
+    /// # Parameters
+    /// - `signer`: The verified uploader of the envelope.
+    /// - `envelope`: The envelope.
+    /// - `now`: The current time, in seconds since the Unix epoch.
+    ///
+    /// # Returns
+    /// - `Result<(), EnvelopeError>`: Why the envelope is rejected, if it is.
+    pub fn precheck(&self, signer: Address, envelope: &ObservationEnvelope, now: u64) -> Result<(), EnvelopeError> {
+        let max_age = self.max_age.as_secs();
+        if envelope.timestamp.saturating_add(max_age) < now {
+            return Err(EnvelopeError::Stale { timestamp: envelope.timestamp, now });
+        }
+        if envelope.timestamp > now.saturating_add(self.max_skew.as_secs()) {
+            return Err(EnvelopeError::FromFuture { timestamp: envelope.timestamp, now });
+        }
+        // Nonces of envelopes too old to be accepted anymore are forgotten
+        let seen = self.seen.get(&(signer, envelope.nonce.clone()));
+        if seen.is_some_and(|timestamp| timestamp.saturating_add(max_age) >= now) {
+            return Err(EnvelopeError::Replayed { signer, nonce: envelope.nonce.clone() });
+        }
+        Ok(())
+    }
+
+    /// Records the nonce of an envelope `precheck` accepted, so it is not accepted again.
+    ///
+    /// # Parameters
+    /// - `signer`: The verified uploader of the envelope.
+    /// - `envelope`: The envelope.
+    /// - `now`: The current time, in seconds since the Unix epoch.
+    ///
+    /// # Returns
+    /// - `Result<(), EnvelopeError>`: `Unrecorded` if the nonce could not be written to the nonce
+    ///   log; it is still remembered until the guard is dropped.
+    pub fn record(&mut self, signer: Address, envelope: &ObservationEnvelope, now: u64) -> Result<(), EnvelopeError> {
+        self.forget_stale(now);
+        self.seen.insert((signer, envelope.nonce.clone()), envelope.timestamp);
+        self.append(signer, &envelope.nonce, envelope.timestamp).map_err(|err| EnvelopeError::Unrecorded(err.to_string()))
+    }
+
+    /// Records an envelope, unless it is outside the acceptance window or was seen before.
+    ///
+    /// # Parameters
+    /// - `signer`: The verified uploader of the envelope.
+    /// - `envelope`: The envelope.
+    /// - `now`: The current time, in seconds since the Unix epoch.
+    ///
+    /// # Returns
+    /// - `Result<(), EnvelopeError>`: Why the envelope is rejected, if it is.
+    pub fn check(&mut self, signer: Address, envelope: &ObservationEnvelope, now: u64) -> Result<(), EnvelopeError> {
+        self.forget_stale(now);
+        self.precheck(signer, envelope, now)?;
+        // An envelope whose nonce could not be logged is refused, or a restart would accept it again
+        if let Err(err) = self.record(signer, envelope, now) {
+            self.seen.remove(&(signer, envelope.nonce.clone()));
+            return Err(err);
+        }
+        Ok(())
+    }
+
+    /// Parses, verifies and records an envelope.
+    ///
+    /// The nonce is only recorded once the signature checks out, so forged envelopes cannot
+    /// use up an uploader's nonces.
+    ///
+    /// # Parameters
+    /// - `json`: The envelope JSON.
+    /// - `now`: The current time, in seconds since the Unix epoch.
+    ///
+    /// # Returns
+    /// - `Result<ObservationEnvelope, EnvelopeError>`: The accepted envelope.
+    pub fn open(&mut self, json: &str, now: u64) -> Result<ObservationEnvelope, EnvelopeError> {
+        let envelope = ObservationEnvelope::parse(json)?;
+        let signer = envelope.verify()?;
+        self.check(signer, &envelope, now)?;
+        Ok(envelope)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::signers::{LocalWallet, Signer};
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_open_rejects_replays_and_envelopes_outside_the_window() {
+        let wallet: LocalWallet = "4c0883a69102937d6231471b5dbb6204fe5129617082796e8e1a1e3b7a1e7e3e".parse().unwrap();
+        let payload = json!({ "uploader": format!("{:?}", wallet.address()) });
+        let signed_at = |nonce: &str, timestamp: u64| {
+            let payload = payload.clone();
+            let wallet = wallet.clone();
+            let nonce = nonce.to_string();
+            async move {
+                serde_json::to_string(&ObservationEnvelope::sign(payload, nonce, timestamp, &wallet).await.unwrap()).unwrap()
+            }
+        };
+        let mut guard = ReplayGuard::new(Duration::from_secs(100), Duration::from_secs(10));
+
+        let first = signed_at("a", 1_000).await;
+        assert!(guard.open(&first, 1_050).is_ok());
+        assert!(matches!(guard.open(&first, 1_060), Err(EnvelopeError::Replayed { .. })));
+        assert!(guard.open(&signed_at("b", 1_000).await, 1_060).is_ok());
+
+        assert!(matches!(guard.open(&signed_at("c", 900).await, 1_060), Err(EnvelopeError::Stale { .. })));
+        assert!(matches!(guard.open(&signed_at("d", 1_100).await, 1_060), Err(EnvelopeError::FromFuture { .. })));
+        assert!(guard.open(&signed_at("e", 1_065).await, 1_060).is_ok());
+
+        // Once the first envelope has aged out, replaying it is rejected by its timestamp.
+        assert!(matches!(guard.open(&first, 1_200), Err(EnvelopeError::Stale { .. })));
+        assert!(guard.seen.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_persisted_guard_remembers_nonces_across_restarts() {
+        let wallet: LocalWallet = "4c0883a69102937d6231471b5dbb6204fe5129617082796e8e1a1e3b7a1e7e3e".parse().unwrap();
+        let payload = json!({ "uploader": format!("{:?}", wallet.address()) });
+        let signed = ObservationEnvelope::sign(payload, "a", unix_now() - 100, &wallet).await.unwrap();
+        let envelope = serde_json::to_string(&signed).unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let log = dir.path().join(DEFAULT_NONCE_LOG);
+
+        let mut guard = ReplayGuard::default().persisted(&log).unwrap();
+        assert!(guard.open(&envelope, unix_now()).is_ok());
+        drop(guard);
+
+        let mut restarted = ReplayGuard::default().persisted(&log).unwrap();
+        assert!(matches!(restarted.open(&envelope, unix_now()), Err(EnvelopeError::Replayed { .. })));
+
+        // Nonces too old to matter are dropped from the log when it is loaded
+        let restarted = ReplayGuard::new(Duration::from_secs(50), DEFAULT_MAX_SKEW).persisted(&log).unwrap();
+        assert!(restarted.seen.is_empty());
+        assert_eq!(fs::read_to_string(&log).unwrap(), "");
+    }
+}
@@ -0,0 +1,161 @@
+use ethers::types::H256;
+use ethers::utils::keccak256;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::str::FromStr;
+
+/// The observation field holding the hash of the raw input, as an array of bytes.
+pub const HASH_INPUTDATA_FIELD: &str = "hash_inputdata";
+
+/// The observation field declaring how `hash_inputdata` was computed, `keccak256` when absent.
+pub const HASH_ALGORITHM_FIELD: &str = "hash_algorithm";
+
+/// The hash functions an AI node may use to identify its raw input.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum InputHashAlgorithm {
+    #[default]
+    Keccak256,
+    Sha256,
+}
+
+impl InputHashAlgorithm {
+    pub const ALL: [InputHashAlgorithm; 2] = [InputHashAlgorithm::Keccak256, InputHashAlgorithm::Sha256];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            InputHashAlgorithm::Keccak256 => "keccak256",
+            InputHashAlgorithm::Sha256 => "sha256",
+        }
+    }
+
+    /// Hashes a raw input.
+    pub fn digest(&self, bytes: &[u8]) -> H256 {
+        match self {
+            InputHashAlgorithm::Keccak256 => H256(keccak256(bytes)),
+            InputHashAlgorithm::Sha256 => H256(Sha256::digest(bytes).into()),
+        }
+    }
+}
+
+impl fmt::Display for InputHashAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for InputHashAlgorithm {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        InputHashAlgorithm::ALL
+            .into_iter()
+            .find(|algorithm| algorithm.name() == name)
+            .ok_or_else(|| format!("unsupported hash algorithm `{}`", name))
+    }
+}
+
+/// Identifies the raw game input an observation was extracted from.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct InputReference {
+    pub algorithm: InputHashAlgorithm,
+    pub digest: H256,
+}
+
+impl InputReference {
+    /// The reference to a raw input.
+    ///
+    /// # Parameters
+    /// - `bytes`: The raw input, e.g. the screenshot file or the log chunk.
+    /// - `algorithm`: The hash function to identify it with.
+    ///
+    /// # Returns
+    /// - `InputReference`: The reference, to be written into observations of the input.
+    pub fn of(bytes: &[u8], algorithm: InputHashAlgorithm) -> Self {
+        InputReference { algorithm, digest: algorithm.digest(bytes) }
+    }
+
+    /// Reads the reference an observation declares.
+    ///
+    /// # Parameters
+    /// - `observation`: The observation.
+    ///
+    /// # Returns
+    /// - `Result<InputReference, String>`: The reference, or why the observation's fields are invalid.
+    pub fn from_observation(observation: &Value) -> Result<Self, String> {
+        let algorithm = match &observation[HASH_ALGORITHM_FIELD] {
+            Value::Null => InputHashAlgorithm::default(),
+            Value::String(name) => name.parse()?,
+            _ => return Err(format!("`{}` is not a string", HASH_ALGORITHM_FIELD)),
+        };
+        let bytes = observation[HASH_INPUTDATA_FIELD]
+            .as_array()
+            .ok_or_else(|| format!("missing byte array `{}`", HASH_INPUTDATA_FIELD))?
+            .iter()
+            .map(|byte| byte.as_u64().filter(|byte| *byte <= u8::MAX as u64).map(|byte| byte as u8))
+            .collect::<Option<Vec<u8>>>()
+            .ok_or_else(|| format!("`{}` is not an array of bytes", HASH_INPUTDATA_FIELD))?;
+        if bytes.len() != H256::len_bytes() {
+            return Err(format!(
+                "`{}` holds {} bytes, a {} digest has {}",
+                HASH_INPUTDATA_FIELD,
+                bytes.len(),
+                algorithm,
+                H256::len_bytes()
+            ));
+        }
+        Ok(InputReference { algorithm, digest: H256::from_slice(&bytes) })
+    }
+
+    /// Writes the reference into an observation's `hash_inputdata` and `hash_algorithm` fields.
+    pub fn write_to(&self, observation: &mut Value) {
+        if let Value::Object(fields) = observation {
+            fields.insert(HASH_INPUTDATA_FIELD.to_string(), json!(self.digest.as_bytes()));
+            fields.insert(HASH_ALGORITHM_FIELD.to_string(), json!(self.algorithm.name()));
+        }
+    }
+
+    /// Whether `bytes` is the raw input this reference identifies.
+    pub fn matches(&self, bytes: &[u8]) -> bool {
+        self.algorithm.digest(bytes) == self.digest
+    }
+}
+
+impl fmt::Display for InputReference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{:?}", self.algorithm, self.digest)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reference_round_trips_through_an_observation() {
+        let input = b"frame bytes";
+        for algorithm in InputHashAlgorithm::ALL {
+            let reference = InputReference::of(input, algorithm);
+            let mut observation = json!({ "character": "Mage" });
+            reference.write_to(&mut observation);
+
+            assert_eq!(InputReference::from_observation(&observation).unwrap(), reference);
+            assert!(reference.matches(input));
+            assert!(!reference.matches(b"another frame"));
+        }
+        assert_ne!(InputReference::of(input, InputHashAlgorithm::Keccak256).digest, InputReference::of(input, InputHashAlgorithm::Sha256).digest);
+    }
+
+    #[test]
+    fn test_from_observation_defaults_to_keccak256_and_rejects_bad_fields() {
+        let sample: Value = serde_json::from_str(include_str!("../json_objects/json1.json")).unwrap();
+        assert_eq!(InputReference::from_observation(&sample).unwrap().algorithm, InputHashAlgorithm::Keccak256);
+
+        assert!(InputReference::from_observation(&json!({ "hash_inputdata": [1, 2, 3] })).is_err());
+        assert!(InputReference::from_observation(&json!({ "hash_inputdata": vec![256; 32] })).is_err());
+        assert!(InputReference::from_observation(&json!({ "hash_inputdata": vec![0; 32], "hash_algorithm": "md5" })).is_err());
+        assert!(InputReference::from_observation(&json!({ "character": "Mage" })).is_err());
+    }
+}
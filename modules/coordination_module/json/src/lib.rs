@@ -1,5 +1,9 @@
 use std::fs;
 
+pub mod envelope;
+pub mod input_data;
+pub mod timestamp;
+
 /// Asynchronously loads JSON objects from a specified directory.
 ///
 /// # Parameters
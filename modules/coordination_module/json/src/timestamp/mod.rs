@@ -0,0 +1 @@
+pub mod observation_time;
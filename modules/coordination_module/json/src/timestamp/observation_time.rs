@@ -0,0 +1,144 @@
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+use std::fmt;
+use std::str::FromStr;
+
+/// The observation field holding when the AI node made the observation.
+pub const TIMESTAMP_FIELD: &str = "timestamp";
+
+/// When an AI node made an observation.
+///
+/// Two formats are accepted, and nothing else:
+/// - UTC as written by the nodes, `2024-08-12 16:35:35.952737580 UTC`, with up to nine
+///   fractional digits.
+/// - RFC 3339, such as `2024-08-12T16:35:35.952Z` or `2024-08-12T18:35:35+02:00`.
+///
+/// Times are written back in the first format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ObservationTime(pub DateTime<Utc>);
+
+impl ObservationTime {
+    pub fn now() -> Self {
+        ObservationTime(Utc::now())
+    }
+
+    /// The time `millis` milliseconds after the Unix epoch.
+    pub fn from_unix_millis(millis: u64) -> Option<Self> {
+        DateTime::from_timestamp_millis(i64::try_from(millis).ok()?).map(ObservationTime)
+    }
+
+    /// Parses a time in one of the supported formats.
+    ///
+    /// # Parameters
+    /// - `text`: The time, without surrounding whitespace.
+    ///
+    /// # Returns
+    /// - `Result<ObservationTime, String>`: The time, or why it is not in a supported format.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        if text.trim() != text {
+            return Err(format!("timestamp `{}` has surrounding whitespace", text));
+        }
+        if let Some(naive) = text.strip_suffix(" UTC") {
+            return NaiveDateTime::parse_from_str(naive, "%Y-%m-%d %H:%M:%S%.f")
+                .map(|naive| ObservationTime(naive.and_utc()))
+                .map_err(|err| format!("invalid UTC timestamp `{}`: {}", text, err));
+        }
+        if text.contains('T') {
+            return DateTime::parse_from_rfc3339(text)
+                .map(|time| ObservationTime(time.with_timezone(&Utc)))
+                .map_err(|err| format!("invalid RFC 3339 timestamp `{}`: {}", text, err));
+        }
+        Err(format!("unsupported timestamp format `{}`", text))
+    }
+
+    /// Reads the time an observation was made.
+    ///
+    /// # Parameters
+    /// - `observation`: The observation.
+    ///
+    /// # Returns
+    /// - `Result<ObservationTime, String>`: The time, or why the `timestamp` field is invalid.
+    pub fn from_observation(observation: &Value) -> Result<Self, String> {
+        let text = observation[TIMESTAMP_FIELD]
+            .as_str()
+            .ok_or_else(|| format!("missing string field `{}`", TIMESTAMP_FIELD))?;
+        ObservationTime::parse(text)
+    }
+
+    pub fn unix_millis(&self) -> i64 {
+        self.0.timestamp_millis()
+    }
+
+    /// How many seconds this time is after `earlier`, negative if it is before.
+    pub fn seconds_since(&self, earlier: &ObservationTime) -> f64 {
+        let elapsed = self.0 - earlier.0;
+        match elapsed.num_microseconds() {
+            Some(micros) => micros as f64 / 1e6,
+            None => elapsed.num_seconds() as f64,
+        }
+    }
+}
+
+impl fmt::Display for ObservationTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl FromStr for ObservationTime {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        ObservationTime::parse(text)
+    }
+}
+
+impl Serialize for ObservationTime {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for ObservationTime {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        ObservationTime::parse(&text).map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_supported_formats() {
+        let sample: Value = serde_json::from_str(include_str!("../json_objects/json1.json")).unwrap();
+        let time = ObservationTime::from_observation(&sample).unwrap();
+
+        assert_eq!(time.to_string(), "2024-08-12 16:35:35.952737580 UTC");
+        assert_eq!(ObservationTime::parse(&time.to_string()).unwrap(), time);
+        assert_eq!(ObservationTime::parse("2024-08-12T18:35:35.952737580+02:00").unwrap(), time);
+        assert_eq!(
+            ObservationTime::parse("2024-08-12 16:35:35 UTC").unwrap().seconds_since(&time),
+            -0.952737
+        );
+    }
+
+    #[test]
+    fn test_parse_rejects_other_formats() {
+        for text in [
+            "2024-08-12 16:35:35.952737580",
+            " 2024-08-12 16:35:35 UTC",
+            "2024-08-12 16:35:35 CET",
+            "2024-08-12",
+            "2024-13-12 16:35:35 UTC",
+            "2024-08-12T16:35:35",
+            "1723480535",
+            "12/08/2024 16:35:35 UTC",
+        ] {
+            assert!(ObservationTime::parse(text).is_err(), "{} was accepted", text);
+        }
+        assert!(ObservationTime::from_observation(&serde_json::json!({ "timestamp": 1723480535 })).is_err());
+    }
+}
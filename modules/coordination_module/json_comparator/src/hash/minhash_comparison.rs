@@ -1,9 +1,10 @@
 use probabilistic_collections::similarity::{MinHash, ShingleIterator};
-use colored::*;
+use probabilistic_collections::SipHasherBuilder;
+use serde::{Deserialize, Serialize};
 
 use crate::data::json_set::json_to_sets;
 
-/// Calculates and prints the similarities between JSON objects using MinHash.
+/// Calculates and logs the similarities between JSON objects using MinHash.
 ///
 /// # Parameters
 /// - `json_strs`: A vector of JSON strings.
@@ -25,23 +26,13 @@ pub fn calculate_similarities(json_strs: Vec<&str>, similarity_threshold: f64, n
     for i in 0..min_hashes.len() {
         for j in i + 1..min_hashes.len() {
             let similarity = min_hash.get_similarity_from_hashes(&min_hashes[i], &min_hashes[j]);
-            if similarity >= similarity_threshold {
-                println!(
-                    "Similarity between JSON {} and JSON {}: {:.1}% - {}",
-                    i + 1,
-                    j + 1,
-                    similarity * 100.0,
-                    "PASS".green()
-                );
-            } else {
-                println!(
-                    "Similarity between JSON {} and JSON {}: {:.1}% - {}",
-                    i + 1,
-                    j + 1,
-                    similarity * 100.0,
-                    "NOT PASS".red()
-                );
-            }
+            tracing::info!(
+                left = i + 1,
+                right = j + 1,
+                similarity = similarity,
+                passed = similarity >= similarity_threshold,
+                "similarity between JSON objects"
+            );
         }
     }
 }
@@ -82,4 +73,112 @@ pub fn find_best_similarity(json_strs: Vec<&str>, similarity_threshold: f64, num
     }
 
     best_json
-}
\ No newline at end of file
+}
+
+/// The similarity of one pair of JSON objects.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PairSimilarity {
+    /// The position of the first JSON object.
+    pub left: usize,
+    /// The position of the second JSON object.
+    pub right: usize,
+    pub similarity: f64,
+    /// Whether the similarity meets the threshold.
+    pub passed: bool,
+}
+
+/// Every pairwise similarity of a comparison, and the JSON object it picked.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ComparisonReport {
+    /// The seed of the MinHash hash functions. Comparing again with it gives the same report.
+    pub seed: u64,
+    pub similarities: Vec<PairSimilarity>,
+    /// The position of the JSON object with the highest similarity, if any pair met the threshold.
+    pub best: Option<usize>,
+    /// The confidence score of each JSON object, when they weighted the choice of `best`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weights: Option<Vec<f64>>,
+}
+
+/// Compares every pair of JSON objects with seeded MinHash hash functions.
+///
+/// The winner is picked the same way as in `find_best_similarity`, but the hash functions are
+/// derived from `seed` instead of fresh entropy, so the scores can be reproduced.
+///
+/// # Parameters
+/// - `json_strs`: A vector of JSON strings.
+/// - `similarity_threshold`: The threshold for considering two JSON objects similar.
+/// - `num_hash_functions`: The number of hash functions to use in MinHash.
+/// - `seed`: The seed of the hash functions.
+///
+/// # Returns
+/// - `ComparisonReport`: The pairwise similarities and the position of the best JSON object.
+pub fn seeded_similarity_report(json_strs: Vec<&str>, similarity_threshold: f64, num_hash_functions: usize, seed: u64) -> ComparisonReport {
+    let json_sets = json_to_sets(&json_strs);
+
+    let min_hash = MinHash::with_hashers(
+        num_hash_functions,
+        [SipHasherBuilder::from_seed(seed, !seed), SipHasherBuilder::from_seed(!seed, seed)],
+    );
+
+    let min_hashes: Vec<_> = json_sets
+        .iter()
+        .map(|set| {
+            let shingles = ShingleIterator::new(1, set.iter().map(|s| s.as_str()).collect());
+            min_hash.get_min_hashes(shingles)
+        })
+        .collect();
+
+    let mut similarities = Vec::new();
+    let mut best_similarity = 0.0;
+    let mut best = None;
+
+    for i in 0..min_hashes.len() {
+        for j in (i + 1)..min_hashes.len() {
+            let similarity = min_hash.get_similarity_from_hashes(&min_hashes[i], &min_hashes[j]);
+            let passed = similarity >= similarity_threshold;
+            if passed && similarity > best_similarity {
+                best_similarity = similarity;
+                best = Some(j);
+            }
+            similarities.push(PairSimilarity { left: i, right: j, similarity, passed });
+        }
+    }
+
+    ComparisonReport { seed, similarities, best, weights: None }
+}
+
+/// Picks the most confident of the JSON objects that agree the most.
+///
+/// Scores are reported by whoever made the JSON objects, so they only break ties: the candidates
+/// are both JSON objects of every pair that met the threshold with the highest similarity, the
+/// pairs `seeded_similarity_report` picks from, and the one with the highest score wins. A high
+/// score cannot make a less similar JSON object win. On a tie the first candidate found wins, the
+/// later object of a pair before the earlier one, as in `seeded_similarity_report`.
+///
+/// # Parameters
+/// - `similarities`: The pairwise similarities of a report.
+/// - `weights`: The confidence score of each JSON object, from 0 to 1.
+///
+/// # Returns
+/// - `Option<usize>`: The position of the best JSON object, if any pair met the threshold.
+pub fn weighted_best(similarities: &[PairSimilarity], weights: &[f64]) -> Option<usize> {
+    let passed = || similarities.iter().filter(|pair| pair.passed);
+    let highest = passed().map(|pair| pair.similarity).fold(None, |highest: Option<f64>, similarity| {
+        Some(highest.map_or(similarity, |highest| highest.max(similarity)))
+    })?;
+    let mut best_weight = 0.0;
+    let mut best = None;
+
+    for pair in passed().filter(|pair| pair.similarity == highest) {
+        for candidate in [pair.right, pair.left] {
+            let weight = weights.get(candidate).copied().unwrap_or(1.0);
+            if best.is_none() || weight > best_weight {
+                best_weight = weight;
+                best = Some(candidate);
+            }
+        }
+    }
+
+    best
+}
@@ -1,11 +1,96 @@
-use colored::*;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::hash_map::RandomState;
 use std::env;
+use std::hash::{BuildHasher, Hasher};
 
 #[macro_use]
 pub mod data;
 pub mod hash;
 
-use hash::minhash_comparison::{calculate_similarities, find_best_similarity};
+use hash::minhash_comparison::{seeded_similarity_report, weighted_best};
+pub use hash::minhash_comparison::{ComparisonReport, PairSimilarity};
+
+/// Settings of the MinHash comparison.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct ComparatorSettings {
+    /// The threshold for considering two JSON objects similar.
+    pub similarity_threshold: f64,
+    /// The number of hash functions to use in MinHash.
+    pub num_hash_functions: usize,
+    /// The seed of the MinHash hash functions. Without one, every comparison draws a fresh seed.
+    #[serde(default)]
+    pub seed: Option<u64>,
+    /// Whether the confidence `score` of each JSON object breaks ties between the most similar ones.
+    #[serde(default)]
+    pub confidence_weighted: bool,
+}
+
+impl Default for ComparatorSettings {
+    fn default() -> Self {
+        ComparatorSettings {
+            similarity_threshold: 0.72,
+            num_hash_functions: 100,
+            seed: None,
+            confidence_weighted: false,
+        }
+    }
+}
+
+impl ComparatorSettings {
+    /// Reads the settings from `SIMILARITY_THRESHOLD`, `NUM_HASH_FUNCTIONS`, `MINHASH_SEED` and
+    /// `CONFIDENCE_WEIGHTED`.
+    ///
+    /// # Returns
+    /// - `ComparatorSettings`: The settings, with the defaults for unset variables.
+    pub fn from_env() -> Self {
+        let defaults = ComparatorSettings::default();
+        let similarity_threshold: f64 = env::var("SIMILARITY_THRESHOLD")
+            .map(|value| value.parse().unwrap_or(0.0))
+            .unwrap_or(defaults.similarity_threshold);
+        let num_hash_functions: usize = env::var("NUM_HASH_FUNCTIONS")
+            .map(|value| value.parse().unwrap_or(0))
+            .unwrap_or(defaults.num_hash_functions);
+        let seed = env::var("MINHASH_SEED").ok().and_then(|value| value.parse().ok());
+        let confidence_weighted = env::var("CONFIDENCE_WEIGHTED").is_ok_and(|value| matches!(value.as_str(), "1" | "true"));
+
+        ComparatorSettings { similarity_threshold, num_hash_functions, seed, confidence_weighted }
+    }
+}
+
+/// The fields holding how sure a model was of an observation rather than what it observed.
+///
+/// They are left out of the comparison, so two observations of the same facts are as similar
+/// whatever their confidences.
+pub const CONFIDENCE_FIELDS: [&str; 2] = ["confidence", "score"];
+
+/// The confidence score of a JSON object, its `score` clamped to `[0, 1]`, or 1 without one.
+///
+/// # Parameters
+/// - `json_str`: The JSON string.
+///
+/// # Returns
+/// - `f64`: The confidence score.
+pub fn confidence_score(json_str: &str) -> f64 {
+    serde_json::from_str::<Value>(json_str)
+        .ok()
+        .and_then(|json| json["score"].as_f64())
+        .map(|score| score.clamp(0.0, 1.0))
+        .unwrap_or(1.0)
+}
+
+/// A JSON object without its confidence fields, or the string itself if it is not an object.
+fn without_confidences(json_str: &str) -> String {
+    match serde_json::from_str::<Value>(json_str) {
+        Ok(Value::Object(mut map)) => {
+            for field in CONFIDENCE_FIELDS {
+                map.remove(field);
+            }
+            Value::Object(map).to_string()
+        }
+        _ => json_str.to_string(),
+    }
+}
 
 /// Runs the JSON comparator on a given set of JSON objects.
 ///
@@ -15,25 +100,61 @@ use hash::minhash_comparison::{calculate_similarities, find_best_similarity};
 /// # Returns
 /// - `Option<String>`: The JSON string with the highest similarity, if any.
 pub fn run_json_comparator(json_objects: &[String]) -> Option<String> {
-    let similarity_threshold: f64 = env::var("SIMILARITY_THRESHOLD")
-        .unwrap_or_else(|_| "0.72".to_string())
-        .parse()
-        .unwrap_or(0.0);
-    let num_hash_functions: usize = env::var("NUM_HASH_FUNCTIONS")
-        .unwrap_or_else(|_| "100".to_string())
-        .parse()
-        .unwrap_or(0);
-
-    let json_strs: Vec<&str> = json_objects.iter().map(|s| s.as_str()).collect();
-
-    calculate_similarities(json_strs.clone(), similarity_threshold, num_hash_functions);
-    if let Some(best_json) = find_best_similarity(json_strs, similarity_threshold, num_hash_functions) {
-        println!("\nThe JSON with the best similarity is: {}", best_json.green().bold());
-        return Some(best_json);
-    } else {
-        println!("\nNo JSON objects met the similarity threshold.");
-        return None;
+    compare_json_objects(json_objects, &ComparatorSettings::from_env())
+}
+
+/// Runs the JSON comparator with explicit settings.
+///
+/// # Parameters
+/// - `json_objects`: A slice of JSON strings.
+/// - `settings`: The comparison settings.
+///
+/// # Returns
+/// - `Option<String>`: The JSON string with the highest similarity, if any.
+pub fn compare_json_objects(json_objects: &[String], settings: &ComparatorSettings) -> Option<String> {
+    let report = run_comparison(json_objects, settings);
+    report.best.map(|best| json_objects[best].clone())
+}
+
+/// Runs the JSON comparator and reports every pairwise similarity.
+///
+/// # Parameters
+/// - `json_objects`: A slice of JSON strings.
+/// - `settings`: The comparison settings. Without a seed, a fresh one is drawn and reported.
+///   With `confidence_weighted`, the best JSON object is picked by `weighted_best`: scores only
+///   choose among the JSON objects that agree the most.
+///
+/// # Returns
+/// - `ComparisonReport`: The seed used, the pairwise similarities and the position of the best JSON object.
+pub fn run_comparison(json_objects: &[String], settings: &ComparatorSettings) -> ComparisonReport {
+    let seed = settings.seed.unwrap_or_else(|| RandomState::new().build_hasher().finish());
+    let compared: Vec<String> = json_objects.iter().map(|s| without_confidences(s)).collect();
+    let json_strs: Vec<&str> = compared.iter().map(|s| s.as_str()).collect();
+
+    let span = tracing::info_span!("compare", observations = json_objects.len(), threshold = settings.similarity_threshold, seed);
+    let _entered = span.enter();
+
+    let mut report = seeded_similarity_report(json_strs, settings.similarity_threshold, settings.num_hash_functions, seed);
+    if settings.confidence_weighted {
+        let weights: Vec<f64> = json_objects.iter().map(|s| confidence_score(s)).collect();
+        report.best = weighted_best(&report.similarities, &weights);
+        report.weights = Some(weights);
+    }
+    for pair in &report.similarities {
+        tracing::info!(
+            left = pair.left + 1,
+            right = pair.right + 1,
+            similarity = pair.similarity,
+            passed = pair.passed,
+            "similarity between JSON objects"
+        );
+    }
+
+    match report.best {
+        Some(best) => tracing::info!(best = best + 1, "consensus reached"),
+        None => tracing::info!("no JSON objects met the similarity threshold"),
     }
+    report
 }
 
 #[cfg(test)]
@@ -72,4 +193,44 @@ mod tests {
 
         assert!(result.is_none());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_run_comparison_is_reproducible_with_its_seed() {
+        let json_objects = vec![
+            json!({"a": 1, "b": 2, "c": 3, "d": 4}).to_string(),
+            json!({"a": 1, "b": 2, "c": 3, "d": 5}).to_string(),
+            json!({"a": 9, "b": 8, "c": 7, "d": 6}).to_string(),
+        ];
+        let settings = ComparatorSettings { similarity_threshold: 0.3, ..ComparatorSettings::default() };
+
+        let first = run_comparison(&json_objects, &settings);
+        let second = run_comparison(&json_objects, &ComparatorSettings { seed: Some(first.seed), ..settings });
+
+        assert_eq!(first, second);
+        assert_eq!(first.similarities.len(), 3);
+        assert_eq!(first.best, Some(1));
+    }
+
+    #[test]
+    fn test_confidences_are_not_compared_but_can_weight_the_best() {
+        let observation = |score: f64| json!({"a": 1, "b": 2, "c": 3, "confidence": {"a": score}, "score": score}).to_string();
+        let json_objects = vec![observation(0.9), observation(0.4), json!({"a": 7, "b": 8, "c": 9}).to_string()];
+        let settings = ComparatorSettings { seed: Some(7), ..ComparatorSettings::default() };
+
+        let unweighted = run_comparison(&json_objects, &settings);
+        assert_eq!(unweighted.similarities[0].similarity, 1.0);
+        assert_eq!(unweighted.best, Some(1));
+        assert_eq!(unweighted.weights, None);
+
+        let weighted = run_comparison(&json_objects, &ComparatorSettings { confidence_weighted: true, ..settings });
+        assert_eq!(weighted.similarities, unweighted.similarities);
+        assert_eq!(weighted.best, Some(0));
+        assert_eq!(weighted.weights, Some(vec![0.9, 0.4, 1.0]));
+
+        // A self-reported score of 1 does not make a less similar observation win
+        let json_objects = vec![observation(0.5), observation(0.4), json!({"a": 1, "b": 2, "c": 3, "d": 4, "score": 1.0}).to_string()];
+        let weighted = run_comparison(&json_objects, &ComparatorSettings { confidence_weighted: true, ..settings });
+        assert!(weighted.similarities[1].passed && weighted.similarities[1].similarity < 1.0);
+        assert_eq!(weighted.best, Some(0));
+    }
+}
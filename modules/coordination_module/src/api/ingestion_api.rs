@@ -0,0 +1,300 @@
+use axum::body::Bytes;
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use json::envelope::observation_envelope::{unix_now, EnvelopeError, ObservationEnvelope};
+use json::envelope::replay_guard::ReplayGuard;
+use json::input_data::input_reference::InputReference;
+use serde_json::{json, Value};
+use std::sync::{Arc, Mutex};
+
+use crate::api::input_upload::{input_router, InputUploads};
+use crate::api::round_board::RoundBoard;
+use crate::metrics::coordinator_metrics::CoordinatorMetrics;
+use crate::metrics::metrics_server::metrics_router;
+use crate::pipeline::stages::validation::ObservationValidation;
+
+/// The most envelopes one request may carry.
+pub const MAX_BATCH_SIZE: usize = 1000;
+
+/// A request the API turns down, with the status it answers with.
+#[derive(Debug)]
+pub struct ApiError {
+    pub status: StatusCode,
+    pub message: String,
+}
+
+impl ApiError {
+    pub fn new(status: StatusCode, message: impl Into<String>) -> Self {
+        ApiError { status, message: message.into() }
+    }
+}
+
+impl From<EnvelopeError> for ApiError {
+    fn from(err: EnvelopeError) -> Self {
+        let status = match err {
+            EnvelopeError::Malformed(_) => StatusCode::BAD_REQUEST,
+            EnvelopeError::Replayed { .. } => StatusCode::CONFLICT,
+            EnvelopeError::Unrecorded(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            _ => StatusCode::UNAUTHORIZED,
+        };
+        ApiError::new(status, err.to_string())
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.status, Json(json!({ "error": self.message }))).into_response()
+    }
+}
+
+/// The state shared by the ingestion routes.
+#[derive(Clone)]
+struct IngestionState {
+    board: Arc<RoundBoard>,
+    guard: Arc<Mutex<ReplayGuard>>,
+}
+
+/// The HTTP API through which AI nodes submit observations and follow their rounds.
+///
+/// - `POST /observations`: one signed observation envelope or an array of them. Each envelope
+///   must be signed by its payload's `uploader` and is accepted once; each uploader may submit a
+///   single observation to a round.
+/// - `GET /submissions/{id}`: the status of the round a submission joined.
+/// - `GET /rounds/{id}`: a round's status and, once it ran, its fingerprint.
+/// - `POST /inputs`: a signed raw game input, when an input store is given.
+/// - `GET /metrics`: the coordinator's metrics, when given.
+///
+/// # Parameters
+/// - `board`: The round board submissions are added to.
+/// - `guard`: The guard that remembers the nonces of accepted envelopes.
+/// - `inputs`: The store raw inputs are uploaded to and the uploaders' quota, if any.
+/// - `metrics`: The metrics to serve, if any.
+pub fn ingestion_router(
+    board: Arc<RoundBoard>,
+    guard: Arc<Mutex<ReplayGuard>>,
+    inputs: Option<InputUploads>,
+    metrics: Option<Arc<CoordinatorMetrics>>,
+) -> Router {
+    let mut router = Router::new()
+        .route("/observations", post(submit_observations))
+        .route("/submissions/:id", get(submission_status))
+        .route("/rounds/:id", get(round_status))
+        .with_state(IngestionState { board, guard });
+
+    if let Some(inputs) = inputs {
+        router = router.merge(input_router(inputs));
+    }
+    match metrics {
+        Some(metrics) => router.merge(metrics_router(metrics)),
+        None => router,
+    }
+}
+
+async fn submit_observations(State(state): State<IngestionState>, body: Bytes) -> Result<Response, ApiError> {
+    let payload: Value = serde_json::from_slice(&body).map_err(|err| ApiError::new(StatusCode::BAD_REQUEST, format!("invalid JSON: {}", err)))?;
+    let envelopes = match payload {
+        Value::Array(envelopes) => envelopes,
+        envelope @ Value::Object(_) => vec![envelope],
+        _ => return Err(ApiError::new(StatusCode::BAD_REQUEST, "expected an envelope or an array of envelopes")),
+    };
+    if envelopes.is_empty() || envelopes.len() > MAX_BATCH_SIZE {
+        return Err(ApiError::new(
+            StatusCode::BAD_REQUEST,
+            format!("expected between 1 and {} envelopes", MAX_BATCH_SIZE),
+        ));
+    }
+
+    // Nonces are only recorded once the round took the observations, so a refused request can be
+    // fixed and sent again. The guard stays locked until then, so no other request can use them.
+    let mut opened = Vec::with_capacity(envelopes.len());
+    for (index, envelope) in envelopes.iter().enumerate() {
+        let envelope = ObservationEnvelope::parse(&envelope.to_string()).map_err(|err| at_envelope(index, err.into()))?;
+        let uploader = envelope.verify().map_err(|err| at_envelope(index, err.into()))?;
+        let json = envelope.payload_json();
+        ObservationValidation::check(&json)
+            .and_then(|()| InputReference::from_observation(&envelope.payload).map(|_| ()))
+            .map_err(|err| at_envelope(index, ApiError::new(StatusCode::UNPROCESSABLE_ENTITY, err)))?;
+        opened.push((uploader, envelope, json));
+    }
+    let mut guard = state.guard.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let now = unix_now();
+    for (index, (uploader, envelope, _)) in opened.iter().enumerate() {
+        guard.precheck(*uploader, envelope, now).map_err(|err| at_envelope(index, err.into()))?;
+    }
+
+    let count = opened.len();
+    let uploaders: Vec<String> = opened.iter().map(|(uploader, _, _)| format!("{:?}", uploader)).collect();
+    let accepted = opened.iter().map(|(uploader, _, json)| (*uploader, json.clone())).collect();
+    let (submission_id, round_id) = state.board.submit(accepted).map_err(|err| ApiError::new(StatusCode::CONFLICT, err))?;
+    for (uploader, envelope, _) in &opened {
+        // The observations are in the round already; only a restart could accept the envelope again
+        if let Err(err) = guard.record(*uploader, envelope, now) {
+            tracing::error!(uploader = ?uploader, nonce = %envelope.nonce, reason = %err, "nonce not recorded");
+        }
+    }
+    drop(guard);
+    tracing::info!(submission_id = %submission_id, round_id = %round_id, uploaders = ?uploaders, observations = count, "observations submitted");
+
+    let receipt = json!({ "submission_id": submission_id, "round_id": round_id, "observations": count });
+    Ok((StatusCode::ACCEPTED, Json(receipt)).into_response())
+}
+
+/// Names the envelope of a batch an error is about.
+fn at_envelope(index: usize, err: ApiError) -> ApiError {
+    ApiError::new(err.status, format!("envelope {}: {}", index, err.message))
+}
+
+async fn submission_status(State(state): State<IngestionState>, Path(id): Path<String>) -> Result<Response, ApiError> {
+    let round_id = state
+        .board
+        .submission_round(&id)
+        .ok_or_else(|| ApiError::new(StatusCode::NOT_FOUND, format!("unknown submission {}", id)))?;
+    let round = state.board.round(&round_id);
+    Ok(Json(json!({ "submission_id": id, "round": round })).into_response())
+}
+
+async fn round_status(State(state): State<IngestionState>, Path(id): Path<String>) -> Result<Response, ApiError> {
+    let round = state.board.round(&id).ok_or_else(|| ApiError::new(StatusCode::NOT_FOUND, format!("unknown round {}", id)))?;
+    Ok(Json(round).into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::round_board::{RoundSchedule, RoundState, SubmittedIngestion};
+    use crate::pipeline::builder::Pipeline;
+    use crate::pipeline::round_context::RoundContext;
+    use crate::pipeline::stages::consensus::MinHashConsensus;
+    use crate::pipeline::stages::fingerprinting::FingerprintHashing;
+    use crate::pipeline::stages::memory::InMemorySubmission;
+    use axum::body::{to_bytes, Body};
+    use axum::http::Request;
+    use ethers::signers::{LocalWallet, Signer};
+    use json_comparator::ComparatorSettings;
+    use std::time::Duration;
+    use tower::ServiceExt;
+
+    fn wallet() -> LocalWallet {
+        "4c0883a69102937d6231471b5dbb6204fe5129617082796e8e1a1e3b7a1e7e3e".parse().unwrap()
+    }
+
+    fn router(board: Arc<RoundBoard>) -> Router {
+        ingestion_router(board, Arc::new(Mutex::new(ReplayGuard::default())), None, None)
+    }
+
+    /// A sample observation, uploaded by `uploader`.
+    fn observation(file: &str, uploader: &LocalWallet) -> Value {
+        let mut observation: Value = serde_json::from_str(file).unwrap();
+        observation["uploader"] = json!(format!("{:?}", uploader.address()));
+        observation
+    }
+
+    /// A sample observation, sealed in an envelope by its uploader.
+    async fn envelope(file: &str, uploader: &LocalWallet) -> Value {
+        json!(ObservationEnvelope::seal(observation(file, uploader), uploader).await.unwrap())
+    }
+
+    async fn post(router: &Router, body: &Value) -> (StatusCode, Value) {
+        let request = Request::post("/observations").header("content-type", "application/json");
+        get_response(router, request.body(Body::from(body.to_string())).unwrap()).await
+    }
+
+    async fn get_response(router: &Router, request: Request<Body>) -> (StatusCode, Value) {
+        let response = router.clone().oneshot(request).await.unwrap();
+        let status = response.status();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&body).unwrap_or(Value::Null))
+    }
+
+    #[tokio::test]
+    async fn test_signed_batch_is_accepted_and_its_round_reports_the_fingerprint() {
+        let other = LocalWallet::new(&mut ethers::core::rand::thread_rng());
+        let board = Arc::new(RoundBoard::new());
+        let router = router(board.clone());
+
+        let body = json!([
+            envelope(include_str!("../../json/src/json_objects/json1.json"), &wallet()).await,
+            envelope(include_str!("../../json/src/json_objects/json5.json"), &other).await,
+        ]);
+        let (status, receipt) = post(&router, &body).await;
+
+        assert_eq!(status, StatusCode::ACCEPTED);
+        assert_eq!(receipt["observations"], 2);
+        let round_id = receipt["round_id"].as_str().unwrap().to_string();
+
+        let schedule = RoundSchedule { size: 2, interval: Duration::from_secs(3600) };
+        assert_eq!(board.close_due(schedule), Some(round_id.clone()));
+        let pipeline = Pipeline::builder()
+            .ingestion(SubmittedIngestion { board: board.clone() })
+            .consensus(MinHashConsensus::new(ComparatorSettings::default()))
+            .fingerprinting(FingerprintHashing)
+            .submission(InMemorySubmission::default())
+            .build();
+        let result = pipeline.run(RoundContext::new(round_id.clone())).await;
+        board.finish(&round_id, &result);
+
+        let uri = format!("/submissions/{}", receipt["submission_id"].as_str().unwrap());
+        let (status, submission) = get_response(&router, Request::get(uri).body(Body::empty()).unwrap()).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(submission["round"]["state"], json!(RoundState::Complete));
+        assert_eq!(submission["round"]["hash"], json!(result.unwrap().hash));
+    }
+
+    #[tokio::test]
+    async fn test_unsigned_foreign_and_replayed_observations_are_refused() {
+        let wallet = wallet();
+        let router = router(Arc::new(RoundBoard::new()));
+        let file = include_str!("../../json/src/json_objects/json1.json");
+
+        let (status, _) = post(&router, &observation(file, &wallet)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let other = LocalWallet::new(&mut ethers::core::rand::thread_rng());
+        let forged = json!(ObservationEnvelope::seal(observation(file, &wallet), &other).await.unwrap());
+        let (status, error) = post(&router, &forged).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert!(error["error"].as_str().unwrap().contains("did not sign"));
+
+        let signed = envelope(file, &wallet).await;
+        assert_eq!(post(&router, &signed).await.0, StatusCode::ACCEPTED);
+        let (status, error) = post(&router, &signed).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert!(error["error"].as_str().unwrap().contains("already used"));
+
+        let (status, error) = post(&router, &envelope(file, &wallet).await).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert!(error["error"].as_str().unwrap().contains("already submitted"));
+    }
+
+    #[tokio::test]
+    async fn test_refused_batch_can_be_sent_again() {
+        let other = LocalWallet::new(&mut ethers::core::rand::thread_rng());
+        let router = router(Arc::new(RoundBoard::new()));
+        let file = include_str!("../../json/src/json_objects/json1.json");
+        let taken = envelope(file, &other).await;
+        assert_eq!(post(&router, &taken).await.0, StatusCode::ACCEPTED);
+
+        // Refused batches leave the nonces of their other envelopes unused: a later envelope is
+        // replayed, or the round already has an observation of its uploader
+        let first = envelope(file, &wallet()).await;
+        assert_eq!(post(&router, &json!([first.clone(), taken])).await.0, StatusCode::CONFLICT);
+        assert_eq!(post(&router, &json!([first.clone(), envelope(file, &other).await])).await.0, StatusCode::CONFLICT);
+
+        let (status, receipt) = post(&router, &json!([first.clone()])).await;
+        assert_eq!(status, StatusCode::ACCEPTED);
+        assert_eq!(receipt["observations"], 1);
+        assert_eq!(post(&router, &first).await.0, StatusCode::CONFLICT);
+    }
+
+    #[tokio::test]
+    async fn test_unknown_round_is_not_found() {
+        let router = router(Arc::new(RoundBoard::new()));
+
+        let (status, _) = get_response(&router, Request::get("/rounds/round-0").body(Body::empty()).unwrap()).await;
+
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+}
@@ -0,0 +1,182 @@
+use axum::body::Bytes;
+use axum::extract::{DefaultBodyLimit, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::post;
+use axum::{Json, Router};
+use ethers::types::Address;
+use json::input_data::input_reference::{InputHashAlgorithm, InputReference};
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::api::ingestion_api::ApiError;
+use crate::api::signature_auth::{recover_signer, SIGNATURE_HEADER};
+use crate::inputs::input_store::InputStore;
+
+/// The largest raw input one request may carry, in bytes.
+pub const MAX_INPUT_SIZE: usize = 16 * 1024 * 1024;
+
+/// The bytes of new inputs each uploader may store per `QUOTA_WINDOW`, by default.
+pub const DEFAULT_INPUT_QUOTA: u64 = 256 * 1024 * 1024;
+
+/// How long an uploader's quota lasts before it is renewed.
+pub const QUOTA_WINDOW: Duration = Duration::from_secs(3600);
+
+/// Where `POST /inputs` stores raw inputs, and how many bytes of new inputs each uploader may
+/// store per `QUOTA_WINDOW`.
+pub struct InputUploads {
+    pub store: Arc<InputStore>,
+    pub quota: u64,
+}
+
+struct UploadState {
+    store: Arc<InputStore>,
+    quota: u64,
+    /// When each uploader's current window started, and the bytes it stored since.
+    used: Mutex<HashMap<Address, (Instant, u64)>>,
+}
+
+impl UploadState {
+    /// Charges an upload to its uploader's quota.
+    ///
+    /// # Parameters
+    /// - `uploader`: The verified uploader.
+    /// - `bytes`: The size of the upload.
+    ///
+    /// # Returns
+    /// - `Result<(), String>`: Why the upload exceeds the quota, if it does.
+    fn reserve(&self, uploader: Address, bytes: u64) -> Result<(), String> {
+        let mut used = self.used.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        // Uploaders whose window ended start over, so the map only holds recent uploaders
+        used.retain(|_, (started, _)| started.elapsed() < QUOTA_WINDOW);
+        let (_, stored) = used.entry(uploader).or_insert((Instant::now(), 0));
+        if *stored + bytes > self.quota {
+            return Err(format!(
+                "uploader {:?} stored {} of its {} bytes this window, the input has {}",
+                uploader, stored, self.quota, bytes
+            ));
+        }
+        *stored += bytes;
+        Ok(())
+    }
+
+    /// Gives back an upload that stored nothing new.
+    fn refund(&self, uploader: Address, bytes: u64) {
+        let mut used = self.used.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        if let Some((_, stored)) = used.get_mut(&uploader) {
+            *stored = stored.saturating_sub(bytes);
+        }
+    }
+}
+
+/// A router accepting raw game inputs on `POST /inputs`.
+///
+/// The `X-Signature` header holds the uploader's signature of the body (see `recover_signer`),
+/// and every new input is charged to the signer's quota. Inputs are content-addressed, so an
+/// upload sent again stores nothing and is not charged: an input only counts for the
+/// observations whose `hash_inputdata` it hashes to.
+pub fn input_router(uploads: InputUploads) -> Router {
+    let state = UploadState { store: uploads.store, quota: uploads.quota, used: Mutex::new(HashMap::new()) };
+    Router::new()
+        .route("/inputs", post(upload_input))
+        .layer(DefaultBodyLimit::max(MAX_INPUT_SIZE))
+        .with_state(Arc::new(state))
+}
+
+async fn upload_input(State(state): State<Arc<UploadState>>, headers: HeaderMap, body: Bytes) -> Result<Response, ApiError> {
+    let signature = headers
+        .get(SIGNATURE_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .ok_or_else(|| ApiError::new(StatusCode::UNAUTHORIZED, format!("missing {} header", SIGNATURE_HEADER)))?;
+    let uploader = recover_signer(&body, signature).map_err(|err| ApiError::new(StatusCode::UNAUTHORIZED, err))?;
+    if body.is_empty() {
+        return Err(ApiError::new(StatusCode::BAD_REQUEST, "empty input"));
+    }
+
+    // Inputs the store already holds are not charged, so a replayed upload costs its uploader nothing
+    let size = body.len() as u64;
+    let input = body.clone();
+    let uploads = state.clone();
+    let new = tokio::task::spawn_blocking(move || {
+        if uploads.store.contains(&InputReference::of(&input, InputHashAlgorithm::Keccak256)) {
+            return Ok(false);
+        }
+        uploads.reserve(uploader, size).map_err(|err| ApiError::new(StatusCode::TOO_MANY_REQUESTS, err))?;
+        match uploads.store.put(&input) {
+            Ok((_, new)) => {
+                if !new {
+                    uploads.refund(uploader, size);
+                }
+                Ok(new)
+            }
+            Err(err) => {
+                uploads.refund(uploader, size);
+                Err(ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, format!("cannot store input: {}", err)))
+            }
+        }
+    })
+    .await
+    .map_err(|err| ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, format!("cannot store input: {}", err)))??;
+
+    let mut hash_inputdata = Map::new();
+    for algorithm in InputHashAlgorithm::ALL {
+        let reference = InputReference::of(&body, algorithm);
+        hash_inputdata.insert(algorithm.name().to_string(), json!(reference.digest.as_bytes()));
+    }
+    tracing::info!(bytes = body.len(), uploader = ?uploader, new, "input stored");
+
+    let receipt = json!({ "bytes": body.len(), "hash_inputdata": Value::Object(hash_inputdata) });
+    Ok((StatusCode::CREATED, Json(receipt)).into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::{to_bytes, Body};
+    use axum::http::Request;
+    use ethers::signers::{LocalWallet, Signer};
+    use tempfile::tempdir;
+    use tower::ServiceExt;
+
+    async fn upload(router: &Router, input: &'static str, wallet: Option<&LocalWallet>) -> Response {
+        let mut request = Request::post("/inputs");
+        if let Some(wallet) = wallet {
+            request = request.header(SIGNATURE_HEADER, wallet.sign_message(input).await.unwrap().to_string());
+        }
+        router.clone().oneshot(request.body(Body::from(input)).unwrap()).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_upload_stores_the_input_and_returns_its_hashes() {
+        let dir = tempdir().unwrap();
+        let store = Arc::new(InputStore::open(dir.path()).unwrap());
+        let router = input_router(InputUploads { store: store.clone(), quota: DEFAULT_INPUT_QUOTA });
+        let wallet = LocalWallet::new(&mut ethers::core::rand::thread_rng());
+
+        let response = upload(&router, "replay chunk", Some(&wallet)).await;
+
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let receipt: Value = serde_json::from_slice(&to_bytes(response.into_body(), usize::MAX).await.unwrap()).unwrap();
+        let sha256 = InputReference::of(b"replay chunk", InputHashAlgorithm::Sha256);
+        assert_eq!(receipt["hash_inputdata"]["sha256"], json!(sha256.digest.as_bytes()));
+        assert_eq!(store.get(&sha256).unwrap().unwrap(), b"replay chunk");
+    }
+
+    #[tokio::test]
+    async fn test_uploads_are_signed_and_charged_to_their_uploader() {
+        let dir = tempdir().unwrap();
+        let store = Arc::new(InputStore::open(dir.path()).unwrap());
+        let router = input_router(InputUploads { store, quota: 20 });
+        let wallet = LocalWallet::new(&mut ethers::core::rand::thread_rng());
+        let other = LocalWallet::new(&mut ethers::core::rand::thread_rng());
+
+        assert_eq!(upload(&router, "frame 1", None).await.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(upload(&router, "frame number 1", Some(&wallet)).await.status(), StatusCode::CREATED);
+        // Sending an input the store already holds is not charged
+        assert_eq!(upload(&router, "frame number 1", Some(&wallet)).await.status(), StatusCode::CREATED);
+        assert_eq!(upload(&router, "frame number 2", Some(&wallet)).await.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(upload(&router, "frame number 2", Some(&other)).await.status(), StatusCode::CREATED);
+    }
+}
@@ -0,0 +1,4 @@
+pub mod ingestion_api;
+pub mod input_upload;
+pub mod round_board;
+pub mod signature_auth;
@@ -0,0 +1,401 @@
+use async_trait::async_trait;
+use ethers::types::{Address, H256};
+use fingerprint::Fingerprint;
+use serde::Serialize;
+use json::timestamp::observation_time::ObservationTime;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::pipeline::builder::PipelineError;
+use crate::pipeline::round_context::{unix_millis, Halt, Observation, RoundContext};
+use crate::pipeline::stage::{Stage, StageError, StageOutcome};
+
+/// Where a round is in its life.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum RoundState {
+    /// Still accepting observations.
+    Open,
+    /// Closed and going through the pipeline.
+    Running,
+    Complete,
+    /// Ended early, for example without consensus.
+    Halted,
+    Failed,
+}
+
+/// What a node can learn about a round.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct RoundStatus {
+    pub round_id: String,
+    pub state: RoundState,
+    pub observations: usize,
+    pub submissions: Vec<String>,
+    pub fingerprint: Option<Fingerprint>,
+    pub hash: Option<String>,
+    pub tx_hash: Option<H256>,
+    pub halted: Option<Halt>,
+    pub failure: Option<Halt>,
+}
+
+impl RoundStatus {
+    fn open(round_id: String) -> Self {
+        RoundStatus {
+            round_id,
+            state: RoundState::Open,
+            observations: 0,
+            submissions: Vec::new(),
+            fingerprint: None,
+            hash: None,
+            tx_hash: None,
+            halted: None,
+            failure: None,
+        }
+    }
+}
+
+/// When the open round closes.
+#[derive(Debug, Clone, Copy)]
+pub struct RoundSchedule {
+    /// Close once this many observations arrived.
+    pub size: usize,
+    /// Close this long after the first observation arrived, even if the round is not full.
+    pub interval: Duration,
+}
+
+struct BoardState {
+    open_round: String,
+    opened_at: Option<Instant>,
+    /// The earliest observation timestamp of the open round's first submission.
+    window_start: Option<ObservationTime>,
+    /// Rounds closed because a submission fell outside their time window, oldest first.
+    due: VecDeque<String>,
+    pending: Vec<Observation>,
+    /// The uploaders of the open round's observations.
+    uploaders: HashSet<Address>,
+    /// The observations of closed rounds, until their ingestion stage takes them.
+    closed: HashMap<String, Vec<Observation>>,
+    rounds: HashMap<String, RoundStatus>,
+    /// The round each submission was added to.
+    submissions: HashMap<String, String>,
+}
+
+impl BoardState {
+    /// Closes the open round and opens the next one.
+    ///
+    /// # Returns
+    /// - `String`: The id of the round that closed.
+    fn rotate(&mut self) -> String {
+        let next_round = RoundContext::generate_id();
+        let closed_round = std::mem::replace(&mut self.open_round, next_round.clone());
+        let observations = std::mem::take(&mut self.pending);
+        self.uploaders.clear();
+        self.opened_at = None;
+        self.window_start = None;
+        self.closed.insert(closed_round.clone(), observations);
+        self.rounds.insert(next_round.clone(), RoundStatus::open(next_round));
+        if let Some(status) = self.rounds.get_mut(&closed_round) {
+            status.state = RoundState::Running;
+        }
+        closed_round
+    }
+}
+
+/// Collects submitted observations into rounds and tracks every round's status.
+pub struct RoundBoard {
+    state: Mutex<BoardState>,
+    next_submission: AtomicU64,
+    /// How far after the open round's first observation a submission may have been made to join it.
+    window: Option<Duration>,
+}
+
+impl Default for RoundBoard {
+    fn default() -> Self {
+        RoundBoard::new()
+    }
+}
+
+impl RoundBoard {
+    pub fn new() -> Self {
+        let open_round = RoundContext::generate_id();
+        let mut rounds = HashMap::new();
+        rounds.insert(open_round.clone(), RoundStatus::open(open_round.clone()));
+
+        RoundBoard {
+            state: Mutex::new(BoardState {
+                open_round,
+                opened_at: None,
+                window_start: None,
+                due: VecDeque::new(),
+                pending: Vec::new(),
+                uploaders: HashSet::new(),
+                closed: HashMap::new(),
+                rounds,
+                submissions: HashMap::new(),
+            }),
+            next_submission: AtomicU64::new(1),
+            window: None,
+        }
+    }
+
+    /// Starts a new round whenever a submission's observations were made more than `window` after
+    /// the first observation of the open round, so each round covers one moment of the game.
+    ///
+    /// Nodes observing the same moment arrive in any order, so earlier submissions join the open
+    /// round rather than closing it; the timestamp check keeps the round's observations within
+    /// the window. Backdating an observation therefore cannot close a round.
+    pub fn with_window(mut self, window: Duration) -> Self {
+        self.window = Some(window);
+        self
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, BoardState> {
+        // A panic while holding the lock leaves the board consistent, every update being a single step
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Adds a submission's observations to the open round.
+    ///
+    /// With a time window, a submission made more than the window after the open round's first
+    /// observation closes that round and opens the next one. Each uploader may add a single observation to a round, so a
+    /// submission is refused whole if it repeats an uploader or names one the round already has.
+    ///
+    /// # Parameters
+    /// - `observations`: The verified uploader and JSON string of each observation of one request.
+    ///
+    /// # Returns
+    /// - `Result<(String, String), String>`: The submission id and the id of the round it joined,
+    ///   or why the submission was refused.
+    pub fn submit(&self, observations: Vec<(Address, String)>) -> Result<(String, String), String> {
+        let received_at = unix_millis();
+        let earliest = observations
+            .iter()
+            .filter_map(|(_, json)| serde_json::from_str(json).ok())
+            .filter_map(|observation: Value| ObservationTime::from_observation(&observation).ok())
+            .min();
+        let mut uploaders = HashSet::with_capacity(observations.len());
+        if let Some((uploader, _)) = observations.iter().find(|(uploader, _)| !uploaders.insert(*uploader)) {
+            return Err(format!("uploader {:?} submitted more than one observation", uploader));
+        }
+        let mut state = self.state();
+
+        if let (Some(window), Some(start), Some(earliest)) = (self.window, state.window_start, earliest) {
+            let offset = earliest.seconds_since(&start);
+            if offset > window.as_secs_f64() {
+                let closed_round = BoardState::rotate(&mut state);
+                state.due.push_back(closed_round);
+            }
+        }
+        if let Some(uploader) = uploaders.iter().find(|uploader| state.uploaders.contains(uploader)) {
+            return Err(format!("uploader {:?} already submitted an observation to round {}", uploader, state.open_round));
+        }
+        if state.window_start.is_none() {
+            state.window_start = earliest;
+        }
+        state.uploaders.extend(uploaders);
+        let submission_id = format!("sub-{}", self.next_submission.fetch_add(1, Ordering::Relaxed));
+        let round_id = state.open_round.clone();
+
+        let count = observations.len();
+        state.pending.extend(
+            observations
+                .into_iter()
+                .enumerate()
+                .map(|(index, (uploader, json))| Observation {
+                    source: format!("{}:{}", submission_id, index),
+                    json,
+                    received_at: Some(received_at),
+                    uploader: Some(uploader),
+                }),
+        );
+        state.opened_at.get_or_insert_with(Instant::now);
+        state.submissions.insert(submission_id.clone(), round_id.clone());
+        if let Some(status) = state.rounds.get_mut(&round_id) {
+            status.observations += count;
+            status.submissions.push(submission_id.clone());
+        }
+
+        Ok((submission_id, round_id))
+    }
+
+    /// The round a submission joined.
+    pub fn submission_round(&self, submission_id: &str) -> Option<String> {
+        self.state().submissions.get(submission_id).cloned()
+    }
+
+    pub fn round(&self, round_id: &str) -> Option<RoundStatus> {
+        self.state().rounds.get(round_id).cloned()
+    }
+
+    /// Closes the open round if it is full or has waited long enough, and opens the next one.
+    ///
+    /// # Parameters
+    /// - `schedule`: When rounds close.
+    ///
+    /// # Returns
+    /// - `Option<String>`: The id of the round that closed and must now be run.
+    pub fn close_due(&self, schedule: RoundSchedule) -> Option<String> {
+        let mut state = self.state();
+        if let Some(closed_round) = state.due.pop_front() {
+            return Some(closed_round);
+        }
+        let opened_at = state.opened_at?;
+        if state.pending.len() < schedule.size && opened_at.elapsed() < schedule.interval {
+            return None;
+        }
+
+        Some(BoardState::rotate(&mut state))
+    }
+
+    /// Takes the observations of a closed round.
+    pub fn take_observations(&self, round_id: &str) -> Vec<Observation> {
+        self.state().closed.remove(round_id).unwrap_or_default()
+    }
+
+    /// Records how a round ended.
+    ///
+    /// # Parameters
+    /// - `round_id`: The round.
+    /// - `result`: What the pipeline returned for it.
+    pub fn finish(&self, round_id: &str, result: &Result<RoundContext, PipelineError>) {
+        let mut state = self.state();
+        state.closed.remove(round_id);
+        let Some(status) = state.rounds.get_mut(round_id) else {
+            return;
+        };
+
+        match result {
+            Ok(round) => {
+                status.state = match (&round.halted, &round.failure) {
+                    (_, Some(_)) => RoundState::Failed,
+                    (Some(_), None) => RoundState::Halted,
+                    (None, None) => RoundState::Complete,
+                };
+                status.fingerprint = round.fingerprint.clone();
+                status.hash = round.hash.clone();
+                status.tx_hash = round.submission.as_ref().and_then(|submission| submission.tx_hash);
+                status.halted = round.halted.clone();
+                status.failure = round.failure.clone();
+            }
+            Err(err) => {
+                status.state = RoundState::Failed;
+                status.failure = Some(Halt { stage: err.stage, reason: err.to_string() });
+            }
+        }
+    }
+}
+
+/// Feeds a round the observations nodes submitted to it.
+pub struct SubmittedIngestion {
+    pub board: Arc<RoundBoard>,
+}
+
+#[async_trait]
+impl Stage for SubmittedIngestion {
+    fn name(&self) -> &str {
+        "http"
+    }
+
+    async fn run(&self, round: &mut RoundContext) -> Result<StageOutcome, StageError> {
+        round.observations.extend(self.board.take_observations(&round.round_id));
+        if round.observations.is_empty() {
+            return Ok(StageOutcome::Halt("no observations".to_string()));
+        }
+        tracing::info!(observations = round.observations.len(), "observations loaded");
+        Ok(StageOutcome::Continue)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// One observation from each of the uploaders `from`.
+    fn from(uploaders: &[u8]) -> Vec<(Address, String)> {
+        uploaders.iter().map(|uploader| (Address::repeat_byte(*uploader), "{}".to_string())).collect()
+    }
+
+    #[test]
+    fn test_round_closes_when_full() {
+        let board = RoundBoard::new();
+        let schedule = RoundSchedule { size: 3, interval: Duration::from_secs(3600) };
+
+        let (first, round_id) = board.submit(from(&[1, 2])).unwrap();
+        assert_eq!(board.close_due(schedule), None);
+        let (second, same_round) = board.submit(from(&[3])).unwrap();
+        assert_eq!(round_id, same_round);
+
+        assert_eq!(board.close_due(schedule), Some(round_id.clone()));
+        assert_eq!(board.round(&round_id).unwrap().state, RoundState::Running);
+        assert_eq!(board.round(&round_id).unwrap().submissions, vec![first.clone(), second]);
+        let sources: Vec<String> = board.take_observations(&round_id).into_iter().map(|observation| observation.source).collect();
+        assert_eq!(sources, vec!["sub-1:0", "sub-1:1", "sub-2:0"]);
+
+        let (_, next_round) = board.submit(from(&[1])).unwrap();
+        assert_ne!(next_round, round_id);
+        assert_eq!(board.submission_round(&first), Some(round_id));
+    }
+
+    #[test]
+    fn test_submission_outside_the_window_starts_a_new_round() {
+        let board = RoundBoard::new().with_window(Duration::from_secs(10));
+        let schedule = RoundSchedule { size: 100, interval: Duration::from_secs(3600) };
+        let at = |uploader: u8, time: &str| vec![(Address::repeat_byte(uploader), format!(r#"{{"timestamp": "{}"}}"#, time))];
+
+        let (_, first_round) = board.submit(at(1, "2024-08-12 16:35:30 UTC")).unwrap();
+        let (_, same_round) = board.submit(at(2, "2024-08-12 16:35:38 UTC")).unwrap();
+        let (_, second_round) = board.submit(at(1, "2024-08-12 16:35:45 UTC")).unwrap();
+
+        assert_eq!(first_round, same_round);
+        assert_ne!(first_round, second_round);
+        assert_eq!(board.close_due(schedule), Some(first_round.clone()));
+        assert_eq!(board.close_due(schedule), None);
+        assert_eq!(board.take_observations(&first_round).len(), 2);
+        assert!(board.take_observations(&first_round).is_empty());
+        assert_eq!(board.round(&second_round).unwrap().observations, 1);
+    }
+
+    #[test]
+    fn test_earlier_submissions_join_the_open_round() {
+        let board = RoundBoard::new().with_window(Duration::from_secs(10));
+        let schedule = RoundSchedule { size: 100, interval: Duration::from_secs(3600) };
+        let at = |uploader: u8, time: &str| vec![(Address::repeat_byte(uploader), format!(r#"{{"timestamp": "{}"}}"#, time))];
+
+        // Nodes observing one moment arrive out of order, and a backdated observation cannot close the round
+        let (_, first_round) = board.submit(at(1, "2024-08-12 16:35:30 UTC")).unwrap();
+        let (_, earlier) = board.submit(at(2, "2024-08-12 16:35:29 UTC")).unwrap();
+        let (_, backdated) = board.submit(at(3, "2024-08-12 15:00:00 UTC")).unwrap();
+        let (_, later) = board.submit(at(4, "2024-08-12 16:35:40 UTC")).unwrap();
+
+        assert_eq!(vec![earlier, backdated, later], vec![first_round.clone(); 3]);
+        assert_eq!(board.close_due(schedule), None);
+        assert_eq!(board.round(&first_round).unwrap().observations, 4);
+    }
+
+    #[test]
+    fn test_uploader_submits_once_per_round() {
+        let board = RoundBoard::new();
+        let schedule = RoundSchedule { size: 100, interval: Duration::ZERO };
+
+        let (_, round_id) = board.submit(from(&[1])).unwrap();
+        assert!(board.submit(from(&[2, 2])).unwrap_err().contains("more than one observation"));
+        assert!(board.submit(from(&[2, 1])).unwrap_err().contains("already submitted"));
+        assert_eq!(board.round(&round_id).unwrap().observations, 1);
+
+        assert_eq!(board.close_due(schedule), Some(round_id));
+        assert!(board.submit(from(&[1])).is_ok());
+    }
+
+    #[test]
+    fn test_round_closes_after_its_interval() {
+        let board = RoundBoard::new();
+        let schedule = RoundSchedule { size: 100, interval: Duration::ZERO };
+
+        assert_eq!(board.close_due(schedule), None);
+        let (_, round_id) = board.submit(from(&[1])).unwrap();
+        assert_eq!(board.close_due(schedule), Some(round_id));
+    }
+}
@@ -0,0 +1,39 @@
+use ethers::types::{Address, Signature};
+
+/// The header holding the node's signature of the request body.
+pub const SIGNATURE_HEADER: &str = "x-signature";
+
+/// Recovers the address that signed a request body.
+///
+/// Nodes sign the exact body bytes as an EIP-191 personal message, as `eth_sign` and
+/// `personal_sign` do.
+///
+/// # Parameters
+/// - `body`: The raw request body.
+/// - `signature`: The 65-byte signature, as hex.
+///
+/// # Returns
+/// - `Result<Address, String>`: The signer's address, or why the signature is invalid.
+pub fn recover_signer(body: &[u8], signature: &str) -> Result<Address, String> {
+    let signature: Signature = signature.trim().parse().map_err(|err| format!("invalid signature: {}", err))?;
+    signature.recover(body).map_err(|err| format!("invalid signature: {}", err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::signers::{LocalWallet, Signer};
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_recover_signer() {
+        let wallet: LocalWallet = "4c0883a69102937d6231471b5dbb6204fe5129617082796e8e1a1e3b7a1e7e3e".parse().unwrap();
+        let body = json!({ "uploader": format!("{:?}", wallet.address()) }).to_string();
+        let signature = wallet.sign_message(body.as_bytes()).await.unwrap();
+
+        let signer = recover_signer(body.as_bytes(), &signature.to_string()).unwrap();
+
+        assert_eq!(signer, wallet.address());
+        assert!(recover_signer(b"another body", &signature.to_string()).map(|other| other != signer).unwrap_or(true));
+    }
+}
@@ -0,0 +1,2 @@
+pub mod replay;
+pub mod round_log;
@@ -0,0 +1,216 @@
+use ai_module::registry::model_registry::ModelRegistry;
+use serde::Serialize;
+use serde_json::Value;
+use std::sync::Arc;
+
+use crate::audit::round_log::RoundRecord;
+use crate::inputs::input_store::InputStore;
+use crate::pipeline::builder::{Pipeline, PipelineError};
+use crate::pipeline::round_context::RoundContext;
+use crate::pipeline::stages::chain::StageChain;
+use crate::pipeline::stages::consensus::MinHashConsensus;
+use crate::pipeline::stages::fingerprinting::FingerprintHashing;
+use crate::pipeline::stages::input_data::InputDataValidation;
+use crate::pipeline::stages::memory::InMemoryIngestion;
+use crate::pipeline::stages::model::ModelValidation;
+use crate::pipeline::stages::timestamp::TimestampValidation;
+use crate::pipeline::stages::validation::ObservationValidation;
+
+/// The outcome of replaying a logged round.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct ReplayReport {
+    pub round_id: String,
+    /// The sources of the inputs whose content no longer matches their logged hash.
+    pub tampered_inputs: Vec<String>,
+    /// The logged fields the replay did not reproduce.
+    pub differences: Vec<String>,
+    /// The record of the replayed round.
+    pub replayed: RoundRecord,
+}
+
+impl ReplayReport {
+    /// Whether the replay reproduced the logged round from intact inputs.
+    pub fn matches(&self) -> bool {
+        self.tampered_inputs.is_empty() && self.differences.is_empty()
+    }
+}
+
+/// Re-executes a logged round up to its fingerprint hash and compares the outcome with the log.
+///
+/// The replay runs the logged inputs through validation, the comparison, with the logged settings
+/// and seed, and fingerprinting. Nothing is submitted.
+///
+/// # Parameters
+/// - `record`: The logged round.
+/// - `inputs`: The input store the round verified raw inputs against, if it did.
+/// - `models`: The model registry the round checked models against, if it did.
+///
+/// # Returns
+/// - `Result<ReplayReport, PipelineError>`: What the replay reproduced, or the stage that failed.
+pub async fn replay_round(
+    record: &RoundRecord,
+    inputs: Option<Arc<InputStore>>,
+    models: Option<Arc<ModelRegistry>>,
+) -> Result<ReplayReport, PipelineError> {
+    let input_data = match inputs {
+        Some(store) => InputDataValidation::verified_against(store),
+        None => InputDataValidation::new(),
+    };
+    // Rounds logged before timestamps were checked skip the check
+    let mut validation = StageChain::new().then(ObservationValidation);
+    if let Some(registry) = models {
+        validation = validation.then(ModelValidation { registry });
+    }
+    if let Some(window) = record.config.time_window {
+        validation = validation.then(TimestampValidation { window });
+    }
+    let pipeline = Pipeline::builder()
+        .ingestion(InMemoryIngestion { observations: record.inputs.iter().map(|input| input.to_observation()).collect() })
+        .validation(validation.then(input_data))
+        .consensus(MinHashConsensus::new(record.config.comparator.unwrap_or_default()))
+        .fingerprinting(FingerprintHashing)
+        .build();
+
+    // Rejections of sources that are not logged inputs were made while loading, before the
+    // replayed stages, so the replay starts from them
+    let mut round = RoundContext::new(record.round_id.clone());
+    // Timestamps are checked against when the logged round ran, not against now
+    round.started_at = match record.started_at {
+        0 => record.recorded_at * 1000,
+        started_at => started_at,
+    };
+    round.rejected = record
+        .rejected
+        .iter()
+        .filter(|rejection| !record.inputs.iter().any(|input| input.source == rejection.source))
+        .cloned()
+        .collect();
+    let round = pipeline.run(round).await?;
+    let mut replayed = RoundRecord::from_round(&round, record.config.network.clone(), record.config.dry_run);
+    replayed.recorded_at = record.recorded_at;
+    replayed.tx_hash = record.tx_hash;
+
+    // A round that halted or failed after fingerprinting cannot do so again without submitting
+    let replayed_stages = pipeline.stage_kinds();
+    let expected_halt = record.halted.clone().filter(|halt| replayed_stages.contains(&halt.stage));
+    let expected_failure = record.failure.clone().filter(|failure| replayed_stages.contains(&failure.stage));
+
+    let mut differences = Vec::new();
+    let mut compare = |field: &str, logged: Value, replayed: Value| {
+        if logged != replayed {
+            differences.push(field.to_string());
+        }
+    };
+    compare("model_registry", json_of(&record.config.model_registry), json_of(&replayed.config.model_registry));
+    compare("rejected", json_of(&record.rejected), json_of(&replayed.rejected));
+    compare("model_family", json_of(&record.model_family), json_of(&replayed.model_family));
+    compare("input_data", json_of(&record.input_data), json_of(&replayed.input_data));
+    compare("comparison", json_of(&record.comparison), json_of(&replayed.comparison));
+    compare("winner", json_of(&record.winner), json_of(&replayed.winner));
+    compare("fingerprint", json_of(&record.fingerprint), json_of(&replayed.fingerprint));
+    compare("hash", json_of(&record.hash), json_of(&replayed.hash));
+    compare("halted", json_of(&expected_halt), json_of(&replayed.halted));
+    compare("failure", json_of(&expected_failure), json_of(&replayed.failure));
+
+    Ok(ReplayReport {
+        round_id: record.round_id.clone(),
+        tampered_inputs: record.inputs.iter().filter(|input| !input.is_intact()).map(|input| input.source.clone()).collect(),
+        differences,
+        replayed,
+    })
+}
+
+/// Compares logged and replayed fields through JSON, as they are stored in the log.
+fn json_of<T: Serialize>(value: &T) -> Value {
+    serde_json::to_value(value).unwrap_or(Value::Null)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audit::round_log::RoundLog;
+    use crate::pipeline::round_context::Rejection;
+    use crate::pipeline::stages::memory::{InMemoryIngestion, InMemorySubmission};
+    use crate::pipeline::stages::timestamp::TimeWindow;
+    use json::timestamp::observation_time::ObservationTime;
+    use json_comparator::ComparatorSettings;
+    use tempfile::tempdir;
+
+    fn observations() -> Vec<String> {
+        vec![
+            include_str!("../../json/src/json_objects/json1.json").to_string(),
+            include_str!("../../json/src/json_objects/json2.json").to_string(),
+            include_str!("../../json/src/json_objects/json5.json").to_string(),
+        ]
+    }
+
+    async fn logged_round(log: &std::path::Path) -> RoundRecord {
+        let pipeline = Pipeline::builder()
+            .ingestion(InMemoryIngestion::from_json(observations()))
+            .validation(
+                StageChain::new()
+                    .then(ObservationValidation)
+                    .then(TimestampValidation { window: TimeWindow { max_age: Some(60), ..TimeWindow::default() } })
+                    .then(InputDataValidation::new()),
+            )
+            .consensus(MinHashConsensus::new(ComparatorSettings::default()))
+            .fingerprinting(FingerprintHashing)
+            .submission(InMemorySubmission::default())
+            .storage(RoundLog::new(log, Some("local".to_string()), false))
+            .build();
+        // The samples were made half a minute before the round, which replays must reproduce
+        let mut round = RoundContext::new("round-1");
+        round.started_at = ObservationTime::parse("2024-08-12 16:36:05 UTC").unwrap().unix_millis() as u64;
+        pipeline.run(round).await.unwrap();
+
+        RoundLog::find(log, "round-1").unwrap().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_round_log_records_the_round() {
+        let dir = tempdir().unwrap();
+        let log = dir.path().join("rounds.jsonl");
+
+        let record = logged_round(&log).await;
+
+        assert_eq!(record.inputs.len(), 3);
+        assert!(record.inputs.iter().all(|input| input.is_intact()));
+        assert!(record.config.comparator.unwrap().seed.is_some());
+        // json2 was extracted from another input than json1 and json5
+        assert_eq!(record.rejected.len(), 1);
+        assert!(record.input_data.is_some());
+        assert_eq!(record.config.time_window.unwrap().max_age, Some(60));
+        assert_eq!(record.comparison.as_ref().unwrap().similarities.len(), 1);
+        assert!(record.winner.is_some());
+        assert!(record.hash.is_some());
+        assert!(record.tx_hash.is_some());
+        assert_eq!(RoundLog::load(&log).unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_replay_reproduces_a_logged_round() {
+        let dir = tempdir().unwrap();
+        let mut record = logged_round(&dir.path().join("rounds.jsonl")).await;
+        // As logged when a file was rejected while loading
+        record.rejected.insert(0, Rejection { source: "inbox/forged.json".to_string(), reason: "invalid signature".to_string() });
+
+        let report = replay_round(&record, None, None).await.unwrap();
+
+        assert!(report.matches(), "{:?}", report.differences);
+        assert_eq!(report.replayed.hash, record.hash);
+    }
+
+    #[tokio::test]
+    async fn test_replay_detects_edited_inputs_and_results() {
+        let dir = tempdir().unwrap();
+        let mut record = logged_round(&dir.path().join("rounds.jsonl")).await;
+        record.inputs[0].json = record.inputs[0].json.replace("kqiyqnihok", "someone-else");
+        record.hash = Some(format!("0x{}", "00".repeat(32)));
+
+        let report = replay_round(&record, None, None).await.unwrap();
+
+        assert!(!report.matches());
+        assert_eq!(report.tampered_inputs, vec!["memory:0".to_string()]);
+        assert!(report.differences.contains(&"hash".to_string()));
+    }
+}
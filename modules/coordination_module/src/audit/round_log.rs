@@ -0,0 +1,216 @@
+use ai_module::registry::model_registry::ModelFamily;
+use async_trait::async_trait;
+use ethers::types::H256;
+use ethers::utils::keccak256;
+use fingerprint::Fingerprint;
+use json::input_data::input_reference::InputReference;
+use json_comparator::{ComparatorSettings, ComparisonReport};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::error::Error;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::pipeline::round_context::{Halt, Observation, Rejection, RoundContext};
+use crate::pipeline::stage::{Stage, StageError, StageOutcome};
+use crate::pipeline::stages::timestamp::TimeWindow;
+
+/// The default path of the round log.
+pub const DEFAULT_ROUND_LOG: &str = "rounds.jsonl";
+
+/// An observation as it entered a round.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct LoggedInput {
+    pub source: String,
+    /// The keccak256 hash of `json`, to detect inputs edited after the round.
+    pub content_hash: String,
+    pub json: String,
+    /// When the coordinator received the observation, in milliseconds since the Unix epoch.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub received_at: Option<u64>,
+}
+
+impl LoggedInput {
+    pub fn from_observation(observation: &Observation) -> Self {
+        LoggedInput {
+            source: observation.source.clone(),
+            content_hash: content_hash(&observation.json),
+            json: observation.json.clone(),
+            received_at: observation.received_at,
+        }
+    }
+
+    pub fn to_observation(&self) -> Observation {
+        Observation { source: self.source.clone(), json: self.json.clone(), received_at: self.received_at, uploader: None }
+    }
+
+    /// Whether `json` still hashes to `content_hash`.
+    pub fn is_intact(&self) -> bool {
+        content_hash(&self.json) == self.content_hash
+    }
+}
+
+/// The keccak256 hash of an observation, as `0x`-prefixed hex.
+pub fn content_hash(json: &str) -> String {
+    format!("{:?}", H256(keccak256(json.as_bytes())))
+}
+
+/// The configuration a round ran with.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RoundConfig {
+    /// The network profile, if the round was run against one.
+    pub network: Option<String>,
+    pub dry_run: bool,
+    /// The comparator settings, with the seed the comparison used.
+    pub comparator: Option<ComparatorSettings>,
+    /// The time limits the observations were checked against.
+    #[serde(default)]
+    pub time_window: Option<TimeWindow>,
+    /// The digest of the model registry the observations' models were checked against.
+    #[serde(default)]
+    pub model_registry: Option<H256>,
+}
+
+/// One line of the round log: everything needed to audit and replay a round.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RoundRecord {
+    pub round_id: String,
+    /// When the round started, in milliseconds since the Unix epoch.
+    #[serde(default)]
+    pub started_at: u64,
+    /// When the round was logged, in seconds since the Unix epoch.
+    pub recorded_at: u64,
+    pub config: RoundConfig,
+    pub inputs: Vec<LoggedInput>,
+    pub rejected: Vec<Rejection>,
+    /// The models whose observations were compared.
+    #[serde(default)]
+    pub model_family: Option<ModelFamily>,
+    /// The raw game input the compared observations were extracted from.
+    #[serde(default)]
+    pub input_data: Option<InputReference>,
+    pub comparison: Option<ComparisonReport>,
+    /// The observation the comparison picked.
+    pub winner: Option<Value>,
+    pub fingerprint: Option<Fingerprint>,
+    pub hash: Option<String>,
+    /// The transaction that appended the hash, if one was sent.
+    pub tx_hash: Option<H256>,
+    pub halted: Option<Halt>,
+    pub failure: Option<Halt>,
+}
+
+impl RoundRecord {
+    /// Builds the record of a finished round.
+    ///
+    /// # Parameters
+    /// - `round`: The round, as the pipeline left it.
+    /// - `network`: The network profile the round ran against, if any.
+    /// - `dry_run`: Whether the round only planned its submission.
+    pub fn from_round(round: &RoundContext, network: Option<String>, dry_run: bool) -> Self {
+        RoundRecord {
+            round_id: round.round_id.clone(),
+            started_at: round.started_at,
+            recorded_at: SystemTime::now().duration_since(UNIX_EPOCH).map(|elapsed| elapsed.as_secs()).unwrap_or_default(),
+            config: RoundConfig {
+                network,
+                dry_run,
+                comparator: round.comparator,
+                time_window: round.time_window,
+                model_registry: round.model_registry,
+            },
+            inputs: round.inputs.iter().map(LoggedInput::from_observation).collect(),
+            rejected: round.rejected.clone(),
+            model_family: round.model_family.clone(),
+            input_data: round.input_data,
+            comparison: round.comparison.clone(),
+            winner: round.consensus.clone(),
+            fingerprint: round.fingerprint.clone(),
+            hash: round.hash.clone(),
+            tx_hash: round.submission.as_ref().and_then(|submission| submission.tx_hash),
+            halted: round.halted.clone(),
+            failure: round.failure.clone(),
+        }
+    }
+}
+
+/// An append-only log of rounds, one JSON record per line.
+pub struct RoundLog {
+    pub path: PathBuf,
+    pub network: Option<String>,
+    pub dry_run: bool,
+}
+
+impl RoundLog {
+    pub fn new(path: impl Into<PathBuf>, network: Option<String>, dry_run: bool) -> Self {
+        RoundLog { path: path.into(), network, dry_run }
+    }
+
+    /// Appends a record to the log, creating the log if needed.
+    ///
+    /// # Parameters
+    /// - `path`: The path of the log.
+    /// - `record`: The record to append.
+    ///
+    /// # Returns
+    /// - `Result<(), Box<dyn Error + Send + Sync>>`: An error if the log could not be written.
+    pub fn append(path: &Path, record: &RoundRecord) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let mut line = serde_json::to_string(record)?;
+        line.push('\n');
+
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        file.write_all(line.as_bytes())?;
+        file.sync_data()?;
+        Ok(())
+    }
+
+    /// Reads every record of a log.
+    ///
+    /// # Parameters
+    /// - `path`: The path of the log.
+    ///
+    /// # Returns
+    /// - `Result<Vec<RoundRecord>, Box<dyn Error + Send + Sync>>`: The records, oldest first.
+    pub fn load(path: &Path) -> Result<Vec<RoundRecord>, Box<dyn Error + Send + Sync>> {
+        let contents = fs::read_to_string(path).map_err(|err| format!("cannot read {}: {}", path.display(), err))?;
+
+        contents
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(number, line)| {
+                serde_json::from_str(line)
+                    .map_err(|err| format!("{} line {}: {}", path.display(), number + 1, err).into())
+            })
+            .collect()
+    }
+
+    /// Finds the record of a round.
+    ///
+    /// # Parameters
+    /// - `path`: The path of the log.
+    /// - `round_id`: The round's identifier.
+    ///
+    /// # Returns
+    /// - `Result<Option<RoundRecord>, Box<dyn Error + Send + Sync>>`: The round's record, if it was logged.
+    pub fn find(path: &Path, round_id: &str) -> Result<Option<RoundRecord>, Box<dyn Error + Send + Sync>> {
+        Ok(RoundLog::load(path)?.into_iter().find(|record| record.round_id == round_id))
+    }
+}
+
+#[async_trait]
+impl Stage for RoundLog {
+    fn name(&self) -> &str {
+        "round_log"
+    }
+
+    async fn run(&self, round: &mut RoundContext) -> Result<StageOutcome, StageError> {
+        let record = RoundRecord::from_round(round, self.network.clone(), self.dry_run);
+        RoundLog::append(&self.path, &record)?;
+
+        round.stored_at = Some(self.path.display().to_string());
+        Ok(StageOutcome::Continue)
+    }
+}
@@ -0,0 +1,179 @@
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use std::net::SocketAddr;
+use std::path::PathBuf;
+
+use coordination_module::api::input_upload::DEFAULT_INPUT_QUOTA;
+use coordination_module::audit::round_log::DEFAULT_ROUND_LOG;
+use coordination_module::pipeline::stages::timestamp::DEFAULT_MAX_CLOCK_SKEW;
+use json::envelope::replay_guard::{DEFAULT_MAX_AGE, DEFAULT_MAX_SKEW, DEFAULT_NONCE_LOG};
+use fingerprint::network::profile::{DEFAULT_NETWORK_CONFIG, DEFAULT_NETWORK_PROFILE, FINGERPRINT_PROXY_ENV};
+
+/// Coordinates AI observations into on-chain fingerprints.
+#[derive(Parser, Debug)]
+#[command(name = "coordination_module", version)]
+pub struct Cli {
+    #[command(flatten)]
+    pub global: GlobalArgs,
+
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+/// Flags shared by every subcommand.
+#[derive(Args, Debug, Clone)]
+pub struct GlobalArgs {
+    /// The network configuration file.
+    #[arg(long, global = true, env = "NETWORK_CONFIG", default_value = DEFAULT_NETWORK_CONFIG)]
+    pub config: PathBuf,
+
+    /// The network profile to use, e.g. `local`, `sepolia` or `mainnet`.
+    #[arg(long, global = true, env = "NETWORK_PROFILE", default_value = DEFAULT_NETWORK_PROFILE)]
+    pub network: String,
+
+    /// The fingerprint proxy address, overriding the selected profile's `contracts.fingerprint_proxy`.
+    #[arg(long, global = true, env = FINGERPRINT_PROXY_ENV)]
+    pub fingerprint_proxy: Option<String>,
+
+    /// How results are printed.
+    #[arg(long, global = true, value_enum, default_value_t = OutputFormat::Human)]
+    pub format: OutputFormat,
+
+    /// Show what would be submitted without sending any transaction.
+    #[arg(long, global = true)]
+    pub dry_run: bool,
+
+    /// How logs are written to stderr. Levels are set with `RUST_LOG`, `info` by default.
+    #[arg(long, global = true, env = "LOG_FORMAT", value_enum, default_value_t = LogFormat::Text)]
+    pub log_format: LogFormat,
+
+    /// The append-only log every coordination round is recorded in.
+    #[arg(long, global = true, env = "ROUND_LOG", default_value = DEFAULT_ROUND_LOG)]
+    pub round_log: PathBuf,
+
+    /// A directory of raw game inputs to verify every observation's `hash_inputdata` against.
+    #[arg(long, global = true, env = "INPUT_STORE")]
+    pub input_store: Option<PathBuf>,
+
+    /// Only accept observations of the models in this registry, see `ai_module::registry`.
+    #[arg(long, global = true, env = "MODEL_REGISTRY")]
+    pub model_registry: Option<PathBuf>,
+
+    /// How far ahead of the coordinator's clock an observation's timestamp may be, in seconds.
+    #[arg(long, global = true, env = "MAX_CLOCK_SKEW", default_value_t = DEFAULT_MAX_CLOCK_SKEW)]
+    pub max_clock_skew: u64,
+
+    /// Reject observations made longer than this before they were received, in seconds.
+    #[arg(long, global = true, env = "MAX_OBSERVATION_AGE")]
+    pub max_observation_age: Option<u64>,
+
+    /// Only compare observations made within this many seconds of each other in one round.
+    #[arg(long, global = true, env = "ROUND_WINDOW")]
+    pub round_window: Option<u64>,
+
+    /// Only load signed observation envelopes from directories, rejecting bare observations.
+    #[arg(long, global = true, env = "REQUIRE_ENVELOPES")]
+    pub require_envelopes: bool,
+
+    /// How long after signing an envelope is still accepted, in seconds.
+    #[arg(long, global = true, env = "ENVELOPE_MAX_AGE", default_value_t = DEFAULT_MAX_AGE.as_secs())]
+    pub envelope_max_age: u64,
+
+    /// How far ahead of the local clock an envelope may be signed, in seconds.
+    #[arg(long, global = true, env = "ENVELOPE_MAX_SKEW", default_value_t = DEFAULT_MAX_SKEW.as_secs())]
+    pub envelope_max_skew: u64,
+
+    /// The log the nonces of accepted envelopes are remembered in across restarts.
+    #[arg(long, global = true, env = "NONCE_LOG", default_value = DEFAULT_NONCE_LOG)]
+    pub nonce_log: PathBuf,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq)]
+pub enum OutputFormat {
+    Human,
+    Json,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq)]
+pub enum LogFormat {
+    /// Human-readable lines, colored when stderr is a terminal.
+    Text,
+    /// One JSON object per event, with the fields of its spans.
+    Json,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Compare the observations in a directory and report the consensus observation.
+    Compare {
+        /// The directory holding the observation JSON files.
+        dir: PathBuf,
+    },
+    /// Compute the fingerprint and hash of an observation file.
+    Fingerprint {
+        /// The observation JSON file.
+        file: PathBuf,
+    },
+    /// Run a full coordination round on a directory: compare, fingerprint and submit.
+    Run {
+        /// The directory holding the observation JSON files.
+        dir: PathBuf,
+    },
+    /// Check whether a fingerprint hash is on chain, and where it was appended.
+    Verify {
+        /// The fingerprint hash.
+        hash: String,
+        /// An observation file that should produce `hash`.
+        #[arg(long)]
+        observation: Option<PathBuf>,
+    },
+    /// Watch a directory and run a coordination round whenever new observations arrive.
+    Watch {
+        /// The directory holding the observation JSON files.
+        dir: PathBuf,
+        /// How often to look for new observations, in seconds.
+        #[arg(long, default_value_t = 10)]
+        interval: u64,
+        /// Serve Prometheus metrics on `/metrics` at this address, e.g. `0.0.0.0:9100`.
+        #[arg(long, env = "METRICS_ADDR")]
+        metrics_addr: Option<SocketAddr>,
+    },
+    /// Serve the HTTP ingestion API and run a round whenever enough observations arrived.
+    Serve {
+        /// The address to listen on.
+        #[arg(long, env = "API_ADDR", default_value = "127.0.0.1:8080")]
+        addr: SocketAddr,
+        /// Close a round once this many observations arrived.
+        #[arg(long, default_value_t = 3)]
+        round_size: usize,
+        /// Close a round this many seconds after its first observation, even if it is not full.
+        #[arg(long, default_value_t = 30)]
+        round_interval: u64,
+        /// The bytes of new raw inputs each uploader may store on `POST /inputs` per hour.
+        #[arg(long, env = "INPUT_QUOTA", default_value_t = DEFAULT_INPUT_QUOTA)]
+        input_quota: u64,
+    },
+    /// Re-run a logged round and confirm that it reaches the same result.
+    Replay {
+        /// The round's identifier, as printed when the round ran.
+        round_id: String,
+    },
+    /// Index and query on-chain fingerprint appends.
+    #[command(subcommand)]
+    Index(IndexCommand),
+}
+
+#[derive(Subcommand, Debug)]
+pub enum IndexCommand {
+    /// Follow the chain and keep the local index up to date.
+    Follow,
+    /// List the fingerprints appended since a block.
+    Since {
+        block: u64,
+    },
+    /// Show who appended a fingerprint hash.
+    Who {
+        hash: String,
+    },
+    /// Check that every indexed hash is still appended on chain.
+    Audit,
+}
@@ -0,0 +1,525 @@
+use ai_module::registry::model_registry::ModelRegistry;
+use coordination_module::api::ingestion_api::ingestion_router;
+use coordination_module::api::input_upload::InputUploads;
+use coordination_module::api::round_board::{RoundBoard, RoundSchedule, SubmittedIngestion};
+use coordination_module::audit::replay::replay_round;
+use coordination_module::audit::round_log::RoundLog;
+use coordination_module::inputs::input_store::InputStore;
+use coordination_module::metrics::clock_skew::{clock_skew_router, ClockSkewStats};
+use coordination_module::metrics::coordinator_metrics::CoordinatorMetrics;
+use coordination_module::metrics::metrics_server::serve_metrics;
+use coordination_module::pipeline::builder::{Pipeline, PipelineError};
+use coordination_module::pipeline::observer::RoundObserver;
+use coordination_module::pipeline::round_context::RoundContext;
+use coordination_module::pipeline::stage::{Stage, StageKind};
+use coordination_module::pipeline::stages::chain::StageChain;
+use coordination_module::pipeline::stages::consensus::MinHashConsensus;
+use coordination_module::pipeline::stages::fingerprinting::FingerprintHashing;
+use coordination_module::pipeline::stages::ingestion::DirectoryIngestion;
+use coordination_module::pipeline::stages::input_data::InputDataValidation;
+use coordination_module::pipeline::stages::model::ModelValidation;
+use coordination_module::pipeline::stages::submission::{ChainSubmission, DryRunNode, DryRunSubmission};
+use coordination_module::pipeline::stages::timestamp::{TimeWindow, TimestampValidation};
+use coordination_module::pipeline::stages::validation::{FingerprintFieldValidation, ObservationValidation};
+use ethers::prelude::*;
+use fingerprint::check::check_batch::check_fingerprints;
+use fingerprint::indexer::fingerprint_indexer::FingerprintIndexer;
+use fingerprint::indexer::index_store::IndexStore;
+use fingerprint::indexer::log_source::ProviderLogSource;
+use fingerprint::network::connect::{connect_provider, ChainIdMismatch};
+use fingerprint::network::profile::{NetworkConfig, NetworkProfile};
+use fingerprint::signer::signer_config::SignerConfig;
+use fingerprint::verify::verify_hash::{fingerprint_observation, locate_hash, verify_observation};
+use fingerprint::FingerprintClient;
+use json::envelope::replay_guard::ReplayGuard;
+use serde_json::json;
+use std::fs;
+use std::net::SocketAddr;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::cli::args::{Cli, Command, GlobalArgs, IndexCommand};
+use crate::cli::output::{exit_code, CliError, Output};
+
+/// Runs a parsed command line.
+///
+/// # Parameters
+/// - `cli`: The parsed command line.
+///
+/// # Returns
+/// - `i32`: The process exit code, see `exit_code`.
+pub async fn run(cli: Cli) -> i32 {
+    let output = Output { format: cli.global.format };
+
+    match execute(cli, output).await {
+        Ok(code) => code,
+        Err(err) => {
+            output.error(&err);
+            err.exit_code()
+        }
+    }
+}
+
+async fn execute(cli: Cli, output: Output) -> Result<i32, CliError> {
+    let global = cli.global;
+
+    match cli.command {
+        Command::Compare { dir } => compare(&global, &dir, output).await,
+        Command::Fingerprint { file } => fingerprint_file(&file, output),
+        Command::Run { dir } => {
+            let ingestion = directory_ingestion(&global, DirectoryIngestion::new(dir), !global.dry_run)?;
+            let pipeline = round_pipeline(&global, ingestion, Vec::new(), output).await?;
+            let round = pipeline.run(RoundContext::start()).await.map_err(stage_error)?;
+            Ok(report_round(&round, output))
+        }
+        Command::Serve { addr, round_size, round_interval, input_quota } => {
+            let schedule = RoundSchedule { size: round_size, interval: Duration::from_secs(round_interval) };
+            serve(&global, addr, schedule, input_quota, output).await
+        }
+        Command::Replay { round_id } => replay(&global, &round_id, output).await,
+        Command::Verify { hash, observation } => verify(&global, &hash, observation.as_deref(), output).await,
+        Command::Watch { dir, interval, metrics_addr } => {
+            watch(&global, &dir, Duration::from_secs(interval), metrics_addr, output).await
+        }
+        Command::Index(command) => index(&global, command, output).await,
+    }
+}
+
+/// Loads and validates the selected network profile.
+fn load_profile(global: &GlobalArgs) -> Result<NetworkProfile, CliError> {
+    let config_path = global.config.to_str().ok_or_else(|| CliError::config("config path is not valid UTF-8"))?;
+    NetworkConfig::load(config_path)
+        .and_then(|config| config.profile(&global.network, global.fingerprint_proxy.as_deref()))
+        .map_err(CliError::config)
+}
+
+/// Connects to the selected network without a signer.
+async fn connect_reader(global: &GlobalArgs) -> Result<(Provider<Http>, NetworkProfile), CliError> {
+    let profile = load_profile(global)?;
+    let provider = connect_provider(&profile).await.map_err(|err| {
+        if err.downcast_ref::<ChainIdMismatch>().is_some() {
+            CliError::config(err)
+        } else {
+            CliError::chain(err)
+        }
+    })?;
+    Ok((provider, profile))
+}
+
+/// Connects to the selected network and loads the signer.
+async fn connect(global: &GlobalArgs) -> Result<(Arc<FingerprintClient>, NetworkProfile), CliError> {
+    let (provider, profile) = connect_reader(global).await?;
+    let signer_config = match &profile.signer {
+        Some(signer_config) => signer_config.clone(),
+        None => SignerConfig::from_env().map_err(CliError::config)?,
+    };
+    let signer = signer_config.load(profile.chain_id).await.map_err(CliError::config)?;
+    Ok((Arc::new(SignerMiddleware::new(provider, signer)), profile))
+}
+
+/// Builds the pipeline of a coordination round.
+///
+/// A real run needs a valid profile, a node and a signer. A dry run uses whatever of them is
+/// available and explains what is missing, so it also works fully offline.
+///
+/// # Parameters
+/// - `global`: The global flags, selecting the network and `--dry-run`.
+/// - `ingestion`: Where the round's observations come from.
+/// - `observers`: What to update after every round in long-running mode, such as the metrics.
+/// - `output`: Where to report what a dry run is missing.
+async fn round_pipeline(
+    global: &GlobalArgs,
+    ingestion: impl Stage + 'static,
+    observers: Vec<Arc<dyn RoundObserver>>,
+    output: Output,
+) -> Result<Pipeline, CliError> {
+    let mut builder = Pipeline::builder()
+        .ingestion(ingestion)
+        .validation(validation(global, ObservationValidation)?)
+        .consensus(MinHashConsensus::from_env())
+        .fingerprinting(FingerprintHashing)
+        .storage(RoundLog::new(&global.round_log, Some(global.network.clone()), global.dry_run));
+    for observer in observers {
+        builder = builder.observer(observer);
+    }
+
+    if !global.dry_run {
+        let (client, profile) = connect(global).await?;
+        return Ok(builder.submission(ChainSubmission { client, profile }).build());
+    }
+
+    let (provider, profile) = match connect_reader(global).await {
+        Ok(connected) => connected,
+        Err(err) => {
+            output.note(&format!("no node available, skipping gas estimation ({})", err));
+            return Ok(builder.submission(DryRunSubmission { node: None }).build());
+        }
+    };
+    let signer_config = match &profile.signer {
+        Some(signer_config) => Ok(signer_config.clone()),
+        None => SignerConfig::from_env(),
+    };
+    let from = match signer_config {
+        Ok(signer_config) => signer_config.load(profile.chain_id).await.ok().map(|signer| signer.address()),
+        Err(_) => None,
+    };
+    if from.is_none() {
+        output.note("no signer available, estimating gas without a sender");
+    }
+
+    let node = DryRunNode { provider: Arc::new(provider), profile, from };
+    Ok(builder.submission(DryRunSubmission { node: Some(node) }).build())
+}
+
+/// Makes a directory ingestion require signed envelopes when `--require-envelopes` is set.
+///
+/// # Parameters
+/// - `global`: The global flags.
+/// - `ingestion`: The directory ingestion.
+/// - `persist`: Whether accepted nonces are remembered in `--nonce-log`, see `replay_guard`.
+fn directory_ingestion(global: &GlobalArgs, ingestion: DirectoryIngestion, persist: bool) -> Result<DirectoryIngestion, CliError> {
+    if !global.require_envelopes {
+        return Ok(ingestion);
+    }
+    Ok(ingestion.requiring_envelopes(replay_guard(global, persist)?))
+}
+
+/// A replay guard accepting envelopes within `--envelope-max-age` and `--envelope-max-skew`.
+///
+/// # Parameters
+/// - `global`: The global flags.
+/// - `persist`: Whether accepted nonces are remembered in `--nonce-log` across restarts. Commands
+///   that submit nothing leave the log alone, so they do not use up the nonces of a later run.
+fn replay_guard(global: &GlobalArgs, persist: bool) -> Result<ReplayGuard, CliError> {
+    let guard = ReplayGuard::new(
+        Duration::from_secs(global.envelope_max_age),
+        Duration::from_secs(global.envelope_max_skew),
+    );
+    if !persist {
+        return Ok(guard);
+    }
+    guard.persisted(&global.nonce_log).map_err(|err| {
+        CliError::config(format!("cannot open the nonce log {}: {}", global.nonce_log.display(), err))
+    })
+}
+
+/// Opens the input store given with `--input-store`, if any.
+fn input_store(global: &GlobalArgs) -> Result<Option<Arc<InputStore>>, CliError> {
+    match &global.input_store {
+        Some(dir) => {
+            let store = InputStore::open(dir)
+                .map_err(|err| CliError::input(format!("cannot open input store {}: {}", dir.display(), err)))?;
+            Ok(Some(Arc::new(store)))
+        }
+        None => Ok(None),
+    }
+}
+
+/// Loads the model registry given with `--model-registry`, if any.
+fn model_registry(global: &GlobalArgs) -> Result<Option<Arc<ModelRegistry>>, CliError> {
+    match &global.model_registry {
+        Some(path) => Ok(Some(Arc::new(ModelRegistry::load(path).map_err(CliError::config)?))),
+        None => Ok(None),
+    }
+}
+
+/// The observation checks of a round: the required fields, the models that extracted the
+/// observations when a registry is given, when the observations were made, then the raw input
+/// they were extracted from, verified against the input store when one is given.
+///
+/// `fields` checks the required fields; rounds that submit require the AI fields, `compare` only
+/// the fingerprint fields.
+fn validation(global: &GlobalArgs, fields: impl Stage + 'static) -> Result<StageChain, CliError> {
+    let window = TimeWindow {
+        max_skew: global.max_clock_skew,
+        max_age: global.max_observation_age,
+        spread: global.round_window,
+    };
+    let input_data = match input_store(global)? {
+        Some(store) => InputDataValidation::verified_against(store),
+        None => InputDataValidation::new(),
+    };
+    let mut validation = StageChain::new().then(fields);
+    if let Some(registry) = model_registry(global)? {
+        validation = validation.then(ModelValidation { registry });
+    }
+    Ok(validation.then(TimestampValidation { window }).then(input_data))
+}
+
+/// Classifies a failed stage by the exit code it should produce.
+fn stage_error(err: PipelineError) -> CliError {
+    match err.stage {
+        StageKind::Submission => CliError::chain(err),
+        _ => CliError::input(err),
+    }
+}
+
+/// Reports a finished round.
+///
+/// # Parameters
+/// - `round`: The round, as the pipeline left it.
+/// - `output`: Where to report the outcome.
+///
+/// # Returns
+/// - `i32`: The exit code the round's outcome maps to.
+fn report_round(round: &RoundContext, output: Output) -> i32 {
+    let mut details = json!({
+        "round_id": round.round_id,
+        "observations": round.observations.len(),
+        "rejected": round.rejected,
+        "consensus": round.consensus,
+    });
+
+    if let Some(halt) = &round.halted {
+        details["halted"] = json!(halt);
+        output.result(&capitalize(&halt.reason), false, details);
+        return exit_code::NEGATIVE;
+    }
+
+    if let Some(plan) = &round.plan {
+        details["dry_run"] = json!(true);
+        for (key, value) in json!(plan).as_object().into_iter().flatten() {
+            details[key] = value.clone();
+        }
+        output.result("Dry run: fingerprint not submitted", true, details);
+        return exit_code::SUCCESS;
+    }
+
+    if let Some(submission) = &round.submission {
+        let summary = match submission.tx_hash {
+            Some(_) => "Fingerprint submitted",
+            None => "Fingerprint already appended",
+        };
+        details["fingerprint"] = json!(round.fingerprint);
+        details["hash"] = json!(submission.hash);
+        details["tx_hash"] = json!(submission.tx_hash);
+        details["appended"] = json!(submission.appended);
+        output.result(summary, submission.appended, details);
+        return if submission.appended { exit_code::SUCCESS } else { exit_code::NEGATIVE };
+    }
+
+    output.result("Consensus reached", true, details);
+    exit_code::SUCCESS
+}
+
+fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+async fn compare(global: &GlobalArgs, dir: &Path, output: Output) -> Result<i32, CliError> {
+    let pipeline = Pipeline::builder()
+        .ingestion(directory_ingestion(global, DirectoryIngestion::new(dir), false)?)
+        .validation(validation(global, FingerprintFieldValidation)?)
+        .consensus(MinHashConsensus::from_env())
+        .build();
+    let round = pipeline.run(RoundContext::start()).await.map_err(stage_error)?;
+
+    Ok(report_round(&round, output))
+}
+
+fn fingerprint_file(file: &Path, output: Output) -> Result<i32, CliError> {
+    let observation = fs::read_to_string(file).map_err(CliError::input)?;
+    let (fingerprint, hash) = fingerprint_observation(&observation).map_err(CliError::input)?;
+
+    output.result("Fingerprint computed", true, json!({ "fingerprint": fingerprint, "hash": hash }));
+    Ok(exit_code::SUCCESS)
+}
+
+async fn replay(global: &GlobalArgs, round_id: &str, output: Output) -> Result<i32, CliError> {
+    let record = RoundLog::find(&global.round_log, round_id)
+        .map_err(CliError::input)?
+        .ok_or_else(|| CliError::input(format!("round {} is not in {}", round_id, global.round_log.display())))?;
+    let report = replay_round(&record, input_store(global)?, model_registry(global)?).await.map_err(CliError::input)?;
+
+    let matches = report.matches();
+    output.result(
+        if matches { "Replay matches the logged round" } else { "Replay differs from the logged round" },
+        matches,
+        json!({
+            "round_id": report.round_id,
+            "tampered_inputs": report.tampered_inputs,
+            "differences": report.differences,
+            "hash": report.replayed.hash,
+            "logged_hash": record.hash,
+            "tx_hash": record.tx_hash,
+        }),
+    );
+    Ok(if matches { exit_code::SUCCESS } else { exit_code::NEGATIVE })
+}
+
+async fn verify(global: &GlobalArgs, hash: &str, observation: Option<&Path>, output: Output) -> Result<i32, CliError> {
+    let (provider, profile) = connect_reader(global).await?;
+    let source = ProviderLogSource::new(provider.clone(), profile.contracts.fingerprint_proxy, &profile.indexer.event_signature);
+    let index = IndexStore::load(&profile.indexer.store_path).map_err(CliError::input)?;
+
+    let (verified, details) = match observation {
+        Some(path) => {
+            let observation = fs::read_to_string(path).map_err(CliError::input)?;
+            let report = verify_observation(&observation, Some(hash), Arc::new(provider), &profile, &source, &index)
+                .await
+                .map_err(CliError::chain)?;
+            (report.is_verified(), serde_json::to_value(&report).map_err(CliError::input)?)
+        }
+        None => {
+            let (appended, appends) = locate_hash(hash, Arc::new(provider), &profile, &source, &index)
+                .await
+                .map_err(CliError::chain)?;
+            (appended, json!({ "hash": hash, "appended": appended, "appends": appends }))
+        }
+    };
+
+    output.result(if verified { "Fingerprint verified" } else { "Fingerprint not verified" }, verified, details);
+    Ok(if verified { exit_code::SUCCESS } else { exit_code::NEGATIVE })
+}
+
+async fn watch(
+    global: &GlobalArgs,
+    dir: &Path,
+    interval: Duration,
+    metrics_addr: Option<SocketAddr>,
+    output: Output,
+) -> Result<i32, CliError> {
+    if !dir.is_dir() {
+        return Err(CliError::input(format!("{} is not a directory", dir.display())));
+    }
+    let mut observers: Vec<Arc<dyn RoundObserver>> = Vec::new();
+    if let Some(addr) = metrics_addr {
+        observers.push(spawn_metrics_server(addr).await?);
+    }
+    // Every observation that arrived since the last poll forms the next round
+    let ingestion = directory_ingestion(global, DirectoryIngestion::watching(dir), !global.dry_run)?;
+    let pipeline = round_pipeline(global, ingestion, observers, output).await?;
+
+    loop {
+        match pipeline.run(RoundContext::start()).await {
+            // Polls without new observations are not rounds worth reporting
+            Ok(round) if round.halted.as_ref().map(|halt| halt.stage) == Some(StageKind::Ingestion) => {}
+            Ok(round) => {
+                report_round(&round, output);
+            }
+            // A failed round is reported but does not stop the watcher
+            Err(err) => output.error(&stage_error(err)),
+        }
+
+        tokio::time::sleep(interval).await;
+    }
+}
+
+async fn serve(
+    global: &GlobalArgs,
+    addr: SocketAddr,
+    schedule: RoundSchedule,
+    input_quota: u64,
+    output: Output,
+) -> Result<i32, CliError> {
+    let board = match global.round_window {
+        Some(window) => RoundBoard::new().with_window(Duration::from_secs(window)),
+        None => RoundBoard::new(),
+    };
+    let board = Arc::new(board);
+    let metrics = Arc::new(CoordinatorMetrics::new().map_err(CliError::config)?);
+    let clocks = Arc::new(ClockSkewStats::new());
+    let observers: Vec<Arc<dyn RoundObserver>> = vec![metrics.clone(), clocks.clone()];
+    let pipeline = round_pipeline(global, SubmittedIngestion { board: board.clone() }, observers, output).await?;
+
+    let listener = tokio::net::TcpListener::bind(addr)
+        .await
+        .map_err(|err| CliError::config(format!("cannot listen on {}: {}", addr, err)))?;
+    tracing::info!(addr = %addr, "serving the ingestion API");
+    let guard = Arc::new(Mutex::new(replay_guard(global, !global.dry_run)?));
+    let uploads = input_store(global)?.map(|store| InputUploads { store, quota: input_quota });
+    let router = ingestion_router(board.clone(), guard, uploads, Some(metrics)).merge(clock_skew_router(clocks));
+    tokio::spawn(async move {
+        if let Err(err) = axum::serve(listener, router).await {
+            tracing::error!(error = %err, "ingestion API stopped");
+        }
+    });
+
+    loop {
+        // Rounds run one at a time, in the order they closed
+        if let Some(round_id) = board.close_due(schedule) {
+            let result = pipeline.run(RoundContext::new(round_id.clone())).await;
+            board.finish(&round_id, &result);
+            match result {
+                Ok(round) => {
+                    report_round(&round, output);
+                }
+                Err(err) => output.error(&stage_error(err)),
+            }
+        }
+
+        tokio::time::sleep(Duration::from_millis(250)).await;
+    }
+}
+
+/// Starts serving fresh metrics on `addr` in the background.
+async fn spawn_metrics_server(addr: SocketAddr) -> Result<Arc<CoordinatorMetrics>, CliError> {
+    let metrics = Arc::new(CoordinatorMetrics::new().map_err(CliError::config)?);
+    let listener = tokio::net::TcpListener::bind(addr)
+        .await
+        .map_err(|err| CliError::config(format!("cannot serve metrics on {}: {}", addr, err)))?;
+
+    let served = metrics.clone();
+    tokio::spawn(async move {
+        if let Err(err) = serve_metrics(listener, served).await {
+            tracing::error!(error = %err, "metrics server stopped");
+        }
+    });
+    Ok(metrics)
+}
+
+async fn index(global: &GlobalArgs, command: IndexCommand, output: Output) -> Result<i32, CliError> {
+    let profile = load_profile(global)?;
+    let store_path = profile.indexer.store_path.clone();
+    let store = IndexStore::load(&store_path).map_err(CliError::input)?;
+
+    match command {
+        IndexCommand::Follow => {
+            let (provider, profile) = connect_reader(global).await?;
+            let source = ProviderLogSource::new(provider, profile.contracts.fingerprint_proxy, &profile.indexer.event_signature);
+            let mut indexer = FingerprintIndexer::new(source, store, profile.indexer.clone());
+            indexer
+                .follow(&store_path, Duration::from_millis(profile.confirmations.poll_interval_ms))
+                .await
+                .map_err(CliError::chain)?;
+            Ok(exit_code::SUCCESS)
+        }
+        IndexCommand::Since { block } => {
+            let appends = store.since_block(block);
+            output.result(&format!("{} fingerprints appended since block {}", appends.len(), block), true, json!({ "appends": appends }));
+            Ok(exit_code::SUCCESS)
+        }
+        IndexCommand::Who { hash } => {
+            let hash_bytes: H256 = hash.parse().map_err(CliError::input)?;
+            let appends = store.appends_of(&hash_bytes);
+            let found = !appends.is_empty();
+            output.result(
+                if found { "Fingerprint found in the index" } else { "Fingerprint not found in the index" },
+                found,
+                json!({ "hash": hash, "senders": store.who_appended(&hash_bytes), "appends": appends }),
+            );
+            Ok(if found { exit_code::SUCCESS } else { exit_code::NEGATIVE })
+        }
+        IndexCommand::Audit => {
+            // Confirm that every indexed hash is still reported as appended by the contract
+            let (provider, profile) = connect_reader(global).await?;
+            let hashes: Vec<String> = store.records.iter().map(|record| format!("{:?}", record.hash)).collect();
+            let appended = check_fingerprints(Arc::new(provider), profile.contracts.fingerprint_proxy, profile.contracts.multicall, &hashes)
+                .await
+                .map_err(CliError::chain)?;
+
+            let missing: Vec<&String> = hashes.iter().filter(|hash| !appended[*hash]).collect();
+            output.result(
+                &format!("Audited {} indexed fingerprints, {} not appended on chain", hashes.len(), missing.len()),
+                missing.is_empty(),
+                json!({ "audited": hashes.len(), "missing": missing }),
+            );
+            Ok(if missing.is_empty() { exit_code::SUCCESS } else { exit_code::NEGATIVE })
+        }
+    }
+}
@@ -0,0 +1,18 @@
+use std::io::{self, IsTerminal};
+use tracing_subscriber::EnvFilter;
+
+use crate::cli::args::LogFormat;
+
+/// Sends the logs of the coordinator and its libraries to stderr, keeping stdout for results.
+///
+/// # Parameters
+/// - `format`: Whether to write text or JSON lines.
+pub fn init(format: LogFormat) {
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+    let builder = tracing_subscriber::fmt().with_env_filter(filter).with_writer(io::stderr);
+
+    match format {
+        LogFormat::Text => builder.with_ansi(io::stderr().is_terminal()).init(),
+        LogFormat::Json => builder.json().with_current_span(true).with_span_list(true).init(),
+    }
+}
@@ -0,0 +1,109 @@
+use colored::*;
+use serde_json::Value;
+use std::fmt;
+
+use crate::cli::args::OutputFormat;
+
+/// The process exit codes of the CLI.
+pub mod exit_code {
+    /// The command succeeded.
+    pub const SUCCESS: i32 = 0;
+    /// The command ran, but the answer is negative: no consensus, or a hash that is not on chain.
+    pub const NEGATIVE: i32 = 1;
+    /// The command line could not be parsed. Reported by clap.
+    pub const USAGE: i32 = 2;
+    /// The network configuration is missing or invalid, or the node does not match it.
+    pub const CONFIG: i32 = 3;
+    /// The inputs could not be read or parsed.
+    pub const INPUT: i32 = 4;
+    /// The node or contract could not be reached, or a transaction failed.
+    pub const CHAIN: i32 = 5;
+}
+
+/// An error that ends a command, classified by its exit code.
+#[derive(Debug)]
+pub enum CliError {
+    Config(String),
+    Input(String),
+    Chain(String),
+}
+
+impl CliError {
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            CliError::Config(_) => exit_code::CONFIG,
+            CliError::Input(_) => exit_code::INPUT,
+            CliError::Chain(_) => exit_code::CHAIN,
+        }
+    }
+
+    pub fn config<E: fmt::Display>(err: E) -> Self {
+        CliError::Config(err.to_string())
+    }
+
+    pub fn input<E: fmt::Display>(err: E) -> Self {
+        CliError::Input(err.to_string())
+    }
+
+    pub fn chain<E: fmt::Display>(err: E) -> Self {
+        CliError::Chain(err.to_string())
+    }
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CliError::Config(msg) => write!(f, "configuration error: {}", msg),
+            CliError::Input(msg) => write!(f, "input error: {}", msg),
+            CliError::Chain(msg) => write!(f, "chain error: {}", msg),
+        }
+    }
+}
+
+/// Prints command results either as human-readable lines or as one JSON document per result.
+#[derive(Debug, Clone, Copy)]
+pub struct Output {
+    pub format: OutputFormat,
+}
+
+impl Output {
+    /// Prints a result.
+    ///
+    /// # Parameters
+    /// - `summary`: The line shown in human format.
+    /// - `success`: Whether the result is positive, used to color the human summary.
+    /// - `details`: The JSON document printed in JSON format. Its fields are also listed in human format.
+    pub fn result(&self, summary: &str, success: bool, details: Value) {
+        match self.format {
+            OutputFormat::Json => println!("{}", details),
+            OutputFormat::Human => {
+                let summary = if success { summary.green().bold() } else { summary.red().bold() };
+                println!("{}", summary);
+                if let Value::Object(fields) = details {
+                    for (key, value) in fields {
+                        match value {
+                            Value::String(text) => println!("  {}: {}", key, text),
+                            other => println!("  {}: {}", key, other),
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Prints a side note to stderr, keeping stdout free for results.
+    pub fn note(&self, note: &str) {
+        match self.format {
+            OutputFormat::Json => eprintln!("{}", serde_json::json!({ "note": note })),
+            OutputFormat::Human => eprintln!("{} {}", "note:".yellow().bold(), note),
+        }
+    }
+
+    /// Prints an error to stderr.
+    pub fn error(&self, err: &CliError) {
+        match self.format {
+            OutputFormat::Json => eprintln!("{}", serde_json::json!({ "error": err.to_string() })),
+            OutputFormat::Human => eprintln!("{}", err.to_string().red().bold()),
+        }
+    }
+}
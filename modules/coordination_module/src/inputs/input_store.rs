@@ -0,0 +1,147 @@
+use json::input_data::input_reference::{InputHashAlgorithm, InputReference};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// A directory of the raw game inputs observations were extracted from.
+///
+/// Inputs are stored as files named after their keccak256 hash, but any file dropped in the
+/// directory can be found by its hash under every supported algorithm. Files are hashed the
+/// first time a lookup misses, and only once. Every method reads or writes the disk, so async
+/// callers run them in a blocking task; the index is only locked to look up or record hashes.
+pub struct InputStore {
+    dir: PathBuf,
+    index: Mutex<InputIndex>,
+}
+
+#[derive(Default)]
+struct InputIndex {
+    /// The files already hashed.
+    files: HashSet<PathBuf>,
+    by_reference: HashMap<InputReference, PathBuf>,
+}
+
+impl InputIndex {
+    fn add(&mut self, path: PathBuf, bytes: &[u8]) {
+        for algorithm in InputHashAlgorithm::ALL {
+            self.by_reference.insert(InputReference::of(bytes, algorithm), path.clone());
+        }
+        self.files.insert(path);
+    }
+}
+
+impl InputStore {
+    /// Opens the store in `dir`, creating the directory if needed.
+    pub fn open(dir: impl Into<PathBuf>) -> io::Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        Ok(InputStore { dir, index: Mutex::new(InputIndex::default()) })
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Stores a raw input.
+    ///
+    /// # Parameters
+    /// - `bytes`: The raw input.
+    ///
+    /// # Returns
+    /// - `io::Result<(InputReference, bool)>`: Its keccak256 reference, and whether the store did not hold it yet.
+    pub fn put(&self, bytes: &[u8]) -> io::Result<(InputReference, bool)> {
+        let reference = InputReference::of(bytes, InputHashAlgorithm::Keccak256);
+        let path = self.path_of(&reference);
+        let new = !path.exists();
+        if new {
+            // Written aside first, so a lookup never reads a partial input
+            let partial = path.with_extension("partial");
+            fs::write(&partial, bytes)?;
+            fs::rename(&partial, &path)?;
+        }
+
+        self.lock().add(path, bytes);
+        Ok((reference, new))
+    }
+
+    /// Whether the store already holds the input a keccak256 reference identifies, without reading it.
+    pub fn contains(&self, reference: &InputReference) -> bool {
+        self.lock().by_reference.contains_key(reference)
+            || (reference.algorithm == InputHashAlgorithm::Keccak256 && self.path_of(reference).exists())
+    }
+
+    /// The path an input with this keccak256 reference is stored at.
+    fn path_of(&self, reference: &InputReference) -> PathBuf {
+        self.dir.join(format!("{:x}", reference.digest))
+    }
+
+    /// Reads the raw input a reference identifies.
+    ///
+    /// The bytes are returned as they are now on disk; callers should check them against the
+    /// reference, as the file may have changed since it was hashed.
+    ///
+    /// # Parameters
+    /// - `reference`: The reference, as declared by an observation.
+    ///
+    /// # Returns
+    /// - `io::Result<Option<Vec<u8>>>`: The raw input, if the store holds it.
+    pub fn get(&self, reference: &InputReference) -> io::Result<Option<Vec<u8>>> {
+        if !self.lock().by_reference.contains_key(reference) {
+            self.index_new_files()?;
+        }
+
+        let path = self.lock().by_reference.get(reference).cloned();
+        match path {
+            Some(path) => match fs::read(path) {
+                Ok(bytes) => Ok(Some(bytes)),
+                Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+                Err(err) => Err(err),
+            },
+            None => Ok(None),
+        }
+    }
+
+    /// Hashes the files of the directory that were not hashed yet.
+    fn index_new_files(&self) -> io::Result<()> {
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            let partial = path.extension().and_then(|ext| ext.to_str()) == Some("partial");
+            if !path.is_file() || partial || self.lock().files.contains(&path) {
+                continue;
+            }
+            // Read unlocked, so lookups of known inputs do not wait for the disk
+            let bytes = fs::read(&path)?;
+            self.lock().add(path, &bytes);
+        }
+        Ok(())
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, InputIndex> {
+        // The index is only a cache of the directory, so a poisoned one is still usable
+        self.index.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_store_finds_inputs_under_every_algorithm() {
+        let dir = tempdir().unwrap();
+        let store = InputStore::open(dir.path().join("inputs")).unwrap();
+
+        let (stored, new) = store.put(b"replay chunk").unwrap();
+        assert!(new);
+        assert!(!store.put(b"replay chunk").unwrap().1);
+        fs::write(store.dir().join("frame.png"), b"dropped frame").unwrap();
+
+        assert_eq!(store.get(&stored).unwrap().unwrap(), b"replay chunk");
+        let dropped = InputReference::of(b"dropped frame", InputHashAlgorithm::Sha256);
+        assert_eq!(store.get(&dropped).unwrap().unwrap(), b"dropped frame");
+        assert!(store.get(&InputReference::of(b"never seen", InputHashAlgorithm::Keccak256)).unwrap().is_none());
+    }
+}
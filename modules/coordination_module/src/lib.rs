@@ -0,0 +1,5 @@
+pub mod api;
+pub mod audit;
+pub mod inputs;
+pub mod metrics;
+pub mod pipeline;
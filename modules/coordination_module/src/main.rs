@@ -1,82 +1,67 @@
+mod cli;
 
-use json::load_json_objects;
-use json_comparator::run_json_comparator;
-use fingerprint::{run_fingerprint, Fingerprint};
-use serde_json::Value;
+use clap::Parser;
+use std::process;
 
-#[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
+use cli::args::Cli;
+use cli::output::exit_code;
 
-    // Load JSON objects from the specified directory
-    let json_objects = load_json_objects("modules/coordination_module/json/src/json_objects").await;
+#[tokio::main]
+async fn main() {
+    dotenv::dotenv().ok();
 
-    // Run the JSON comparator and get the JSON object with the highest similarity
-    let best_json_str = match run_json_comparator(&json_objects) {
-        Some(json) => json,
-        None => {
-            println!("No JSON objects met the similarity threshold.");
-            return Ok(());
+    let cli = match Cli::try_parse() {
+        Ok(cli) => cli,
+        Err(err) => {
+            // `--help` and `--version` are reported as errors by clap, but are not failures
+            let _ = err.print();
+            process::exit(if err.use_stderr() { exit_code::USAGE } else { exit_code::SUCCESS });
         }
     };
 
-    // Parse the resulting JSON object to create a Fingerprint
-    let best_json: Value = serde_json::from_str(&best_json_str)?;
-    let fingerprint = Fingerprint {
-        gamer: best_json["character"].as_str().unwrap_or_default().to_string(),
-        strikes: 0,
-        place: best_json["place"].as_str().unwrap_or_default().to_string(),
-        weapon: best_json["ability"].as_str().unwrap_or_default().to_string(),
-        place2: best_json["place2"].as_str().unwrap_or_default().to_string(),
-    };
-
-    // Run the fingerprint process with the resulting Fingerprint object
-    run_fingerprint(fingerprint).await?;
-
-    Ok(())
+    cli::logging::init(cli.global.log_format);
+    process::exit(cli::commands::run(cli).await);
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use serde_json::json;
-    use tokio;
+    use cli::args::{Command, IndexCommand, LogFormat, OutputFormat};
 
-    async fn mock_load_json_objects(_path: &str) -> Vec<String> {
-        vec![json!({
-            "character": "test_character",
-            "place": "test_place",
-            "ability": "test_ability",
-            "place2": "test_place2"
-        }).to_string()]
-    }
+    #[test]
+    fn test_cli_parses_global_flags_after_subcommand() {
+        let cli = Cli::try_parse_from([
+            "coordination_module",
+            "run",
+            "observations",
+            "--network",
+            "local",
+            "--format",
+            "json",
+            "--dry-run",
+            "--log-format",
+            "json",
+        ])
+        .unwrap();
 
-    fn mock_run_json_comparator(_json_objects: &[String]) -> Option<String> {
-        Some(json!({
-            "character": "test_character",
-            "place": "test_place",
-            "ability": "test_ability",
-            "place2": "test_place2"
-        }).to_string())
+        assert!(matches!(cli.command, Command::Run { ref dir } if dir.to_str() == Some("observations")));
+        assert_eq!(cli.global.network, "local");
+        assert_eq!(cli.global.format, OutputFormat::Json);
+        assert!(cli.global.dry_run);
+        assert_eq!(cli.global.log_format, LogFormat::Json);
     }
 
-    async fn mock_run_fingerprint(_fingerprint: Fingerprint) -> Result<(), Box<dyn std::error::Error>> {
-        Ok(())
+    #[test]
+    fn test_cli_parses_index_subcommands() {
+        let cli = Cli::try_parse_from(["coordination_module", "index", "since", "42"]).unwrap();
+
+        assert!(matches!(cli.command, Command::Index(IndexCommand::Since { block: 42 })));
     }
 
-    #[tokio::test]
-    async fn test_main() {
-        let json_objects = mock_load_json_objects("mock_path").await;
-        let best_json_str = mock_run_json_comparator(&json_objects).unwrap();
-        let best_json: Value = serde_json::from_str(&best_json_str).unwrap();
-        let fingerprint = Fingerprint {
-            gamer: best_json["character"].as_str().unwrap_or_default().to_string(),
-            strikes: 0,
-            place: best_json["place"].as_str().unwrap_or_default().to_string(),
-            weapon: best_json["ability"].as_str().unwrap_or_default().to_string(),
-            place2: best_json["place2"].as_str().unwrap_or_default().to_string(),
-        };
+    #[test]
+    fn test_cli_rejects_unknown_format() {
+        let err = Cli::try_parse_from(["coordination_module", "compare", "dir", "--format", "xml"]).unwrap_err();
 
-        let result = mock_run_fingerprint(fingerprint).await;
-        assert!(result.is_ok());
+        assert_eq!(err.exit_code(), cli::output::exit_code::USAGE);
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,142 @@
+use axum::extract::State;
+use axum::routing::get;
+use axum::{Json, Router};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashSet};
+use std::sync::{Arc, Mutex};
+
+use crate::pipeline::observer::RoundObserver;
+use crate::pipeline::round_context::RoundContext;
+
+/// The most nodes tracked one by one. Anyone can sign with a fresh key, so the skew of any
+/// further node is tracked together under `OTHER_NODES`.
+pub const MAX_TRACKED_NODES: usize = 1000;
+
+/// The node the skew of nodes beyond `MAX_TRACKED_NODES` is tracked under.
+pub const OTHER_NODES: &str = "other";
+
+/// The nodes tracked one by one so far, to bound the nodes a metric or map is keyed by.
+#[derive(Default)]
+pub struct TrackedNodes {
+    nodes: Mutex<HashSet<String>>,
+}
+
+impl TrackedNodes {
+    /// The key to track a node under: the node itself while there is room, `OTHER_NODES` after.
+    pub fn key<'a>(&self, node: &'a str) -> &'a str {
+        let mut nodes = self.nodes.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        if nodes.contains(node) {
+            return node;
+        }
+        if nodes.len() >= MAX_TRACKED_NODES {
+            return OTHER_NODES;
+        }
+        nodes.insert(node.to_string());
+        node
+    }
+}
+
+/// How far a node's clock has been off from the coordinator's, in seconds.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct NodeClock {
+    pub node: String,
+    /// How many observations the statistics cover.
+    pub observations: u64,
+    /// Positive when the node's clock is ahead.
+    pub mean: f64,
+    pub min: f64,
+    pub max: f64,
+    /// The skew of the node's latest observation.
+    pub last: f64,
+}
+
+/// The clock skew of every AI node, over every round since the coordinator started.
+#[derive(Default)]
+pub struct ClockSkewStats {
+    tracked: TrackedNodes,
+    nodes: Mutex<BTreeMap<String, NodeClock>>,
+}
+
+impl ClockSkewStats {
+    pub fn new() -> Self {
+        ClockSkewStats::default()
+    }
+
+    /// Adds one observation's skew to its node's statistics.
+    pub fn record(&self, node: &str, seconds: f64) {
+        let node = self.tracked.key(node);
+        let mut nodes = self.nodes.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let clock = nodes.entry(node.to_string()).or_insert_with(|| NodeClock {
+            node: node.to_string(),
+            observations: 0,
+            mean: 0.0,
+            min: seconds,
+            max: seconds,
+            last: seconds,
+        });
+        clock.observations += 1;
+        clock.mean += (seconds - clock.mean) / clock.observations as f64;
+        clock.min = clock.min.min(seconds);
+        clock.max = clock.max.max(seconds);
+        clock.last = seconds;
+    }
+
+    /// The statistics of every node, ordered by node.
+    pub fn snapshot(&self) -> Vec<NodeClock> {
+        self.nodes.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).values().cloned().collect()
+    }
+}
+
+impl RoundObserver for ClockSkewStats {
+    fn round_finished(&self, round: &RoundContext) {
+        for skew in &round.clock_skew {
+            self.record(&skew.node, skew.seconds);
+        }
+    }
+}
+
+/// A router serving the clock skew of every node on `GET /nodes`.
+pub fn clock_skew_router(stats: Arc<ClockSkewStats>) -> Router {
+    Router::new().route("/nodes", get(node_clocks)).with_state(stats)
+}
+
+async fn node_clocks(State(stats): State<Arc<ClockSkewStats>>) -> Json<Vec<NodeClock>> {
+    Json(stats.snapshot())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pipeline::round_context::NodeSkew;
+
+    #[test]
+    fn test_stats_aggregate_each_node_across_rounds() {
+        let stats = ClockSkewStats::new();
+        for seconds in [[2.0, -1.0], [4.0, -3.0]] {
+            let mut round = RoundContext::new("round");
+            round.clock_skew = vec![
+                NodeSkew { node: "7".to_string(), seconds: seconds[0] },
+                NodeSkew { node: "3".to_string(), seconds: seconds[1] },
+            ];
+            stats.round_finished(&round);
+        }
+
+        let nodes = stats.snapshot();
+        assert_eq!(nodes.len(), 2);
+        assert_eq!(nodes[0], NodeClock { node: "3".to_string(), observations: 2, mean: -2.0, min: -3.0, max: -1.0, last: -3.0 });
+        assert_eq!(nodes[1].mean, 3.0);
+    }
+
+    #[test]
+    fn test_stats_track_a_bounded_number_of_nodes() {
+        let stats = ClockSkewStats::new();
+        for node in 0..MAX_TRACKED_NODES + 5 {
+            stats.record(&node.to_string(), 1.0);
+        }
+
+        let nodes = stats.snapshot();
+        assert_eq!(nodes.len(), MAX_TRACKED_NODES + 1);
+        let other = nodes.iter().find(|clock| clock.node == OTHER_NODES).unwrap();
+        assert_eq!(other.observations, 5);
+    }
+}
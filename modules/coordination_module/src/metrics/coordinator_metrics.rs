@@ -0,0 +1,185 @@
+use ethers::types::U256;
+use prometheus::{
+    Counter, Encoder, Histogram, HistogramOpts, HistogramVec, IntCounter, IntCounterVec, Opts, Registry, TextEncoder,
+};
+
+use crate::metrics::clock_skew::TrackedNodes;
+use crate::pipeline::observer::RoundObserver;
+use crate::pipeline::round_context::RoundContext;
+use crate::pipeline::stage::StageKind;
+
+/// The coordinator's Prometheus metrics, updated from every finished round.
+pub struct CoordinatorMetrics {
+    registry: Registry,
+    pub observations_ingested: IntCounter,
+    pub observations_rejected: IntCounter,
+    /// Rounds that reached the consensus stage, by `result`: `passed` or `failed`.
+    pub consensus_rounds: IntCounterVec,
+    /// Rounds that failed, by the `stage` that failed them.
+    pub round_errors: IntCounterVec,
+    pub similarity: Histogram,
+    pub submission_latency: Histogram,
+    pub gas_used: Counter,
+    pub gas_spent_wei: Counter,
+    /// Rounds whose submission failed, whether at the node, the signer, the nonce or the gas estimate.
+    pub submission_failures: IntCounter,
+    /// How far each node's clock was ahead of the coordinator's, by `node`: its verified uploader
+    /// address or, for unsigned observations, its `ainode`.
+    pub clock_skew: HistogramVec,
+    /// The nodes `clock_skew` has a label for, bounded so nodes cannot add labels without end.
+    skew_nodes: TrackedNodes,
+}
+
+impl CoordinatorMetrics {
+    /// Creates the metrics in a fresh registry.
+    ///
+    /// # Returns
+    /// - `Result<CoordinatorMetrics, prometheus::Error>`: The metrics, or an error if two share a name.
+    pub fn new() -> Result<Self, prometheus::Error> {
+        let registry = Registry::new();
+
+        let observations_ingested = IntCounter::new("coordinator_observations_ingested_total", "Observations collected by ingestion")?;
+        let observations_rejected = IntCounter::new("coordinator_observations_rejected_total", "Observations rejected by envelope checks or validation")?;
+        let consensus_rounds = IntCounterVec::new(
+            Opts::new("coordinator_consensus_rounds_total", "Rounds that reached the consensus stage, by result"),
+            &["result"],
+        )?;
+        let round_errors = IntCounterVec::new(Opts::new("coordinator_round_errors_total", "Rounds that failed, by stage"), &["stage"])?;
+        let similarity = Histogram::with_opts(
+            HistogramOpts::new("coordinator_similarity_score", "Pairwise MinHash similarity of observations")
+                .buckets(vec![0.1, 0.2, 0.3, 0.4, 0.5, 0.6, 0.7, 0.8, 0.9, 1.0]),
+        )?;
+        let submission_latency = Histogram::with_opts(
+            HistogramOpts::new("coordinator_submission_latency_seconds", "Time to submit and confirm a fingerprint")
+                .buckets(vec![0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0, 120.0, 300.0]),
+        )?;
+        let gas_used = Counter::new("coordinator_gas_used_total", "Gas used by fingerprint transactions")?;
+        let gas_spent_wei = Counter::new("coordinator_gas_spent_wei_total", "Wei spent on fingerprint transactions")?;
+        let submission_failures = IntCounter::new("coordinator_submission_failures_total", "Rounds whose fingerprint submission failed")?;
+        let clock_skew = HistogramVec::new(
+            HistogramOpts::new("coordinator_clock_skew_seconds", "Observation timestamp minus receipt time, by AI node")
+                .buckets(vec![-300.0, -60.0, -10.0, -1.0, 0.0, 1.0, 10.0, 60.0, 300.0]),
+            &["node"],
+        )?;
+
+        registry.register(Box::new(observations_ingested.clone()))?;
+        registry.register(Box::new(observations_rejected.clone()))?;
+        registry.register(Box::new(consensus_rounds.clone()))?;
+        registry.register(Box::new(round_errors.clone()))?;
+        registry.register(Box::new(similarity.clone()))?;
+        registry.register(Box::new(submission_latency.clone()))?;
+        registry.register(Box::new(gas_used.clone()))?;
+        registry.register(Box::new(gas_spent_wei.clone()))?;
+        registry.register(Box::new(submission_failures.clone()))?;
+        registry.register(Box::new(clock_skew.clone()))?;
+
+        Ok(CoordinatorMetrics {
+            registry,
+            observations_ingested,
+            observations_rejected,
+            consensus_rounds,
+            round_errors,
+            similarity,
+            submission_latency,
+            gas_used,
+            gas_spent_wei,
+            submission_failures,
+            clock_skew,
+            skew_nodes: TrackedNodes::default(),
+        })
+    }
+
+    /// Renders every metric in the Prometheus text format.
+    pub fn render(&self) -> String {
+        let mut buffer = Vec::new();
+        // Encoding into memory only fails on metrics Prometheus cannot represent, which are not registered here
+        TextEncoder::new().encode(&self.registry.gather(), &mut buffer).unwrap_or_default();
+        String::from_utf8(buffer).unwrap_or_default()
+    }
+}
+
+impl RoundObserver for CoordinatorMetrics {
+    fn round_finished(&self, round: &RoundContext) {
+        self.observations_ingested.inc_by(round.inputs.len() as u64);
+        self.observations_rejected.inc_by(round.rejected.len() as u64);
+        for skew in &round.clock_skew {
+            self.clock_skew.with_label_values(&[self.skew_nodes.key(&skew.node)]).observe(skew.seconds);
+        }
+
+        if let Some(comparison) = &round.comparison {
+            for pair in &comparison.similarities {
+                self.similarity.observe(pair.similarity);
+            }
+            let result = if comparison.best.is_some() { "passed" } else { "failed" };
+            self.consensus_rounds.with_label_values(&[result]).inc();
+        }
+
+        if let Some(failure) = &round.failure {
+            self.round_errors.with_label_values(&[&failure.stage.to_string()]).inc();
+            if failure.stage == StageKind::Submission {
+                self.submission_failures.inc();
+            }
+        }
+
+        if let Some(submission) = &round.submission {
+            if submission.tx_hash.is_some() {
+                if let Some(seconds) = round.timing(StageKind::Submission) {
+                    self.submission_latency.observe(seconds);
+                }
+            }
+            if let Some(gas_used) = submission.gas_used {
+                self.gas_used.inc_by(saturating_f64(gas_used));
+            }
+            if let Some(cost) = submission.cost {
+                self.gas_spent_wei.inc_by(saturating_f64(cost));
+            }
+        }
+    }
+}
+
+/// `value` as a float, saturating at `u128::MAX` instead of panicking as `U256::as_u128` does.
+fn saturating_f64(value: U256) -> f64 {
+    u128::try_from(value).unwrap_or(u128::MAX) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pipeline::builder::Pipeline;
+    use crate::pipeline::stages::consensus::MinHashConsensus;
+    use crate::pipeline::stages::fingerprinting::FingerprintHashing;
+    use crate::pipeline::stages::memory::{InMemoryIngestion, InMemorySubmission};
+    use crate::pipeline::stages::validation::ObservationValidation;
+    use json_comparator::ComparatorSettings;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_metrics_follow_finished_rounds() {
+        let metrics = Arc::new(CoordinatorMetrics::new().unwrap());
+        let pipeline = Pipeline::builder()
+            .ingestion(InMemoryIngestion::from_json(vec![
+                include_str!("../../json/src/json_objects/json1.json").to_string(),
+                include_str!("../../json/src/json_objects/json5.json").to_string(),
+                "not json".to_string(),
+            ]))
+            .validation(ObservationValidation)
+            .consensus(MinHashConsensus::new(ComparatorSettings::default()))
+            .fingerprinting(FingerprintHashing)
+            .submission(InMemorySubmission::default())
+            .observer(metrics.clone())
+            .build();
+
+        pipeline.run(RoundContext::new("round-1")).await.unwrap();
+
+        assert_eq!(metrics.observations_ingested.get(), 3);
+        assert_eq!(metrics.observations_rejected.get(), 1);
+        assert_eq!(metrics.consensus_rounds.with_label_values(&["passed"]).get(), 1);
+        assert_eq!(metrics.similarity.get_sample_count(), 1);
+        assert_eq!(metrics.submission_latency.get_sample_count(), 1);
+
+        let rendered = metrics.render();
+        assert!(rendered.contains("coordinator_observations_ingested_total 3"));
+        assert!(rendered.contains("coordinator_consensus_rounds_total{result=\"passed\"} 1"));
+        assert_eq!(saturating_f64(U256::MAX), u128::MAX as f64);
+    }
+}
@@ -0,0 +1,58 @@
+use axum::extract::State;
+use axum::http::header;
+use axum::response::IntoResponse;
+use axum::routing::get;
+use axum::Router;
+use std::sync::Arc;
+use tokio::net::TcpListener;
+
+use crate::metrics::coordinator_metrics::CoordinatorMetrics;
+
+/// The content type of the Prometheus text format.
+const PROMETHEUS_CONTENT_TYPE: &str = "text/plain; version=0.0.4";
+
+/// A router serving the metrics on `GET /metrics`.
+pub fn metrics_router(metrics: Arc<CoordinatorMetrics>) -> Router {
+    Router::new().route("/metrics", get(render_metrics)).with_state(metrics)
+}
+
+async fn render_metrics(State(metrics): State<Arc<CoordinatorMetrics>>) -> impl IntoResponse {
+    ([(header::CONTENT_TYPE, PROMETHEUS_CONTENT_TYPE)], metrics.render())
+}
+
+/// Serves the metrics until the process exits.
+///
+/// # Parameters
+/// - `listener`: The bound listener, so a taken address is reported before serving starts.
+/// - `metrics`: The metrics to serve.
+///
+/// # Returns
+/// - `Result<(), std::io::Error>`: An error if the server fails.
+pub async fn serve_metrics(listener: TcpListener, metrics: Arc<CoordinatorMetrics>) -> Result<(), std::io::Error> {
+    tracing::info!(addr = %listener.local_addr()?, "serving metrics");
+    axum::serve(listener, metrics_router(metrics)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    #[tokio::test]
+    async fn test_metrics_endpoint_serves_the_text_format() {
+        let metrics = Arc::new(CoordinatorMetrics::new().unwrap());
+        metrics.observations_ingested.inc_by(2);
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(serve_metrics(listener, metrics));
+
+        let mut stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+        stream.write_all(b"GET /metrics HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n").await.unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+
+        assert!(response.starts_with("HTTP/1.1 200"));
+        assert!(response.contains(PROMETHEUS_CONTENT_TYPE));
+        assert!(response.contains("coordinator_observations_ingested_total 2"));
+    }
+}
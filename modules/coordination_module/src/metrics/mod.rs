@@ -0,0 +1,3 @@
+pub mod clock_skew;
+pub mod coordinator_metrics;
+pub mod metrics_server;
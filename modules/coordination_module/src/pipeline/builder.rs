@@ -0,0 +1,354 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+use std::time::Instant;
+use tracing::field::Empty;
+use tracing::{Instrument, Span};
+
+use crate::pipeline::observer::RoundObserver;
+use crate::pipeline::round_context::{Halt, RoundContext, StageTiming};
+use crate::pipeline::stage::{Stage, StageError, StageKind, StageOutcome};
+
+/// A stage that failed, ending its round.
+#[derive(Debug)]
+pub struct PipelineError {
+    pub stage: StageKind,
+    /// The name of the failed stage.
+    pub name: String,
+    pub source: StageError,
+}
+
+impl fmt::Display for PipelineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} stage `{}` failed: {}", self.stage, self.name, self.source)
+    }
+}
+
+impl Error for PipelineError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(self.source.as_ref())
+    }
+}
+
+/// A coordination round as a sequence of stages.
+///
+/// Each slot holds at most one stage and empty slots are skipped, so the same pipeline type
+/// covers a full round, a dry run and a comparison only.
+pub struct Pipeline {
+    stages: BTreeMap<StageKind, Box<dyn Stage>>,
+    observers: Vec<Box<dyn RoundObserver>>,
+}
+
+impl Pipeline {
+    pub fn builder() -> PipelineBuilder {
+        PipelineBuilder { stages: BTreeMap::new(), observers: Vec::new() }
+    }
+
+    /// The slots that hold a stage, in the order they run.
+    pub fn stage_kinds(&self) -> Vec<StageKind> {
+        self.stages.keys().copied().collect()
+    }
+
+    /// Runs one round through every stage.
+    ///
+    /// The storage stage also runs when a later stage halted or failed the round, so every round
+    /// with inputs is kept, whatever its outcome.
+    ///
+    /// # Parameters
+    /// - `round`: The round to run. It may already hold observations.
+    ///
+    /// # Returns
+    /// - `Result<RoundContext, PipelineError>`: The finished round, with `halted` set if a stage
+    ///   ended it early, or the first stage error.
+    pub async fn run(&self, round: RoundContext) -> Result<RoundContext, PipelineError> {
+        let span = tracing::info_span!("round", round_id = %round.round_id, hash = Empty, tx = Empty);
+        self.run_stages(round).instrument(span).await
+    }
+
+    async fn run_stages(&self, mut round: RoundContext) -> Result<RoundContext, PipelineError> {
+        let mut error = None;
+
+        for (kind, stage) in &self.stages {
+            if *kind == StageKind::Storage {
+                break;
+            }
+
+            let stage_span = tracing::info_span!("stage", stage = %kind, implementation = stage.name());
+            let started = Instant::now();
+            let outcome = stage.run(&mut round).instrument(stage_span).await;
+            round.timings.push(StageTiming { stage: *kind, seconds: started.elapsed().as_secs_f64() });
+
+            match outcome {
+                Ok(StageOutcome::Continue) => {}
+                Ok(StageOutcome::Halt(reason)) => {
+                    round.halted = Some(Halt { stage: *kind, reason });
+                }
+                Err(source) => {
+                    let err = PipelineError { stage: *kind, name: stage.name().to_string(), source };
+                    round.failure = Some(Halt { stage: *kind, reason: err.to_string() });
+                    error = Some(err);
+                }
+            }
+            if *kind == StageKind::Ingestion {
+                round.inputs = round.observations.clone();
+            }
+            record_round_fields(*kind, &round);
+            if !round.is_complete() {
+                break;
+            }
+        }
+
+        match (&round.halted, &round.failure) {
+            (_, Some(failure)) => tracing::error!(stage = %failure.stage, reason = %failure.reason, "round failed"),
+            (Some(halt), None) => tracing::info!(stage = %halt.stage, reason = %halt.reason, "round halted"),
+            (None, None) => tracing::info!("round complete"),
+        }
+
+        if let Some(storage) = self.stages.get(&StageKind::Storage).filter(|_| !round.inputs.is_empty()) {
+            let stage_span = tracing::info_span!("stage", stage = %StageKind::Storage, implementation = storage.name());
+            let started = Instant::now();
+            let stored = storage.run(&mut round).instrument(stage_span).await;
+            round.timings.push(StageTiming { stage: StageKind::Storage, seconds: started.elapsed().as_secs_f64() });
+
+            if let Err(source) = stored {
+                let err = PipelineError { stage: StageKind::Storage, name: storage.name().to_string(), source };
+                tracing::error!(error = %err, "round not stored");
+                // The stage that failed the round is the error worth reporting
+                if error.is_none() {
+                    round.failure = Some(Halt { stage: StageKind::Storage, reason: err.to_string() });
+                    error = Some(err);
+                }
+            }
+        }
+
+        for observer in &self.observers {
+            observer.round_finished(&round);
+        }
+
+        match error {
+            Some(err) => Err(err),
+            None => Ok(round),
+        }
+    }
+}
+
+/// Adds the fingerprint hash and transaction to the round span once the stage producing them ran.
+fn record_round_fields(kind: StageKind, round: &RoundContext) {
+    let span = Span::current();
+    match kind {
+        StageKind::Fingerprinting => {
+            if let Some(hash) = &round.hash {
+                span.record("hash", hash.as_str());
+            }
+        }
+        StageKind::Submission => {
+            if let Some(tx_hash) = round.submission.as_ref().and_then(|submission| submission.tx_hash) {
+                span.record("tx", tracing::field::debug(tx_hash));
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Assembles a `Pipeline` one slot at a time.
+pub struct PipelineBuilder {
+    stages: BTreeMap<StageKind, Box<dyn Stage>>,
+    observers: Vec<Box<dyn RoundObserver>>,
+}
+
+impl PipelineBuilder {
+    /// Puts a stage in a slot, replacing the stage already there.
+    pub fn stage(mut self, kind: StageKind, stage: impl Stage + 'static) -> Self {
+        self.stages.insert(kind, Box::new(stage));
+        self
+    }
+
+    pub fn ingestion(self, stage: impl Stage + 'static) -> Self {
+        self.stage(StageKind::Ingestion, stage)
+    }
+
+    pub fn validation(self, stage: impl Stage + 'static) -> Self {
+        self.stage(StageKind::Validation, stage)
+    }
+
+    pub fn consensus(self, stage: impl Stage + 'static) -> Self {
+        self.stage(StageKind::Consensus, stage)
+    }
+
+    pub fn fingerprinting(self, stage: impl Stage + 'static) -> Self {
+        self.stage(StageKind::Fingerprinting, stage)
+    }
+
+    pub fn proof(self, stage: impl Stage + 'static) -> Self {
+        self.stage(StageKind::Proof, stage)
+    }
+
+    pub fn submission(self, stage: impl Stage + 'static) -> Self {
+        self.stage(StageKind::Submission, stage)
+    }
+
+    pub fn storage(self, stage: impl Stage + 'static) -> Self {
+        self.stage(StageKind::Storage, stage)
+    }
+
+    /// Adds an observer that is told about every finished round.
+    pub fn observer(mut self, observer: impl RoundObserver + 'static) -> Self {
+        self.observers.push(Box::new(observer));
+        self
+    }
+
+    pub fn build(self) -> Pipeline {
+        Pipeline { stages: self.stages, observers: self.observers }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pipeline::stages::consensus::MinHashConsensus;
+    use crate::pipeline::stages::fingerprinting::FingerprintHashing;
+    use crate::pipeline::stages::memory::{InMemoryIngestion, InMemoryProof, InMemoryStorage, InMemorySubmission};
+    use crate::pipeline::stages::validation::ObservationValidation;
+    use async_trait::async_trait;
+    use fingerprint::verify::verify_hash::fingerprint_observation;
+    use json_comparator::ComparatorSettings;
+    use serde_json::{json, Value};
+
+    /// Two identical observations, the first and the last, and one that agrees with neither.
+    fn sample_observations() -> Vec<String> {
+        vec![
+            include_str!("../../json/src/json_objects/json1.json").to_string(),
+            include_str!("../../json/src/json_objects/json2.json").to_string(),
+            include_str!("../../json/src/json_objects/json5.json").to_string(),
+        ]
+    }
+
+    fn full_pipeline(observations: Vec<String>, submission: InMemorySubmission, storage: InMemoryStorage) -> Pipeline {
+        Pipeline::builder()
+            .ingestion(InMemoryIngestion::from_json(observations))
+            .validation(ObservationValidation)
+            .consensus(MinHashConsensus::new(ComparatorSettings::default()))
+            .fingerprinting(FingerprintHashing)
+            .proof(InMemoryProof)
+            .submission(submission)
+            .storage(storage)
+            .build()
+    }
+
+    #[tokio::test]
+    async fn test_pipeline_runs_a_round_end_to_end() {
+        let submission = InMemorySubmission::default();
+        let storage = InMemoryStorage::default();
+        let pipeline = full_pipeline(sample_observations(), submission.clone(), storage.clone());
+
+        let round = pipeline.run(RoundContext::new("round-1")).await.unwrap();
+
+        assert!(round.is_complete());
+        // The consensus is one of the two identical observations
+        let consensus = round.consensus.clone().unwrap();
+        assert_eq!(consensus, serde_json::from_str::<Value>(&sample_observations()[2]).unwrap());
+        assert_eq!(consensus, serde_json::from_str::<Value>(&sample_observations()[0]).unwrap());
+
+        let (fingerprint, hash) = fingerprint_observation(&consensus.to_string()).unwrap();
+        assert_eq!(round.fingerprint, Some(fingerprint));
+        assert_eq!(round.hash.as_ref(), Some(&hash));
+        assert!(round.proof.is_some());
+
+        let submitted = round.submission.clone().unwrap();
+        assert_eq!(submitted.hash, hash);
+        assert!(submitted.tx_hash.is_some());
+        assert!(submission.appended.lock().unwrap().contains(&hash));
+
+        assert_eq!(round.stored_at.as_deref(), Some("memory:0"));
+        assert_eq!(storage.rounds.lock().unwrap()[0].round_id, "round-1");
+    }
+
+    #[tokio::test]
+    async fn test_pipeline_does_not_append_a_hash_twice() {
+        let pipeline = full_pipeline(sample_observations(), InMemorySubmission::default(), InMemoryStorage::default());
+
+        let first = pipeline.run(RoundContext::new("round-1")).await.unwrap();
+        let second = pipeline.run(RoundContext::new("round-2")).await.unwrap();
+
+        assert_eq!(first.hash, second.hash);
+        assert!(first.submission.unwrap().tx_hash.is_some());
+        assert!(second.submission.unwrap().tx_hash.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_pipeline_halts_without_consensus() {
+        let observations = vec![
+            include_str!("../../json/src/json_objects/json2.json").to_string(),
+            include_str!("../../json/src/json_objects/json3.json").to_string(),
+            include_str!("../../json/src/json_objects/json4.json").to_string(),
+        ];
+        let storage = InMemoryStorage::default();
+        let pipeline = full_pipeline(observations, InMemorySubmission::default(), storage.clone());
+
+        let round = pipeline.run(RoundContext::new("round-1")).await.unwrap();
+
+        assert_eq!(round.halted, Some(Halt { stage: StageKind::Consensus, reason: "no consensus".to_string() }));
+        assert!(round.fingerprint.is_none());
+        assert_eq!(round.comparison.unwrap().similarities.len(), 3);
+        assert_eq!(storage.rounds.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_pipeline_rejects_invalid_observations() {
+        let mut observations = sample_observations();
+        observations.push(json!({ "character": "a", "place": "b" }).to_string());
+        observations.push("not json".to_string());
+        let pipeline = full_pipeline(observations, InMemorySubmission::default(), InMemoryStorage::default());
+
+        let round = pipeline.run(RoundContext::new("round-1")).await.unwrap();
+
+        assert!(round.is_complete());
+        assert_eq!(round.inputs.len(), 5);
+        assert_eq!(round.observations.len(), 3);
+        let rejected: Vec<&str> = round.rejected.iter().map(|rejection| rejection.source.as_str()).collect();
+        assert_eq!(rejected, vec!["memory:3", "memory:4"]);
+    }
+
+    struct FailingStage;
+
+    #[async_trait]
+    impl Stage for FailingStage {
+        fn name(&self) -> &str {
+            "failing"
+        }
+
+        async fn run(&self, _round: &mut RoundContext) -> Result<StageOutcome, StageError> {
+            Err("node unreachable".into())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_pipeline_reports_the_failed_stage() {
+        let storage = InMemoryStorage::default();
+        let pipeline = Pipeline::builder()
+            .ingestion(InMemoryIngestion::from_json(sample_observations()))
+            .consensus(MinHashConsensus::new(ComparatorSettings::default()))
+            .fingerprinting(FingerprintHashing)
+            .submission(FailingStage)
+            .storage(storage.clone())
+            .build();
+
+        let err = pipeline.run(RoundContext::new("round-1")).await.unwrap_err();
+
+        assert_eq!(err.stage, StageKind::Submission);
+        assert_eq!(err.to_string(), "submission stage `failing` failed: node unreachable");
+        let stored = storage.rounds.lock().unwrap();
+        assert_eq!(stored[0].failure.as_ref().map(|failure| failure.stage), Some(StageKind::Submission));
+    }
+
+    #[test]
+    fn test_builder_orders_stages_by_slot() {
+        let pipeline = Pipeline::builder()
+            .storage(InMemoryStorage::default())
+            .fingerprinting(FingerprintHashing)
+            .ingestion(InMemoryIngestion::from_json(Vec::new()))
+            .build();
+
+        assert_eq!(pipeline.stage_kinds(), vec![StageKind::Ingestion, StageKind::Fingerprinting, StageKind::Storage]);
+    }
+}
@@ -0,0 +1,5 @@
+pub mod builder;
+pub mod observer;
+pub mod round_context;
+pub mod stage;
+pub mod stages;
@@ -0,0 +1,18 @@
+use std::sync::Arc;
+
+use crate::pipeline::round_context::RoundContext;
+
+/// Watches finished rounds, whatever their outcome, for example to update metrics.
+pub trait RoundObserver: Send + Sync {
+    /// Called once per round, after the storage stage.
+    ///
+    /// # Parameters
+    /// - `round`: The finished round, with `halted` or `failure` set if it did not complete.
+    fn round_finished(&self, round: &RoundContext);
+}
+
+impl<T: RoundObserver + ?Sized> RoundObserver for Arc<T> {
+    fn round_finished(&self, round: &RoundContext) {
+        self.as_ref().round_finished(round)
+    }
+}
@@ -0,0 +1,160 @@
+use ai_module::registry::model_registry::ModelFamily;
+use ethers::types::{Address, H256};
+use fingerprint::dry_run::submission_plan::SubmissionPlan;
+use fingerprint::{Fingerprint, FingerprintSubmission};
+use json::input_data::input_reference::InputReference;
+use json_comparator::{ComparatorSettings, ComparisonReport};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::pipeline::stage::StageKind;
+use crate::pipeline::stages::timestamp::TimeWindow;
+
+/// An observation collected by the ingestion stage.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Observation {
+    /// Where the observation came from, such as a file path.
+    pub source: String,
+    /// The observation JSON, as received.
+    pub json: String,
+    /// When the coordinator received the observation, in milliseconds since the Unix epoch.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub received_at: Option<u64>,
+    /// The uploader whose signature the observation carried, when it was signed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uploader: Option<Address>,
+}
+
+/// An observation the validation stage set aside.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Rejection {
+    pub source: String,
+    pub reason: String,
+}
+
+/// Why a round ended before its last stage, or how it failed.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Halt {
+    pub stage: StageKind,
+    pub reason: String,
+}
+
+/// How long a stage of a round took.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct StageTiming {
+    pub stage: StageKind,
+    pub seconds: f64,
+}
+
+/// How far a node's clock was ahead of the coordinator's when it made an observation.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct NodeSkew {
+    /// The node's verified uploader address, or its `ainode` if the observation was not signed.
+    pub node: String,
+    /// The observation's timestamp minus when it was received, negative when the node is behind.
+    pub seconds: f64,
+}
+
+/// Everything a coordination round has produced so far.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct RoundContext {
+    pub round_id: String,
+    /// When the round started, in milliseconds since the Unix epoch.
+    pub started_at: u64,
+    /// Every observation the ingestion stage collected.
+    pub inputs: Vec<Observation>,
+    /// The observations still in the round. Validation removes the rejected ones.
+    pub observations: Vec<Observation>,
+    pub rejected: Vec<Rejection>,
+    /// The time limits the observations were checked against.
+    pub time_window: Option<TimeWindow>,
+    /// The clock skew of the nodes behind every observation with a valid timestamp.
+    pub clock_skew: Vec<NodeSkew>,
+    /// The digest of the model registry the observations' models were checked against.
+    pub model_registry: Option<H256>,
+    /// The models whose observations the round compares.
+    pub model_family: Option<ModelFamily>,
+    /// The raw game input every remaining observation was extracted from.
+    pub input_data: Option<InputReference>,
+    /// The settings the consensus stage compared with, including the seed it used.
+    pub comparator: Option<ComparatorSettings>,
+    pub comparison: Option<ComparisonReport>,
+    /// The observation the nodes agree on.
+    pub consensus: Option<Value>,
+    pub fingerprint: Option<Fingerprint>,
+    pub hash: Option<String>,
+    /// The proof that the fingerprint hash was computed from the consensus.
+    pub proof: Option<Vec<u8>>,
+    /// What a dry run would have submitted.
+    pub plan: Option<SubmissionPlan>,
+    pub submission: Option<FingerprintSubmission>,
+    /// Where the storage stage kept the round, such as a file path.
+    pub stored_at: Option<String>,
+    /// Set when a stage ended the round early.
+    pub halted: Option<Halt>,
+    /// Set when a stage failed.
+    pub failure: Option<Halt>,
+    /// How long each stage that ran took, in order.
+    pub timings: Vec<StageTiming>,
+}
+
+impl RoundContext {
+    /// Starts an empty round.
+    ///
+    /// # Parameters
+    /// - `round_id`: The round's identifier.
+    pub fn new(round_id: impl Into<String>) -> Self {
+        RoundContext {
+            round_id: round_id.into(),
+            started_at: unix_millis(),
+            inputs: Vec::new(),
+            observations: Vec::new(),
+            rejected: Vec::new(),
+            time_window: None,
+            clock_skew: Vec::new(),
+            model_registry: None,
+            model_family: None,
+            input_data: None,
+            comparator: None,
+            comparison: None,
+            consensus: None,
+            fingerprint: None,
+            hash: None,
+            proof: None,
+            plan: None,
+            submission: None,
+            stored_at: None,
+            halted: None,
+            failure: None,
+            timings: Vec::new(),
+        }
+    }
+
+    /// Starts an empty round with a fresh identifier.
+    pub fn start() -> Self {
+        RoundContext::new(RoundContext::generate_id())
+    }
+
+    /// A round identifier derived from the current time, unique within the process.
+    pub fn generate_id() -> String {
+        static SEQUENCE: AtomicU64 = AtomicU64::new(0);
+        format!("round-{}-{}", unix_millis(), SEQUENCE.fetch_add(1, Ordering::Relaxed))
+    }
+
+    /// How long a stage took, if it ran.
+    pub fn timing(&self, stage: StageKind) -> Option<f64> {
+        self.timings.iter().find(|timing| timing.stage == stage).map(|timing| timing.seconds)
+    }
+
+    /// Whether the round went through every stage.
+    pub fn is_complete(&self) -> bool {
+        self.halted.is_none() && self.failure.is_none()
+    }
+}
+
+/// The current time, in milliseconds since the Unix epoch.
+pub fn unix_millis() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|elapsed| elapsed.as_millis() as u64).unwrap_or_default()
+}
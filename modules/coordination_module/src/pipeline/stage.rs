@@ -0,0 +1,69 @@
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fmt;
+
+use crate::pipeline::round_context::RoundContext;
+
+/// The error type of a stage. It must be `Send` so rounds can run on any task.
+pub type StageError = Box<dyn Error + Send + Sync>;
+
+/// The slots of a pipeline, in the order their stages run.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum StageKind {
+    /// Collects the round's observations.
+    Ingestion,
+    /// Rejects observations the AI nodes could not have produced.
+    Validation,
+    /// Picks the observation the nodes agree on.
+    Consensus,
+    /// Computes the fingerprint and its hash.
+    Fingerprinting,
+    /// Proves the fingerprint computation.
+    Proof,
+    /// Appends the fingerprint hash on chain, or plans the append.
+    Submission,
+    /// Stores the round's outcome.
+    Storage,
+}
+
+impl fmt::Display for StageKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            StageKind::Ingestion => "ingestion",
+            StageKind::Validation => "validation",
+            StageKind::Consensus => "consensus",
+            StageKind::Fingerprinting => "fingerprinting",
+            StageKind::Proof => "proof",
+            StageKind::Submission => "submission",
+            StageKind::Storage => "storage",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Whether the round goes on after a stage.
+#[derive(Debug, Clone, PartialEq)]
+pub enum StageOutcome {
+    /// Run the next stage.
+    Continue,
+    /// End the round without an error, for example when there is no consensus.
+    Halt(String),
+}
+
+/// One step of a coordination round.
+#[async_trait]
+pub trait Stage: Send + Sync {
+    /// A short name for reports, such as `minhash` or `chain`.
+    fn name(&self) -> &str;
+
+    /// Runs the stage on a round.
+    ///
+    /// # Parameters
+    /// - `round`: The round, holding what the previous stages produced.
+    ///
+    /// # Returns
+    /// - `Result<StageOutcome, StageError>`: Whether the round goes on.
+    async fn run(&self, round: &mut RoundContext) -> Result<StageOutcome, StageError>;
+}
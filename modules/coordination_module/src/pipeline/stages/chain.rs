@@ -0,0 +1,71 @@
+use async_trait::async_trait;
+use tracing::Instrument;
+
+use crate::pipeline::round_context::RoundContext;
+use crate::pipeline::stage::{Stage, StageError, StageOutcome};
+
+/// Runs several stages in one pipeline slot, in order.
+///
+/// The chain stops at the first stage that halts or fails the round. Its name joins the names
+/// of its stages with `+`, such as `observation_fields+input_data`.
+#[derive(Default)]
+pub struct StageChain {
+    name: String,
+    stages: Vec<Box<dyn Stage>>,
+}
+
+impl StageChain {
+    pub fn new() -> Self {
+        StageChain::default()
+    }
+
+    /// Adds a stage that runs after the stages already in the chain.
+    pub fn then(mut self, stage: impl Stage + 'static) -> Self {
+        if !self.name.is_empty() {
+            self.name.push('+');
+        }
+        self.name.push_str(stage.name());
+        self.stages.push(Box::new(stage));
+        self
+    }
+}
+
+#[async_trait]
+impl Stage for StageChain {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn run(&self, round: &mut RoundContext) -> Result<StageOutcome, StageError> {
+        for stage in &self.stages {
+            let step_span = tracing::info_span!("step", implementation = stage.name());
+            if let StageOutcome::Halt(reason) = stage.run(round).instrument(step_span).await? {
+                return Ok(StageOutcome::Halt(reason));
+            }
+        }
+        Ok(StageOutcome::Continue)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pipeline::stages::memory::InMemoryIngestion;
+    use crate::pipeline::stages::validation::ObservationValidation;
+
+    #[tokio::test]
+    async fn test_chain_runs_its_stages_in_order_until_one_halts() {
+        let chain = StageChain::new()
+            .then(InMemoryIngestion::from_json(vec!["not an observation".to_string()]))
+            .then(ObservationValidation)
+            .then(InMemoryIngestion::from_json(vec!["{}".to_string()]));
+        assert_eq!(chain.name(), "memory+observation_fields+memory");
+
+        let mut round = RoundContext::new("round-1");
+        let outcome = chain.run(&mut round).await.unwrap();
+
+        assert_eq!(outcome, StageOutcome::Halt("no valid observations".to_string()));
+        assert_eq!(round.rejected.len(), 1);
+        assert!(round.observations.is_empty());
+    }
+}
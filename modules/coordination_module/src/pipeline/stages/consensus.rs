@@ -0,0 +1,51 @@
+use async_trait::async_trait;
+use json_comparator::{run_comparison, ComparatorSettings};
+
+use crate::pipeline::round_context::RoundContext;
+use crate::pipeline::stage::{Stage, StageError, StageOutcome};
+
+/// Picks the observation most similar to another one, using MinHash.
+///
+/// The confidences of the observations are not compared; with `confidence_weighted` set, they
+/// break the tie between the observations that agree the most. Nodes report their own scores, so
+/// a score cannot make a less similar observation the consensus.
+///
+/// The round keeps the full comparison report and the seed, so the comparison can be replayed.
+pub struct MinHashConsensus {
+    pub settings: ComparatorSettings,
+}
+
+impl MinHashConsensus {
+    pub fn new(settings: ComparatorSettings) -> Self {
+        MinHashConsensus { settings }
+    }
+
+    /// Uses the settings from `SIMILARITY_THRESHOLD`, `NUM_HASH_FUNCTIONS`, `MINHASH_SEED` and `CONFIDENCE_WEIGHTED`.
+    pub fn from_env() -> Self {
+        MinHashConsensus::new(ComparatorSettings::from_env())
+    }
+}
+
+#[async_trait]
+impl Stage for MinHashConsensus {
+    fn name(&self) -> &str {
+        "minhash"
+    }
+
+    async fn run(&self, round: &mut RoundContext) -> Result<StageOutcome, StageError> {
+        let observations: Vec<String> = round.observations.iter().map(|observation| observation.json.clone()).collect();
+
+        let report = run_comparison(&observations, &self.settings);
+
+        round.comparator = Some(ComparatorSettings { seed: Some(report.seed), ..self.settings });
+        let best = report.best;
+        round.comparison = Some(report);
+        match best {
+            Some(best) => {
+                round.consensus = Some(serde_json::from_str(&observations[best])?);
+                Ok(StageOutcome::Continue)
+            }
+            None => Ok(StageOutcome::Halt("no consensus".to_string())),
+        }
+    }
+}
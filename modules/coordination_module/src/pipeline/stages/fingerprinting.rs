@@ -0,0 +1,26 @@
+use async_trait::async_trait;
+use fingerprint::create::create_hash::create_fingerprint_hash;
+use fingerprint::Fingerprint;
+
+use crate::pipeline::round_context::RoundContext;
+use crate::pipeline::stage::{Stage, StageError, StageOutcome};
+
+/// Computes the fingerprint of the consensus and its keccak256 hash.
+pub struct FingerprintHashing;
+
+#[async_trait]
+impl Stage for FingerprintHashing {
+    fn name(&self) -> &str {
+        "keccak256"
+    }
+
+    async fn run(&self, round: &mut RoundContext) -> Result<StageOutcome, StageError> {
+        let consensus = round.consensus.as_ref().ok_or("no consensus to fingerprint")?;
+        let fingerprint = Fingerprint::from_observation(consensus);
+        let hash = create_fingerprint_hash(&fingerprint).map_err(|err| err.to_string())?;
+
+        round.fingerprint = Some(fingerprint);
+        round.hash = Some(hash);
+        Ok(StageOutcome::Continue)
+    }
+}
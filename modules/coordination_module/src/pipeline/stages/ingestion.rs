@@ -0,0 +1,181 @@
+use async_trait::async_trait;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::UNIX_EPOCH;
+
+use json::envelope::observation_envelope::unix_now;
+use json::envelope::replay_guard::ReplayGuard;
+
+use crate::pipeline::round_context::{Observation, Rejection, RoundContext};
+use crate::pipeline::stage::{Stage, StageError, StageOutcome};
+
+/// Reads the round's observations from the `.json` files of a directory.
+pub struct DirectoryIngestion {
+    dir: PathBuf,
+    /// The files earlier rounds already read and that are still in the directory, when only new
+    /// files are wanted.
+    seen: Option<Mutex<HashSet<PathBuf>>>,
+    /// Set when every file must be a signed observation envelope.
+    envelopes: Option<Mutex<ReplayGuard>>,
+}
+
+impl DirectoryIngestion {
+    /// Reads every observation in `dir` on each round.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        DirectoryIngestion { dir: dir.into(), seen: None, envelopes: None }
+    }
+
+    /// Reads only the observations that arrived in `dir` since the previous round.
+    pub fn watching(dir: impl Into<PathBuf>) -> Self {
+        DirectoryIngestion { dir: dir.into(), seen: Some(Mutex::new(HashSet::new())), envelopes: None }
+    }
+
+    /// Only accepts signed observation envelopes, and loads the observations they carry.
+    ///
+    /// Files that are not envelopes, are not signed by their payload's uploader or were
+    /// already accepted by `guard` are rejected.
+    pub fn requiring_envelopes(mut self, guard: ReplayGuard) -> Self {
+        self.envelopes = Some(Mutex::new(guard));
+        self
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+}
+
+#[async_trait]
+impl Stage for DirectoryIngestion {
+    fn name(&self) -> &str {
+        "directory"
+    }
+
+    async fn run(&self, round: &mut RoundContext) -> Result<StageOutcome, StageError> {
+        let _load = tracing::info_span!("load", dir = %self.dir.display()).entered();
+        if !self.dir.is_dir() {
+            return Err(format!("{} is not a directory", self.dir.display()).into());
+        }
+
+        let mut paths: Vec<PathBuf> = fs::read_dir(&self.dir)?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().and_then(|ext| ext.to_str()) == Some("json"))
+            .collect();
+        paths.sort();
+
+        if let Some(seen) = &self.seen {
+            let mut seen = seen.lock().map_err(|err| err.to_string())?;
+            // Forget the files that were removed, so the set never outgrows the directory
+            let present: HashSet<&PathBuf> = paths.iter().collect();
+            seen.retain(|path| present.contains(path));
+            paths.retain(|path| !seen.contains(path));
+            seen.extend(paths.iter().cloned());
+        }
+
+        for path in paths {
+            let source = path.display().to_string();
+            let json = fs::read_to_string(&path)?;
+            // A file arrived when it was last written
+            let received_at = fs::metadata(&path)?
+                .modified()
+                .ok()
+                .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+                .map(|elapsed| elapsed.as_millis() as u64);
+            let Some(guard) = &self.envelopes else {
+                round.observations.push(Observation { source, json, received_at, uploader: None });
+                continue;
+            };
+            let opened = guard.lock().map_err(|err| err.to_string())?.open(&json, unix_now());
+            match opened {
+                Ok(envelope) => round.observations.push(Observation {
+                    source,
+                    json: envelope.payload_json(),
+                    received_at,
+                    uploader: envelope.uploader().ok(),
+                }),
+                Err(err) => {
+                    tracing::warn!(source = %source, reason = %err, "envelope rejected");
+                    round.rejected.push(Rejection { source, reason: err.to_string() });
+                }
+            }
+        }
+
+        if round.observations.is_empty() {
+            let reason = match round.rejected.len() {
+                0 => format!("no observations in {}", self.dir.display()),
+                rejected => format!("no valid envelopes in {}, {} rejected", self.dir.display(), rejected),
+            };
+            return Ok(StageOutcome::Halt(reason));
+        }
+        tracing::info!(observations = round.observations.len(), "observations loaded");
+        Ok(StageOutcome::Continue)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::signers::{LocalWallet, Signer};
+    use ethers::types::Address;
+    use json::envelope::observation_envelope::ObservationEnvelope;
+    use serde_json::{json, Value};
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_watching_ingestion_reads_only_new_files() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("a.json"), "{}").unwrap();
+        fs::write(dir.path().join("notes.txt"), "ignored").unwrap();
+        let ingestion = DirectoryIngestion::watching(dir.path());
+
+        let mut first = RoundContext::new("round-1");
+        assert_eq!(ingestion.run(&mut first).await.unwrap(), StageOutcome::Continue);
+        assert_eq!(first.observations.len(), 1);
+
+        let mut second = RoundContext::new("round-2");
+        assert!(matches!(ingestion.run(&mut second).await.unwrap(), StageOutcome::Halt(_)));
+
+        fs::write(dir.path().join("b.json"), "{}").unwrap();
+        let mut third = RoundContext::new("round-3");
+        ingestion.run(&mut third).await.unwrap();
+        assert_eq!(third.observations.len(), 1);
+        assert!(third.observations[0].source.ends_with("b.json"));
+
+        // A removed file is forgotten, so a new file of the same name is read again
+        fs::remove_file(dir.path().join("a.json")).unwrap();
+        ingestion.run(&mut RoundContext::new("round-4")).await.unwrap();
+        fs::write(dir.path().join("a.json"), "{}").unwrap();
+        let mut fifth = RoundContext::new("round-5");
+        ingestion.run(&mut fifth).await.unwrap();
+        assert_eq!(fifth.observations.len(), 1);
+        assert!(fifth.observations[0].source.ends_with("a.json"));
+    }
+
+    #[tokio::test]
+    async fn test_envelope_ingestion_rejects_forged_and_replayed_envelopes() {
+        let wallet: LocalWallet = "4c0883a69102937d6231471b5dbb6204fe5129617082796e8e1a1e3b7a1e7e3e".parse().unwrap();
+        let payload = json!({ "character": "Mage", "uploader": format!("{:?}", wallet.address()) });
+        let envelope = ObservationEnvelope::seal(payload.clone(), &wallet).await.unwrap();
+        let mut forged = envelope.clone();
+        forged.payload["uploader"] = json!(format!("{:?}", Address::zero()));
+
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("a.json"), serde_json::to_string(&envelope).unwrap()).unwrap();
+        fs::write(dir.path().join("b.json"), serde_json::to_string(&envelope).unwrap()).unwrap();
+        fs::write(dir.path().join("c.json"), serde_json::to_string(&forged).unwrap()).unwrap();
+        fs::write(dir.path().join("d.json"), payload.to_string()).unwrap();
+        let ingestion = DirectoryIngestion::new(dir.path()).requiring_envelopes(ReplayGuard::default());
+
+        let mut round = RoundContext::new("round-1");
+        assert_eq!(ingestion.run(&mut round).await.unwrap(), StageOutcome::Continue);
+
+        assert_eq!(round.observations.len(), 1);
+        assert_eq!(serde_json::from_str::<Value>(&round.observations[0].json).unwrap(), payload);
+        let rejected: Vec<&str> = round.rejected.iter().map(|rejection| rejection.reason.as_str()).collect();
+        assert_eq!(rejected.len(), 3);
+        assert!(rejected[0].contains("already used"));
+        assert!(rejected[1].contains("did not sign"));
+        assert!(rejected[2].contains("unsigned"));
+    }
+}
@@ -0,0 +1,180 @@
+use async_trait::async_trait;
+use json::input_data::input_reference::{InputHashAlgorithm, InputReference};
+use serde_json::Value;
+use std::sync::Arc;
+
+use crate::inputs::input_store::InputStore;
+use crate::pipeline::round_context::{Observation, Rejection, RoundContext};
+use crate::pipeline::stage::{Stage, StageError, StageOutcome};
+use crate::pipeline::stages::plurality::keep_plurality;
+
+/// Makes sure a round only compares observations of one raw game input.
+///
+/// Every observation must declare its input in `hash_inputdata`, hashed with the algorithm in
+/// `hash_algorithm` (keccak256 by default). With an input store, the raw input must be in the
+/// store and hash to the declared value, and observations are grouped by the keccak256 hash of
+/// the stored input, whichever algorithm they declared it with. Without a store, references
+/// cannot be converted and observations are grouped by the reference they declare. The round
+/// then keeps the input most observations reference and rejects the others; it halts if no
+/// input has more observations than every other one.
+#[derive(Default)]
+pub struct InputDataValidation {
+    store: Option<Arc<InputStore>>,
+}
+
+impl InputDataValidation {
+    /// Checks that the observations agree on their input, without access to the raw inputs.
+    pub fn new() -> Self {
+        InputDataValidation::default()
+    }
+
+    /// Also checks every declared input against the raw input in `store`.
+    pub fn verified_against(store: Arc<InputStore>) -> Self {
+        InputDataValidation { store: Some(store) }
+    }
+
+    /// Reads and, with a store, verifies the input an observation declares.
+    ///
+    /// With a store, this reads the disk; call it from a blocking task.
+    ///
+    /// # Parameters
+    /// - `json`: The observation JSON.
+    ///
+    /// # Returns
+    /// - `Result<InputReference, String>`: The reference the observation is grouped by: the keccak256
+    ///   reference of the stored input with a store, the declared one without. Otherwise why the
+    ///   observation is rejected.
+    pub fn check(&self, json: &str) -> Result<InputReference, String> {
+        let observation: Value = serde_json::from_str(json).map_err(|err| format!("invalid JSON: {}", err))?;
+        let reference = InputReference::from_observation(&observation)?;
+
+        if let Some(store) = &self.store {
+            let bytes = store
+                .get(&reference)
+                .map_err(|err| format!("cannot read the input store: {}", err))?
+                .ok_or_else(|| format!("raw input {} is not in the input store", reference))?;
+            if !reference.matches(&bytes) {
+                return Err(format!("raw input does not hash to {}", reference));
+            }
+            return Ok(InputReference::of(&bytes, InputHashAlgorithm::Keccak256));
+        }
+        Ok(reference)
+    }
+}
+
+#[async_trait]
+impl Stage for InputDataValidation {
+    fn name(&self) -> &str {
+        "input_data"
+    }
+
+    async fn run(&self, round: &mut RoundContext) -> Result<StageOutcome, StageError> {
+        // The store's lookups read the disk, so they run off the async workers
+        let checker = InputDataValidation { store: self.store.clone() };
+        let jsons: Vec<String> = round.observations.iter().map(|observation| observation.json.clone()).collect();
+        let checked = tokio::task::spawn_blocking(move || jsons.iter().map(|json| checker.check(json)).collect::<Vec<_>>()).await?;
+
+        let mut referenced: Vec<(Observation, InputReference)> = Vec::with_capacity(round.observations.len());
+        for (observation, checked) in round.observations.drain(..).zip(checked) {
+            match checked {
+                Ok(reference) => referenced.push((observation, reference)),
+                Err(reason) => {
+                    tracing::warn!(source = %observation.source, reason = %reason, "observation rejected");
+                    round.rejected.push(Rejection { source: observation.source, reason });
+                }
+            }
+        }
+        if referenced.is_empty() {
+            return Ok(StageOutcome::Halt("no valid observations".to_string()));
+        }
+
+        let rejection = |reference: &InputReference, input: &InputReference| format!("references input {}, the round is about {}", reference, input);
+        let input = match keep_plurality(round, referenced, rejection) {
+            Ok(input) => input,
+            Err(inputs) => {
+                return Ok(StageOutcome::Halt(format!(
+                    "observations reference {} different inputs and none of them most often",
+                    inputs
+                )))
+            }
+        };
+        round.input_data = Some(input);
+        tracing::info!(input = %input, observations = round.observations.len(), "input agreed");
+        Ok(StageOutcome::Continue)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn observation(source: &str, input: &[u8], algorithm: InputHashAlgorithm) -> Observation {
+        let mut json: Value = serde_json::from_str(include_str!("../../../json/src/json_objects/json1.json")).unwrap();
+        InputReference::of(input, algorithm).write_to(&mut json);
+        Observation { source: source.to_string(), json: json.to_string(), received_at: None, uploader: None }
+    }
+
+    #[tokio::test]
+    async fn test_keeps_the_input_most_observations_reference() {
+        let mut round = RoundContext::new("round-1");
+        round.observations = vec![
+            observation("a", b"frame 1", InputHashAlgorithm::Keccak256),
+            observation("b", b"frame 2", InputHashAlgorithm::Keccak256),
+            observation("c", b"frame 1", InputHashAlgorithm::Keccak256),
+        ];
+
+        let outcome = InputDataValidation::new().run(&mut round).await.unwrap();
+
+        assert_eq!(outcome, StageOutcome::Continue);
+        assert_eq!(round.input_data, Some(InputReference::of(b"frame 1", InputHashAlgorithm::Keccak256)));
+        let kept: Vec<&str> = round.observations.iter().map(|observation| observation.source.as_str()).collect();
+        assert_eq!(kept, vec!["a", "c"]);
+        assert_eq!(round.rejected.len(), 1);
+        assert_eq!(round.rejected[0].source, "b");
+
+        let mut tied = RoundContext::new("round-2");
+        tied.observations = vec![
+            observation("a", b"frame 1", InputHashAlgorithm::Keccak256),
+            observation("b", b"frame 2", InputHashAlgorithm::Keccak256),
+        ];
+        assert!(matches!(InputDataValidation::new().run(&mut tied).await.unwrap(), StageOutcome::Halt(_)));
+    }
+
+    #[tokio::test]
+    async fn test_rejects_observations_whose_raw_input_is_missing_or_different() {
+        let dir = tempdir().unwrap();
+        let store = Arc::new(InputStore::open(dir.path()).unwrap());
+        store.put(b"frame 1").unwrap();
+        let validation = InputDataValidation::verified_against(store);
+
+        assert!(validation.check(&observation("a", b"frame 1", InputHashAlgorithm::Sha256).json).is_ok());
+        assert!(validation.check(&observation("b", b"frame 2", InputHashAlgorithm::Keccak256).json).unwrap_err().contains("not in the input store"));
+
+        // An input edited after it was stored no longer matches the observations of the original
+        let path = dir.path().join(format!("{:x}", InputReference::of(b"frame 1", InputHashAlgorithm::Keccak256).digest));
+        std::fs::write(path, b"edited frame").unwrap();
+        assert!(validation.check(&observation("c", b"frame 1", InputHashAlgorithm::Keccak256).json).unwrap_err().contains("does not hash"));
+    }
+
+    #[tokio::test]
+    async fn test_groups_references_to_one_stored_input_across_algorithms() {
+        let dir = tempdir().unwrap();
+        let store = Arc::new(InputStore::open(dir.path()).unwrap());
+        store.put(b"frame 1").unwrap();
+        store.put(b"frame 2").unwrap();
+        let mut round = RoundContext::new("round-1");
+        round.observations = vec![
+            observation("a", b"frame 1", InputHashAlgorithm::Keccak256),
+            observation("b", b"frame 1", InputHashAlgorithm::Sha256),
+            observation("c", b"frame 2", InputHashAlgorithm::Keccak256),
+        ];
+
+        let outcome = InputDataValidation::verified_against(store).run(&mut round).await.unwrap();
+
+        assert_eq!(outcome, StageOutcome::Continue);
+        assert_eq!(round.input_data, Some(InputReference::of(b"frame 1", InputHashAlgorithm::Keccak256)));
+        assert_eq!(round.observations.len(), 2);
+        assert_eq!(round.rejected[0].source, "c");
+    }
+}
@@ -0,0 +1,102 @@
+use async_trait::async_trait;
+use ethers::utils::keccak256;
+use fingerprint::FingerprintSubmission;
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+
+use crate::pipeline::round_context::{Observation, RoundContext};
+use crate::pipeline::stage::{Stage, StageError, StageOutcome};
+
+/// Feeds the same observations into every round.
+pub struct InMemoryIngestion {
+    pub observations: Vec<Observation>,
+}
+
+impl InMemoryIngestion {
+    /// Names each observation after its position, `memory:0`, `memory:1` and so on.
+    pub fn from_json(observations: Vec<String>) -> Self {
+        let observations = observations
+            .into_iter()
+            .enumerate()
+            .map(|(index, json)| Observation { source: format!("memory:{}", index), json, received_at: None, uploader: None })
+            .collect();
+        InMemoryIngestion { observations }
+    }
+}
+
+#[async_trait]
+impl Stage for InMemoryIngestion {
+    fn name(&self) -> &str {
+        "memory"
+    }
+
+    async fn run(&self, round: &mut RoundContext) -> Result<StageOutcome, StageError> {
+        round.observations.extend(self.observations.iter().cloned());
+        if round.observations.is_empty() {
+            return Ok(StageOutcome::Halt("no observations".to_string()));
+        }
+        Ok(StageOutcome::Continue)
+    }
+}
+
+/// Stands in for a prover: the proof is the keccak256 hash of the consensus and fingerprint
+/// hash, which proves nothing but lets the rest of the pipeline run.
+pub struct InMemoryProof;
+
+#[async_trait]
+impl Stage for InMemoryProof {
+    fn name(&self) -> &str {
+        "memory"
+    }
+
+    async fn run(&self, round: &mut RoundContext) -> Result<StageOutcome, StageError> {
+        let consensus = round.consensus.as_ref().ok_or("no consensus to prove")?;
+        let hash = round.hash.as_ref().ok_or("no fingerprint hash to prove")?;
+
+        round.proof = Some(keccak256(format!("{}{}", consensus, hash)).to_vec());
+        Ok(StageOutcome::Continue)
+    }
+}
+
+/// Stands in for the proxy contract: remembers the appended hashes and never appends one twice.
+#[derive(Clone, Default)]
+pub struct InMemorySubmission {
+    pub appended: Arc<Mutex<HashSet<String>>>,
+}
+
+#[async_trait]
+impl Stage for InMemorySubmission {
+    fn name(&self) -> &str {
+        "memory"
+    }
+
+    async fn run(&self, round: &mut RoundContext) -> Result<StageOutcome, StageError> {
+        let hash = round.hash.clone().ok_or("no fingerprint hash to submit")?;
+        let newly_appended = self.appended.lock().map_err(|err| err.to_string())?.insert(hash.clone());
+
+        // There is no transaction, so a fresh append gets the hash itself as its transaction hash
+        let tx_hash = if newly_appended { Some(hash.parse()?) } else { None };
+        round.submission = Some(FingerprintSubmission { hash, tx_hash, appended: true, gas_used: None, cost: None });
+        Ok(StageOutcome::Continue)
+    }
+}
+
+/// Keeps every finished round in memory.
+#[derive(Clone, Default)]
+pub struct InMemoryStorage {
+    pub rounds: Arc<Mutex<Vec<RoundContext>>>,
+}
+
+#[async_trait]
+impl Stage for InMemoryStorage {
+    fn name(&self) -> &str {
+        "memory"
+    }
+
+    async fn run(&self, round: &mut RoundContext) -> Result<StageOutcome, StageError> {
+        let mut rounds = self.rounds.lock().map_err(|err| err.to_string())?;
+        round.stored_at = Some(format!("memory:{}", rounds.len()));
+        rounds.push(round.clone());
+        Ok(StageOutcome::Continue)
+    }
+}
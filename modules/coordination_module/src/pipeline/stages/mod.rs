@@ -0,0 +1,11 @@
+pub mod chain;
+pub mod consensus;
+pub mod fingerprinting;
+pub mod ingestion;
+pub mod input_data;
+pub mod memory;
+pub mod model;
+pub mod plurality;
+pub mod submission;
+pub mod timestamp;
+pub mod validation;
@@ -0,0 +1,126 @@
+use ai_module::registry::model_registry::{ModelFamily, ModelRegistry};
+use async_trait::async_trait;
+use serde_json::Value;
+use std::sync::Arc;
+
+use crate::pipeline::round_context::{Observation, Rejection, RoundContext};
+use crate::pipeline::stage::{Stage, StageError, StageOutcome};
+use crate::pipeline::stages::plurality::keep_plurality;
+
+/// Only lets observations of registered models into a round, and only compares compatible ones.
+///
+/// Every observation's `aimodel` and `aiversion` must name a model in the registry that is not
+/// deprecated, supports the observation's `game` and allows its extracted values. The round then
+/// keeps the observations of the model family most of them come from, a model id and a
+/// semver-compatible version range, and rejects the others; it halts if no family has more
+/// observations than every other one.
+pub struct ModelValidation {
+    pub registry: Arc<ModelRegistry>,
+}
+
+impl ModelValidation {
+    /// Checks the model of one observation.
+    ///
+    /// # Parameters
+    /// - `json`: The observation JSON.
+    ///
+    /// # Returns
+    /// - `Result<ModelFamily, String>`: The family of the observation's model, or why it is rejected.
+    pub fn check(&self, json: &str) -> Result<ModelFamily, String> {
+        let observation: Value = serde_json::from_str(json).map_err(|err| format!("invalid JSON: {}", err))?;
+        self.registry.check(&observation).map(|model| model.family()).map_err(|err| err.to_string())
+    }
+}
+
+#[async_trait]
+impl Stage for ModelValidation {
+    fn name(&self) -> &str {
+        "model"
+    }
+
+    async fn run(&self, round: &mut RoundContext) -> Result<StageOutcome, StageError> {
+        round.model_registry = Some(self.registry.digest());
+
+        let mut registered: Vec<(Observation, ModelFamily)> = Vec::with_capacity(round.observations.len());
+        for observation in round.observations.drain(..) {
+            match self.check(&observation.json) {
+                Ok(family) => registered.push((observation, family)),
+                Err(reason) => {
+                    tracing::warn!(source = %observation.source, reason = %reason, "observation rejected");
+                    round.rejected.push(Rejection { source: observation.source, reason });
+                }
+            }
+        }
+        if registered.is_empty() {
+            return Ok(StageOutcome::Halt("no valid observations".to_string()));
+        }
+
+        let rejection = |family: &ModelFamily, compared: &ModelFamily| {
+            format!("model {} is not compatible with {}, the model the round compares", family, compared)
+        };
+        let compared = match keep_plurality(round, registered, rejection) {
+            Ok(compared) => compared,
+            Err(families) => {
+                return Ok(StageOutcome::Halt(format!(
+                    "observations come from {} incompatible models and none of them most often",
+                    families
+                )))
+            }
+        };
+        tracing::info!(models = %compared, observations = round.observations.len(), "models agreed");
+        round.model_family = Some(compared);
+        Ok(StageOutcome::Continue)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::types::H256;
+    use serde_json::json;
+
+    fn registry() -> ModelRegistry {
+        serde_json::from_value(json!({
+            "models": [
+                { "id": "classifier", "hash": format!("{:?}", H256::repeat_byte(1)), "version": "1.0.0", "games": ["arena"] },
+                { "id": "classifier", "hash": format!("{:?}", H256::repeat_byte(2)), "version": "1.1.0", "games": ["arena"] },
+                { "id": "classifier", "hash": format!("{:?}", H256::repeat_byte(3)), "version": "2.0.0", "games": ["arena"] },
+                { "id": "classifier", "hash": format!("{:?}", H256::repeat_byte(4)), "version": "0.1.0", "games": ["arena"], "deprecated": true }
+            ]
+        }))
+        .unwrap()
+    }
+
+    fn observation(source: &str, model: u8, version: &str) -> Observation {
+        let json = json!({ "game": "arena", "aimodel": format!("{:?}", H256::repeat_byte(model)), "aiversion": version });
+        Observation { source: source.to_string(), json: json.to_string(), received_at: None, uploader: None }
+    }
+
+    #[tokio::test]
+    async fn test_keeps_the_compatible_models_most_observations_come_from() {
+        let validation = ModelValidation { registry: Arc::new(registry()) };
+        let mut round = RoundContext::new("round-1");
+        round.observations = vec![
+            observation("a", 1, "1.0.0"),
+            observation("b", 2, "1.1.0"),
+            observation("c", 3, "2.0.0"),
+            observation("d", 4, "0.1.0"),
+            observation("e", 9, "1.0.0"),
+        ];
+
+        assert_eq!(validation.run(&mut round).await.unwrap(), StageOutcome::Continue);
+
+        let kept: Vec<&str> = round.observations.iter().map(|observation| observation.source.as_str()).collect();
+        assert_eq!(kept, vec!["a", "b"]);
+        let rejected: Vec<&str> = round.rejected.iter().map(|rejection| rejection.source.as_str()).collect();
+        assert_eq!(rejected, vec!["d", "e", "c"]);
+        assert!(round.rejected[0].reason.contains("deprecated"));
+        assert!(round.rejected[1].reason.contains("unknown model"));
+        assert_eq!(round.model_family.unwrap().to_string(), "classifier 1.x");
+        assert_eq!(round.model_registry, Some(validation.registry.digest()));
+
+        let mut tied = RoundContext::new("round-2");
+        tied.observations = vec![observation("a", 1, "1.0.0"), observation("c", 3, "2.0.0")];
+        assert!(matches!(validation.run(&mut tied).await.unwrap(), StageOutcome::Halt(_)));
+    }
+}
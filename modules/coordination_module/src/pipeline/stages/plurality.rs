@@ -0,0 +1,69 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use crate::pipeline::round_context::{Observation, Rejection, RoundContext};
+
+/// Keeps the observations whose key the most observations share, and rejects the others.
+///
+/// # Parameters
+/// - `round`: The round the observations are kept in and the rejections recorded in.
+/// - `keyed`: Each observation with its key, such as its model or input.
+/// - `rejection`: Why an observation is rejected, given its key and the kept one.
+///
+/// # Returns
+/// - `Result<K, usize>`: The kept key, or how many keys there are when several are shared by
+///   the most observations. The round then keeps every observation.
+pub fn keep_plurality<K: Eq + Hash + Clone>(
+    round: &mut RoundContext,
+    keyed: Vec<(Observation, K)>,
+    rejection: impl Fn(&K, &K) -> String,
+) -> Result<K, usize> {
+    let mut counts: HashMap<&K, usize> = HashMap::new();
+    for (_, key) in &keyed {
+        *counts.entry(key).or_default() += 1;
+    }
+    let most = counts.values().copied().max().unwrap_or_default();
+    let leaders: Vec<K> = counts.iter().filter(|(_, count)| **count == most).map(|(key, _)| (*key).clone()).collect();
+    if leaders.len() != 1 {
+        let keys = counts.len();
+        round.observations.extend(keyed.into_iter().map(|(observation, _)| observation));
+        return Err(keys);
+    }
+
+    let kept = leaders[0].clone();
+    for (observation, key) in keyed {
+        if key == kept {
+            round.observations.push(observation);
+        } else {
+            let reason = rejection(&key, &kept);
+            tracing::warn!(source = %observation.source, reason = %reason, "observation rejected");
+            round.rejected.push(Rejection { source: observation.source, reason });
+        }
+    }
+    Ok(kept)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keyed(keys: &[&'static str]) -> Vec<(Observation, &'static str)> {
+        keys.iter()
+            .enumerate()
+            .map(|(index, key)| (Observation { source: format!("node-{}", index), json: "{}".to_string(), received_at: None, uploader: None }, *key))
+            .collect()
+    }
+
+    #[test]
+    fn test_keeps_the_most_shared_key_and_halts_on_a_tie() {
+        let mut round = RoundContext::new("round-1");
+        assert_eq!(keep_plurality(&mut round, keyed(&["a", "b", "a"]), |key, kept| format!("{} is not {}", key, kept)), Ok("a"));
+        assert_eq!(round.observations.len(), 2);
+        assert_eq!(round.rejected[0].reason, "b is not a");
+
+        let mut round = RoundContext::new("round-2");
+        assert_eq!(keep_plurality(&mut round, keyed(&["a", "b", "c", "c", "a"]), |_, _| String::new()), Err(3));
+        assert_eq!(round.observations.len(), 5);
+        assert!(round.rejected.is_empty());
+    }
+}
@@ -0,0 +1,68 @@
+use async_trait::async_trait;
+use ethers::prelude::*;
+use fingerprint::dry_run::submission_plan::{estimate_submission, plan_submission};
+use fingerprint::network::profile::NetworkProfile;
+use fingerprint::{run_fingerprint, FingerprintClient};
+use std::sync::Arc;
+
+use crate::pipeline::round_context::RoundContext;
+use crate::pipeline::stage::{Stage, StageError, StageOutcome};
+
+/// Appends the fingerprint hash to the proxy contract and waits for its confirmation.
+pub struct ChainSubmission {
+    pub client: Arc<FingerprintClient>,
+    pub profile: NetworkProfile,
+}
+
+#[async_trait]
+impl Stage for ChainSubmission {
+    fn name(&self) -> &str {
+        "chain"
+    }
+
+    async fn run(&self, round: &mut RoundContext) -> Result<StageOutcome, StageError> {
+        let fingerprint = round.fingerprint.clone().ok_or("no fingerprint to submit")?;
+        let submission = run_fingerprint(fingerprint, self.client.clone(), &self.profile)
+            .await
+            .map_err(|err| err.to_string())?;
+
+        round.submission = Some(submission);
+        Ok(StageOutcome::Continue)
+    }
+}
+
+/// The node a dry run estimates against.
+pub struct DryRunNode {
+    pub provider: Arc<Provider<Http>>,
+    pub profile: NetworkProfile,
+    /// The signer's address, if the signer could be loaded.
+    pub from: Option<Address>,
+}
+
+/// Plans the submission without sending it, estimating its gas when a node is available.
+pub struct DryRunSubmission {
+    pub node: Option<DryRunNode>,
+}
+
+#[async_trait]
+impl Stage for DryRunSubmission {
+    fn name(&self) -> &str {
+        "dry_run"
+    }
+
+    async fn run(&self, round: &mut RoundContext) -> Result<StageOutcome, StageError> {
+        let fingerprint = round.fingerprint.as_ref().ok_or("no fingerprint to plan")?;
+        let mut plan = plan_submission(fingerprint).map_err(|err| err.to_string())?;
+
+        if let Some(node) = &self.node {
+            plan.estimate = Some(
+                estimate_submission(&plan, node.provider.clone(), &node.profile, node.from)
+                    .await
+                    .map_err(|err| err.to_string())?,
+            );
+        }
+
+        round.plan = Some(plan);
+        Ok(StageOutcome::Continue)
+    }
+}
@@ -0,0 +1,216 @@
+use async_trait::async_trait;
+use ethers::types::Address;
+use json::timestamp::observation_time::ObservationTime;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::pipeline::round_context::{NodeSkew, Observation, Rejection, RoundContext};
+use crate::pipeline::stage::{Stage, StageError, StageOutcome};
+
+/// How far ahead of the coordinator's clock an observation may be made by default, in seconds.
+pub const DEFAULT_MAX_CLOCK_SKEW: u64 = 60;
+
+/// The time limits observations are checked against, in seconds.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct TimeWindow {
+    /// How far an observation's timestamp may be ahead of when it was received.
+    pub max_skew: u64,
+    /// How long before it was received an observation may have been made, unlimited when unset.
+    pub max_age: Option<u64>,
+    /// How far apart the timestamps of one round's observations may be, unlimited when unset.
+    pub spread: Option<u64>,
+}
+
+impl Default for TimeWindow {
+    fn default() -> Self {
+        TimeWindow { max_skew: DEFAULT_MAX_CLOCK_SKEW, max_age: None, spread: None }
+    }
+}
+
+/// Checks when observations were made.
+///
+/// Each observation's `timestamp` must be in a supported format and is compared with when the
+/// coordinator received the observation, or when the round started if that is unknown. The
+/// difference is recorded as the clock skew of the observation's node, and observations from too
+/// far in the future or the past are rejected. With a spread, the round keeps the largest group
+/// of observations made within `spread` seconds of each other, the earliest such group on a tie.
+pub struct TimestampValidation {
+    pub window: TimeWindow,
+}
+
+/// The node behind an observation: its verified uploader when it was signed, as the `ainode`
+/// it claims can be anything, otherwise its `ainode`.
+fn node_of(observation: &Value, uploader: Option<Address>) -> String {
+    if let Some(uploader) = uploader {
+        return format!("{:?}", uploader);
+    }
+    match &observation["ainode"] {
+        Value::String(node) => node.clone(),
+        Value::Null => "unknown".to_string(),
+        node => node.to_string(),
+    }
+}
+
+impl TimestampValidation {
+    /// Reads when an observation was made and checks it against the window.
+    ///
+    /// # Parameters
+    /// - `observation`: The observation.
+    /// - `uploader`: The uploader whose signature it carried, if any.
+    /// - `received_at`: When the coordinator received it.
+    ///
+    /// # Returns
+    /// - `(Option<NodeSkew>, Result<ObservationTime, String>)`: The skew of the observation's node,
+    ///   if its timestamp is valid, and the timestamp or why the observation is rejected.
+    fn check(&self, observation: &Value, uploader: Option<Address>, received_at: &ObservationTime) -> (Option<NodeSkew>, Result<ObservationTime, String>) {
+        let time = match ObservationTime::from_observation(observation) {
+            Ok(time) => time,
+            Err(reason) => return (None, Err(reason)),
+        };
+        let seconds = time.seconds_since(received_at);
+        let skew = NodeSkew { node: node_of(observation, uploader), seconds };
+
+        if seconds > self.window.max_skew as f64 {
+            let reason = format!(
+                "timestamp {} is {:.1}s ahead of the coordinator's clock, at most {}s is allowed",
+                time, seconds, self.window.max_skew
+            );
+            return (Some(skew), Err(reason));
+        }
+        if let Some(max_age) = self.window.max_age.filter(|max_age| -seconds > *max_age as f64) {
+            let reason = format!("observation made at {} is {:.0}s old, at most {}s is allowed", time, -seconds, max_age);
+            return (Some(skew), Err(reason));
+        }
+        (Some(skew), Ok(time))
+    }
+}
+
+/// The indices, into `times`, of the largest group of times within `spread` seconds of each other.
+fn largest_group(times: &[ObservationTime], spread: u64) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..times.len()).collect();
+    order.sort_by_key(|index| times[*index]);
+
+    let (mut best_start, mut best_len, mut end) = (0, 0, 0);
+    for start in 0..order.len() {
+        end = end.max(start);
+        while end < order.len() && times[order[end]].seconds_since(&times[order[start]]) <= spread as f64 {
+            end += 1;
+        }
+        if end - start > best_len {
+            (best_start, best_len) = (start, end - start);
+        }
+    }
+    order[best_start..best_start + best_len].to_vec()
+}
+
+#[async_trait]
+impl Stage for TimestampValidation {
+    fn name(&self) -> &str {
+        "timestamp"
+    }
+
+    async fn run(&self, round: &mut RoundContext) -> Result<StageOutcome, StageError> {
+        round.time_window = Some(self.window);
+        let started_at = ObservationTime::from_unix_millis(round.started_at).ok_or("round start time out of range")?;
+
+        let mut timed: Vec<(Observation, ObservationTime)> = Vec::with_capacity(round.observations.len());
+        for observation in round.observations.drain(..) {
+            let received_at = observation.received_at.and_then(ObservationTime::from_unix_millis).unwrap_or(started_at);
+            let checked = match serde_json::from_str::<Value>(&observation.json) {
+                Ok(value) => self.check(&value, observation.uploader, &received_at),
+                Err(err) => (None, Err(format!("invalid JSON: {}", err))),
+            };
+            match checked {
+                (skew, Ok(time)) => {
+                    round.clock_skew.extend(skew);
+                    timed.push((observation, time));
+                }
+                (skew, Err(reason)) => {
+                    round.clock_skew.extend(skew);
+                    tracing::warn!(source = %observation.source, reason = %reason, "observation rejected");
+                    round.rejected.push(Rejection { source: observation.source, reason });
+                }
+            }
+        }
+        if timed.is_empty() {
+            return Ok(StageOutcome::Halt("no valid observations".to_string()));
+        }
+
+        let Some(spread) = self.window.spread else {
+            round.observations = timed.into_iter().map(|(observation, _)| observation).collect();
+            return Ok(StageOutcome::Continue);
+        };
+        let times: Vec<ObservationTime> = timed.iter().map(|(_, time)| *time).collect();
+        let group = largest_group(&times, spread);
+        let first = group.iter().map(|index| times[*index]).min();
+        let last = group.iter().map(|index| times[*index]).max();
+        for (index, (observation, time)) in timed.into_iter().enumerate() {
+            if group.contains(&index) {
+                round.observations.push(observation);
+            } else if let (Some(first), Some(last)) = (first, last) {
+                let reason = format!("timestamp {} is outside the round's window from {} to {}", time, first, last);
+                tracing::warn!(source = %observation.source, reason = %reason, "observation rejected");
+                round.rejected.push(Rejection { source: observation.source, reason });
+            }
+        }
+        Ok(StageOutcome::Continue)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// An observation from `node` made `offset` seconds after the round started.
+    fn observation(round: &RoundContext, node: u64, offset: i64) -> Observation {
+        let started_at = ObservationTime::from_unix_millis(round.started_at).unwrap();
+        let time = ObservationTime(started_at.0 + chrono::Duration::seconds(offset));
+        let json = json!({ "ainode": node, "timestamp": time.to_string() }).to_string();
+        Observation { source: format!("node-{}", node), json, received_at: None, uploader: None }
+    }
+
+    #[tokio::test]
+    async fn test_rejects_observations_from_the_future_or_too_old_and_records_skew() {
+        let mut round = RoundContext::new("round-1");
+        round.observations = vec![
+            observation(&round, 1, 5),
+            observation(&round, 2, 120),
+            observation(&round, 3, -600),
+            Observation { source: "node-4".to_string(), json: json!({ "ainode": 4, "timestamp": "yesterday" }).to_string(), received_at: None, uploader: None },
+        ];
+        // A signed observation's skew is recorded under its uploader, whatever `ainode` it claims
+        round.observations[2].uploader = Some(Address::repeat_byte(3));
+        let validation = TimestampValidation { window: TimeWindow { max_skew: 60, max_age: Some(300), spread: None } };
+
+        assert_eq!(validation.run(&mut round).await.unwrap(), StageOutcome::Continue);
+
+        assert_eq!(round.observations.len(), 1);
+        assert_eq!(round.observations[0].source, "node-1");
+        let rejected: Vec<&str> = round.rejected.iter().map(|rejection| rejection.source.as_str()).collect();
+        assert_eq!(rejected, vec!["node-2", "node-3", "node-4"]);
+        let skew: Vec<(String, f64)> = round.clock_skew.iter().map(|skew| (skew.node.clone(), skew.seconds)).collect();
+        let uploader = format!("{:?}", Address::repeat_byte(3));
+        assert_eq!(skew, vec![("1".to_string(), 5.0), ("2".to_string(), 120.0), (uploader, -600.0)]);
+        assert_eq!(round.time_window.unwrap().max_age, Some(300));
+    }
+
+    #[tokio::test]
+    async fn test_spread_keeps_the_largest_group_of_close_observations() {
+        let mut round = RoundContext::new("round-1");
+        round.observations = vec![
+            observation(&round, 1, -100),
+            observation(&round, 2, -10),
+            observation(&round, 3, -4),
+            observation(&round, 4, -95),
+            observation(&round, 5, -1),
+        ];
+        let validation = TimestampValidation { window: TimeWindow { spread: Some(10), ..TimeWindow::default() } };
+
+        validation.run(&mut round).await.unwrap();
+
+        let kept: Vec<&str> = round.observations.iter().map(|observation| observation.source.as_str()).collect();
+        assert_eq!(kept, vec!["node-2", "node-3", "node-5"]);
+        assert_eq!(round.rejected.len(), 2);
+    }
+}
@@ -0,0 +1,122 @@
+use async_trait::async_trait;
+use serde_json::Value;
+
+use crate::pipeline::round_context::{Rejection, RoundContext};
+use crate::pipeline::stage::{Stage, StageError, StageOutcome};
+
+/// The fields a fingerprint is computed from.
+pub const FINGERPRINT_FIELDS: [&str; 4] = ["character", "place", "ability", "place2"];
+
+/// The fields that identify the AI model and node that produced an observation.
+pub const AI_FIELDS: [&str; 3] = ["aimodel", "aiversion", "ainode"];
+
+/// Rejects observations that are not JSON objects, lack a fingerprint field or lack the AI
+/// metadata of the node that produced them.
+pub struct ObservationValidation;
+
+/// Rejects only the observations that cannot be fingerprinted: those that are not JSON objects
+/// or lack a fingerprint field. `compare` uses it, so observations without AI metadata can still
+/// be compared.
+pub struct FingerprintFieldValidation;
+
+impl ObservationValidation {
+    /// Checks one observation.
+    ///
+    /// # Parameters
+    /// - `json`: The observation JSON.
+    ///
+    /// # Returns
+    /// - `Result<(), String>`: Why the observation is rejected, if it is.
+    pub fn check(json: &str) -> Result<(), String> {
+        let observation = FingerprintFieldValidation::check(json)?;
+        if let Some(field) = AI_FIELDS.iter().find(|field| observation[**field].is_null()) {
+            return Err(format!("missing AI field `{}`", field));
+        }
+        Ok(())
+    }
+}
+
+impl FingerprintFieldValidation {
+    /// Checks one observation.
+    ///
+    /// # Parameters
+    /// - `json`: The observation JSON.
+    ///
+    /// # Returns
+    /// - `Result<Value, String>`: The observation, or why it is rejected.
+    pub fn check(json: &str) -> Result<Value, String> {
+        let observation: Value = serde_json::from_str(json).map_err(|err| format!("invalid JSON: {}", err))?;
+        if !observation.is_object() {
+            return Err("not a JSON object".to_string());
+        }
+        if let Some(field) = FINGERPRINT_FIELDS.iter().find(|field| !observation[**field].is_string()) {
+            return Err(format!("missing string field `{}`", field));
+        }
+        Ok(observation)
+    }
+}
+
+/// Moves the observations `check` rejects to the round's rejections.
+fn reject_invalid(round: &mut RoundContext, check: impl Fn(&str) -> Result<(), String>) -> StageOutcome {
+    let mut accepted = Vec::with_capacity(round.observations.len());
+    for observation in round.observations.drain(..) {
+        match check(&observation.json) {
+            Ok(()) => accepted.push(observation),
+            Err(reason) => {
+                tracing::warn!(source = %observation.source, reason = %reason, "observation rejected");
+                round.rejected.push(Rejection { source: observation.source, reason });
+            }
+        }
+    }
+    round.observations = accepted;
+
+    if round.observations.is_empty() {
+        return StageOutcome::Halt("no valid observations".to_string());
+    }
+    StageOutcome::Continue
+}
+
+#[async_trait]
+impl Stage for ObservationValidation {
+    fn name(&self) -> &str {
+        "observation_fields"
+    }
+
+    async fn run(&self, round: &mut RoundContext) -> Result<StageOutcome, StageError> {
+        Ok(reject_invalid(round, ObservationValidation::check))
+    }
+}
+
+#[async_trait]
+impl Stage for FingerprintFieldValidation {
+    fn name(&self) -> &str {
+        "fingerprint_fields"
+    }
+
+    async fn run(&self, round: &mut RoundContext) -> Result<StageOutcome, StageError> {
+        Ok(reject_invalid(round, |json| FingerprintFieldValidation::check(json).map(|_| ())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pipeline::stages::memory::InMemoryIngestion;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_only_fingerprint_field_validation_accepts_observations_without_ai_fields() {
+        let fingerprinted = json!({ "character": "a", "place": "b", "ability": "c", "place2": "d" }).to_string();
+        let observations = vec![fingerprinted.clone(), json!({ "character": "a" }).to_string()];
+
+        for (stage, accepted) in [(&FingerprintFieldValidation as &dyn Stage, 1), (&ObservationValidation, 0)] {
+            let mut round = RoundContext::new("round-1");
+            InMemoryIngestion::from_json(observations.clone()).run(&mut round).await.unwrap();
+            stage.run(&mut round).await.unwrap();
+
+            assert_eq!(round.observations.len(), accepted);
+            assert_eq!(round.rejected.len(), 2 - accepted);
+        }
+        assert_eq!(ObservationValidation::check(&fingerprinted).unwrap_err(), "missing AI field `aimodel`");
+    }
+}
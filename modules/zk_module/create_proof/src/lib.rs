@@ -0,0 +1,76 @@
+use ark_bn254::Bn254;
+use ark_groth16::{Groth16, ProvingKey, VerifyingKey};
+use ark_snark::SNARK;
+use fingerprint::create::create_hash::create_fingerprint_hash;
+use fingerprint::Fingerprint;
+use rand::{CryptoRng, RngCore};
+use zk_module::fingerprint_proof::circuit::FingerprintCircuit;
+use zk_module::fingerprint_proof::layout::FingerprintLayout;
+use zk_module::fingerprint_proof::proof_error::ProofError;
+use zk_module::fingerprint_proof::proof_file::{parse_hash, FingerprintProof};
+
+/// Generates the proving and verifying keys of a schema's circuit.
+///
+/// This is a Groth16 setup run by one party: whoever knows `rng`'s output can forge proofs, so
+/// the keys are only as trustworthy as the party generating them.
+///
+/// # Parameters
+/// - `layout`: The layout of the schema.
+/// - `rng`: The randomness of the setup, discarded afterwards.
+///
+/// # Returns
+/// - `Result<(ProvingKey<Bn254>, VerifyingKey<Bn254>), ProofError>`: The keys.
+pub fn setup<R: RngCore + CryptoRng>(layout: &FingerprintLayout, rng: &mut R) -> Result<(ProvingKey<Bn254>, VerifyingKey<Bn254>), ProofError> {
+    Ok(Groth16::<Bn254>::circuit_specific_setup(FingerprintCircuit::blank(layout), rng)?)
+}
+
+/// Proves that the hash of `fingerprint`, as published, is the keccak256 hash of a fingerprint
+/// the schema allows. The proof does not reveal which, but the unsalted hash does to anyone who
+/// hashes every fingerprint the schema allows.
+///
+/// # Parameters
+/// - `key`: The proving key of the schema's circuit.
+/// - `layout`: The layout of the same schema.
+/// - `fingerprint`: The fingerprint, the witness of the proof.
+/// - `rng`: The randomness blinding the proof.
+///
+/// # Returns
+/// - `Result<FingerprintProof, ProofError>`: The proof and the hash it is about, or an error if
+///   the schema does not allow the fingerprint.
+pub fn create_proof<R: RngCore + CryptoRng>(
+    key: &ProvingKey<Bn254>,
+    layout: &FingerprintLayout,
+    fingerprint: &Fingerprint,
+    rng: &mut R,
+) -> Result<FingerprintProof, ProofError> {
+    let hash = create_fingerprint_hash(fingerprint).map_err(|err| ProofError::Encoding(err.to_string()))?;
+    let hash = parse_hash(&hash)?;
+    let circuit = FingerprintCircuit::new(layout, fingerprint, hash)?;
+    let proof = Groth16::<Bn254>::prove(key, circuit, rng)?;
+    FingerprintProof::new(&hash, &proof)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ai_module::plugins::arena::ArenaPlugin;
+    use ai_module::plugins::game_plugin::GamePlugin;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use zk_module::fingerprint_proof::schema::FingerprintSchema;
+
+    #[test]
+    fn test_proves_only_fingerprints_the_schema_allows() {
+        let layout = FingerprintLayout::new(&FingerprintSchema::new(ArenaPlugin::new().schema())).unwrap();
+        let mut rng = StdRng::seed_from_u64(7);
+        let (key, _) = setup(&layout, &mut rng).unwrap();
+        let fingerprint = Fingerprint { gamer: "Mage".to_string(), strikes: 0, place: "Bridge".to_string(), weapon: "Fireball".to_string(), place2: "North".to_string() };
+
+        let proof = create_proof(&key, &layout, &fingerprint, &mut rng).unwrap();
+        assert_eq!(proof.hash, create_fingerprint_hash(&fingerprint).unwrap());
+
+        let cheating = Fingerprint { strikes: 3, ..fingerprint };
+        let err = create_proof(&key, &layout, &cheating, &mut rng).unwrap_err();
+        assert_eq!(err, ProofError::NotInSchema { field: "strikes".to_string(), value: "3".to_string() });
+    }
+}
@@ -1,3 +1,98 @@
+use clap::{Args, Parser, Subcommand};
+use create_proof::{create_proof, setup};
+use fingerprint::Fingerprint;
+use rand::rngs::OsRng;
+use serde_json::Value;
+use std::error::Error;
+use std::fs;
+use std::path::PathBuf;
+use std::process;
+use zk_module::fingerprint_proof::layout::FingerprintLayout;
+use zk_module::fingerprint_proof::proof_file::{read_proving_key, write_proving_key, write_verifying_key};
+use zk_module::fingerprint_proof::schema::FingerprintSchema;
+
+/// Proves that a published fingerprint hash is the keccak256 hash of a fingerprint its schema
+/// allows, the proof itself revealing nothing of the fingerprint.
+#[derive(Parser, Debug)]
+#[command(name = "create_proof", version)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Generate the proving and verifying keys of a schema.
+    Setup(SetupArgs),
+    /// Prove the hash of a fingerprint and print the proof's JSON.
+    Prove(ProveArgs),
+}
+
+#[derive(Args, Debug)]
+struct SetupArgs {
+    /// The fingerprint schema.
+    #[arg(long, env = "ZK_SCHEMA")]
+    schema: PathBuf,
+
+    /// Where to write the proving key.
+    #[arg(long, env = "ZK_PROVING_KEY")]
+    proving_key: PathBuf,
+
+    /// Where to write the verifying key.
+    #[arg(long, env = "ZK_VERIFYING_KEY")]
+    verifying_key: PathBuf,
+}
+
+#[derive(Args, Debug)]
+struct ProveArgs {
+    /// The fingerprint, or the consensus observation it is computed from.
+    fingerprint: PathBuf,
+
+    /// The fingerprint schema the proving key was generated for.
+    #[arg(long, env = "ZK_SCHEMA")]
+    schema: PathBuf,
+
+    /// The proving key.
+    #[arg(long, env = "ZK_PROVING_KEY")]
+    proving_key: PathBuf,
+
+    /// Where to write the proof, instead of stdout.
+    #[arg(long)]
+    out: Option<PathBuf>,
+}
+
 fn main() {
-    println!("Hello, world!");
+    let cli = Cli::parse();
+    let result = match cli.command {
+        Command::Setup(args) => run_setup(args),
+        Command::Prove(args) => run_prove(args),
+    };
+    if let Err(err) = result {
+        eprintln!("error: {}", err);
+        process::exit(1);
+    }
+}
+
+fn run_setup(args: SetupArgs) -> Result<(), Box<dyn Error>> {
+    let layout = FingerprintLayout::new(&FingerprintSchema::load(&args.schema)?)?;
+    let (proving_key, verifying_key) = setup(&layout, &mut OsRng)?;
+    write_proving_key(&args.proving_key, &proving_key)?;
+    write_verifying_key(&args.verifying_key, &verifying_key)?;
+    println!("keys written to {} and {}", args.proving_key.display(), args.verifying_key.display());
+    Ok(())
+}
+
+fn run_prove(args: ProveArgs) -> Result<(), Box<dyn Error>> {
+    let layout = FingerprintLayout::new(&FingerprintSchema::load(&args.schema)?)?;
+    let text = fs::read_to_string(&args.fingerprint).map_err(|err| format!("cannot read {}: {}", args.fingerprint.display(), err))?;
+    let value: Value = serde_json::from_str(&text)?;
+    let fingerprint = if value.get("gamer").is_some() { serde_json::from_value(value)? } else { Fingerprint::from_observation(&value) };
+
+    let proof = create_proof(&read_proving_key(&args.proving_key)?, &layout, &fingerprint, &mut OsRng)?;
+    let json = serde_json::to_string_pretty(&proof)?;
+    match args.out {
+        Some(out) => fs::write(&out, json).map_err(|err| format!("cannot write {}: {}", out.display(), err))?,
+        None => println!("{}", json),
+    }
+    Ok(())
 }
@@ -0,0 +1,196 @@
+use ark_bn254::Fr;
+use ark_ff::One;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, LinearCombination, SynthesisError, Variable};
+use fingerprint::Fingerprint;
+
+use crate::fingerprint_proof::keccak::{boolean, keccak256, Bit, DIGEST_BITS, RATE_BYTES};
+use crate::fingerprint_proof::layout::{FingerprintLayout, OPENING};
+use crate::fingerprint_proof::proof_error::ProofError;
+
+/// The public inputs of a proof: the fingerprint hash, as its high and low 128 bits.
+///
+/// # Parameters
+/// - `hash`: The keccak256 hash, as published.
+pub fn public_inputs(hash: &[u8; 32]) -> Vec<Fr> {
+    let (high, low) = hash.split_at(16);
+    [high, low].into_iter().map(|half| Fr::from(u128::from_be_bytes(half.try_into().unwrap_or_default()))).collect()
+}
+
+/// The statement "`hash` is the keccak256 hash of the JSON of a fingerprint `layout`'s schema
+/// allows"; the fingerprint itself is the witness, which the proof does not reveal.
+///
+/// The prover picks one placement per slot of the layout. Each placement is a 0/1 selector, one
+/// per slot is set, and every slot starts where the one before it ends, so the selectors spell
+/// exactly one allowed JSON. Every message bit is then a sum of selectors, which keeps the
+/// message free of constraints; hashing it takes about 155 000 per keccak256 block.
+pub struct FingerprintCircuit<'a> {
+    layout: &'a FingerprintLayout,
+    choices: Option<Vec<usize>>,
+    hash: Option<[u8; 32]>,
+}
+
+impl<'a> FingerprintCircuit<'a> {
+    /// The circuit without a witness, to generate its keys.
+    pub fn blank(layout: &'a FingerprintLayout) -> Self {
+        FingerprintCircuit { layout, choices: None, hash: None }
+    }
+
+    /// The circuit proving `fingerprint`.
+    ///
+    /// # Parameters
+    /// - `layout`: The layout of the schema the keys were generated for.
+    /// - `fingerprint`: The private fingerprint.
+    /// - `hash`: Its keccak256 hash, as published.
+    ///
+    /// # Returns
+    /// - `Result<FingerprintCircuit, ProofError>`: The circuit, or an error if the schema does not
+    ///   allow the fingerprint.
+    pub fn new(layout: &'a FingerprintLayout, fingerprint: &Fingerprint, hash: [u8; 32]) -> Result<Self, ProofError> {
+        Ok(FingerprintCircuit { layout, choices: Some(layout.choose(fingerprint)?), hash: Some(hash) })
+    }
+}
+
+impl ConstraintSynthesizer<Fr> for FingerprintCircuit<'_> {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+        let layout = self.layout;
+        let inputs = self.hash.map(|hash| public_inputs(&hash));
+        let hash_halves = (0..2)
+            .map(|half| cs.new_input_variable(|| inputs.as_ref().map(|inputs| inputs[half]).ok_or(SynthesisError::AssignmentMissing)))
+            .collect::<Result<Vec<Variable>, SynthesisError>>()?;
+        let chosen = self.choices.as_ref().map(|choices| layout.placements(choices));
+        let message_bytes = self.choices.as_ref().map(|choices| layout.padded_message(choices));
+        let last_block = self.choices.as_ref().map(|choices| layout.last_block(choices));
+
+        // The message bits as sums of selectors, starting with the opening every fingerprint shares
+        let mut message = vec![LinearCombination::<Fr>::zero(); 8 * RATE_BYTES * layout.blocks()];
+        for (index, byte) in OPENING.iter().enumerate() {
+            for bit in (0..8).filter(|bit| byte >> bit & 1 == 1) {
+                message[8 * index + bit] += (Fr::one(), Variable::One);
+            }
+        }
+        let mut ends = vec![LinearCombination::<Fr>::zero(); layout.blocks()];
+        let mut previous_end: Option<LinearCombination<Fr>> = None;
+        let last_slot = layout.slots().len() - 1;
+
+        for (index, slot) in layout.slots().iter().enumerate() {
+            let mut selected = LinearCombination::<Fr>::zero();
+            let mut start = LinearCombination::<Fr>::zero();
+            let mut end = LinearCombination::<Fr>::zero();
+            for placement in &slot.placements {
+                let selector = boolean(&cs, chosen.as_ref().map(|chosen| chosen[index] == *placement))?;
+                let option = &slot.options[placement.option];
+                selected += (Fr::one(), selector);
+                start += (Fr::from(placement.offset as u64), selector);
+                end += (Fr::from((placement.offset + option.len()) as u64), selector);
+
+                for (position, byte) in option.iter().enumerate() {
+                    for bit in (0..8).filter(|bit| byte >> bit & 1 == 1) {
+                        message[8 * (placement.offset + position) + bit] += (Fr::one(), selector);
+                    }
+                }
+                if index == last_slot {
+                    let block = (0..layout.blocks()).find(|block| layout.ends_in(placement, *block)).unwrap_or_default();
+                    ends[block] += (Fr::one(), selector);
+                }
+            }
+
+            // One placement per slot, starting where the slot before it ends
+            cs.enforce_constraint(selected, Variable::One.into(), Variable::One.into())?;
+            if let Some(previous_end) = previous_end {
+                cs.enforce_constraint(start, Variable::One.into(), previous_end)?;
+            }
+            previous_end = Some(end);
+        }
+
+        // The padding bit ending the block the message ends in
+        for (block, end) in ends.iter().enumerate() {
+            let last_bit = 8 * (RATE_BYTES * (block + 1) - 1) + 7;
+            message[last_bit] = &message[last_bit] + end;
+        }
+
+        let bits: Vec<Bit<Fr>> = message
+            .into_iter()
+            .enumerate()
+            .map(|(index, lc)| Bit::from_lc(lc, message_bytes.as_ref().map(|bytes| bytes[index / 8] >> (index % 8) & 1 == 1)))
+            .collect();
+        let blocks: Vec<Vec<Bit<Fr>>> = bits.chunks(8 * RATE_BYTES).map(<[Bit<Fr>]>::to_vec).collect();
+        let digests = keccak256(&cs, &blocks)?;
+
+        // The hash is the digest after the block the message ends in
+        let digest: Vec<Bit<Fr>> = if digests.len() == 1 {
+            digests.into_iter().next().unwrap_or_default()
+        } else {
+            let ends: Vec<Bit<Fr>> = ends
+                .into_iter()
+                .enumerate()
+                .map(|(block, lc)| Bit::from_lc(lc, last_block.map(|last_block| last_block == block)))
+                .collect();
+            let mut digest = Vec::with_capacity(DIGEST_BITS);
+            for bit in 0..DIGEST_BITS {
+                let mut sum = LinearCombination::<Fr>::zero();
+                let mut value = Some(false);
+                for (end, block_digest) in ends.iter().zip(&digests) {
+                    let selected = end.and(&cs, &block_digest[bit])?;
+                    sum = sum + selected.lc();
+                    value = value.zip(selected.value()).map(|(value, selected)| value | selected);
+                }
+                digest.push(Bit::from_lc(sum, value));
+            }
+            digest
+        };
+
+        // Both halves of the hash, most significant byte first
+        for (half, variable) in hash_halves.into_iter().enumerate() {
+            let mut packed = LinearCombination::<Fr>::zero();
+            for byte in 0..16 {
+                for bit in 0..8 {
+                    let weight = Fr::from(1u128 << (8 * (15 - byte) + bit));
+                    packed = packed + (weight, digest[8 * (16 * half + byte) + bit].lc());
+                }
+            }
+            cs.enforce_constraint(packed, Variable::One.into(), variable.into())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fingerprint_proof::schema::FingerprintSchema;
+    use ai_module::inference::observation::{ObservationField, OutputSchema};
+    use ark_relations::r1cs::ConstraintSystem;
+    use ethers::utils::keccak256 as native_keccak256;
+
+    fn satisfied(layout: &FingerprintLayout, fingerprint: &Fingerprint, hash: [u8; 32]) -> bool {
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        FingerprintCircuit::new(layout, fingerprint, hash).unwrap().generate_constraints(cs.clone()).unwrap();
+        cs.is_satisfied().unwrap()
+    }
+
+    #[test]
+    fn test_is_satisfied_only_by_the_hash_of_the_fingerprint() {
+        // Long values push some fingerprints into a second block
+        let long = "Sorcerer of the Northern Wastes and the Frozen Bridge Beyond the Keep".to_string();
+        let fields: OutputSchema = [
+            (ObservationField::Character, vec!["Mage".to_string(), long.clone()]),
+            (ObservationField::Ability, vec!["Fireball".to_string(), "Shield \"Bash\"".to_string()]),
+            (ObservationField::Place, vec!["Bridge".to_string()]),
+            (ObservationField::Place2, vec!["North".to_string(), long.clone()]),
+        ]
+        .into_iter()
+        .collect();
+        let layout = FingerprintLayout::new(&FingerprintSchema::new(fields)).unwrap();
+        assert_eq!(layout.blocks(), 2);
+
+        let short = Fingerprint { gamer: "Mage".to_string(), strikes: 0, place: "Bridge".to_string(), weapon: "Fireball".to_string(), place2: "North".to_string() };
+        let escaped = Fingerprint { gamer: long.clone(), weapon: "Shield \"Bash\"".to_string(), place2: long, ..short.clone() };
+        for fingerprint in [&short, &escaped] {
+            let hash = native_keccak256(serde_json::to_string(fingerprint).unwrap());
+            assert!(satisfied(&layout, fingerprint, hash));
+        }
+
+        let other = native_keccak256(serde_json::to_string(&escaped).unwrap());
+        assert!(!satisfied(&layout, &short, other));
+    }
+}
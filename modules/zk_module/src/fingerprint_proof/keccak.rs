@@ -0,0 +1,241 @@
+use ark_ff::PrimeField;
+use ark_relations::r1cs::{ConstraintSystemRef, LinearCombination, SynthesisError, Variable};
+
+/// The bytes of a message absorbed by one Keccak-f[1600] permutation of keccak256.
+pub const RATE_BYTES: usize = 136;
+/// The bits of the Keccak-f[1600] state.
+pub const STATE_BITS: usize = 1600;
+/// The bits of a keccak256 digest.
+pub const DIGEST_BITS: usize = 256;
+
+const ROUND_CONSTANTS: [u64; 24] = [
+    0x0000000000000001, 0x0000000000008082, 0x800000000000808A, 0x8000000080008000,
+    0x000000000000808B, 0x0000000080000001, 0x8000000080008081, 0x8000000000008009,
+    0x000000000000008A, 0x0000000000000088, 0x0000000080008009, 0x000000008000000A,
+    0x000000008000808B, 0x800000000000008B, 0x8000000000008089, 0x8000000000008003,
+    0x8000000000008002, 0x8000000000000080, 0x000000000000800A, 0x800000008000000A,
+    0x8000000080008081, 0x8000000000008080, 0x0000000080000001, 0x8000000080008008,
+];
+
+/// The rotation of lane `(x, y)`, indexed `[x][y]`.
+const ROTATIONS: [[usize; 5]; 5] = [
+    [0, 36, 3, 41, 18],
+    [1, 44, 10, 45, 2],
+    [62, 6, 43, 15, 61],
+    [28, 55, 25, 21, 56],
+    [27, 20, 39, 8, 14],
+];
+
+/// A bit of the circuit: a constant, or a linear combination of variables that is 0 or 1.
+#[derive(Debug, Clone)]
+pub enum Bit<F: PrimeField> {
+    Constant(bool),
+    /// The combination, and its value when the circuit is proven rather than set up.
+    Linear(LinearCombination<F>, Option<bool>),
+}
+
+impl<F: PrimeField> Bit<F> {
+    /// Allocates a bit the prover chooses.
+    pub fn witness(cs: &ConstraintSystemRef<F>, value: Option<bool>) -> Result<Self, SynthesisError> {
+        Ok(Bit::Linear(boolean(cs, value)?.into(), value))
+    }
+
+    /// The bit `lc` is, known to be 0 or 1 from how it was built, folded into a constant when it
+    /// holds no variable.
+    pub fn from_lc(lc: LinearCombination<F>, value: Option<bool>) -> Self {
+        match lc.0.as_slice() {
+            [] => Bit::Constant(false),
+            [(coefficient, Variable::One)] if coefficient.is_one() => Bit::Constant(true),
+            _ => Bit::Linear(lc, value),
+        }
+    }
+
+    pub fn value(&self) -> Option<bool> {
+        match self {
+            Bit::Constant(value) => Some(*value),
+            Bit::Linear(_, value) => *value,
+        }
+    }
+
+    pub fn lc(&self) -> LinearCombination<F> {
+        match self {
+            Bit::Constant(true) => Variable::One.into(),
+            Bit::Constant(false) => LinearCombination::zero(),
+            Bit::Linear(lc, _) => lc.clone(),
+        }
+    }
+
+    pub fn not(&self) -> Self {
+        match self {
+            Bit::Constant(value) => Bit::Constant(!*value),
+            Bit::Linear(lc, value) => Bit::Linear(LinearCombination::from(Variable::One) - lc, value.map(|value| !value)),
+        }
+    }
+
+    /// `self ^ other`, one constraint unless either is a constant.
+    pub fn xor(&self, cs: &ConstraintSystemRef<F>, other: &Self) -> Result<Self, SynthesisError> {
+        match (self, other) {
+            (Bit::Constant(false), bit) | (bit, Bit::Constant(false)) => Ok(bit.clone()),
+            (Bit::Constant(true), bit) | (bit, Bit::Constant(true)) => Ok(bit.not()),
+            (Bit::Linear(a, a_value), Bit::Linear(b, b_value)) => {
+                let value = a_value.zip(*b_value).map(|(a, b)| a ^ b);
+                let c = cs.new_witness_variable(|| value.map(F::from).ok_or(SynthesisError::AssignmentMissing))?;
+                // 2a * b = a + b - c
+                cs.enforce_constraint(a * F::from(2u64), b.clone(), (a + b) - c)?;
+                Ok(Bit::Linear(c.into(), value))
+            }
+        }
+    }
+
+    /// `self & other`, one constraint unless either is a constant.
+    pub fn and(&self, cs: &ConstraintSystemRef<F>, other: &Self) -> Result<Self, SynthesisError> {
+        match (self, other) {
+            (Bit::Constant(false), _) | (_, Bit::Constant(false)) => Ok(Bit::Constant(false)),
+            (Bit::Constant(true), bit) | (bit, Bit::Constant(true)) => Ok(bit.clone()),
+            (Bit::Linear(a, a_value), Bit::Linear(b, b_value)) => {
+                let value = a_value.zip(*b_value).map(|(a, b)| a & b);
+                let c = cs.new_witness_variable(|| value.map(F::from).ok_or(SynthesisError::AssignmentMissing))?;
+                cs.enforce_constraint(a.clone(), b.clone(), c.into())?;
+                Ok(Bit::Linear(c.into(), value))
+            }
+        }
+    }
+}
+
+/// Allocates a witness variable constrained to 0 or 1.
+pub fn boolean<F: PrimeField>(cs: &ConstraintSystemRef<F>, value: Option<bool>) -> Result<Variable, SynthesisError> {
+    let variable = cs.new_witness_variable(|| value.map(F::from).ok_or(SynthesisError::AssignmentMissing))?;
+    cs.enforce_constraint(variable.into(), LinearCombination::from(Variable::One) - variable, LinearCombination::zero())?;
+    Ok(variable)
+}
+
+/// The Keccak-f[1600] permutation.
+///
+/// # Parameters
+/// - `state`: The 1600 bits of the state, lane `x + 5y` holding bits `64 * (x + 5y)..`, least
+///   significant bit first.
+///
+/// # Returns
+/// - `Result<Vec<Bit<F>>, SynthesisError>`: The permuted state.
+pub fn keccak_f<F: PrimeField>(cs: &ConstraintSystemRef<F>, mut state: Vec<Bit<F>>) -> Result<Vec<Bit<F>>, SynthesisError> {
+    assert_eq!(state.len(), STATE_BITS, "the Keccak state has 1600 bits");
+    let at = |x: usize, y: usize, z: usize| 64 * (x % 5 + 5 * (y % 5)) + z % 64;
+
+    for round_constant in ROUND_CONSTANTS {
+        // θ: xor every bit with the parities of two neighbouring columns
+        let mut parities = Vec::with_capacity(5 * 64);
+        for x in 0..5 {
+            for z in 0..64 {
+                let mut parity = state[at(x, 0, z)].clone();
+                for y in 1..5 {
+                    parity = parity.xor(cs, &state[at(x, y, z)])?;
+                }
+                parities.push(parity);
+            }
+        }
+        for x in 0..5 {
+            for z in 0..64 {
+                let d = parities[64 * ((x + 4) % 5) + z].xor(cs, &parities[64 * ((x + 1) % 5) + (z + 63) % 64])?;
+                for y in 0..5 {
+                    state[at(x, y, z)] = state[at(x, y, z)].xor(cs, &d)?;
+                }
+            }
+        }
+
+        // ρ and π: rotate every lane and move it
+        let mut moved = vec![Bit::Constant(false); STATE_BITS];
+        for x in 0..5 {
+            for y in 0..5 {
+                for z in 0..64 {
+                    moved[at(y, 2 * x + 3 * y, z + ROTATIONS[x][y])] = state[at(x, y, z)].clone();
+                }
+            }
+        }
+
+        // χ: a ^ (!b & c) along each row
+        for x in 0..5 {
+            for y in 0..5 {
+                for z in 0..64 {
+                    let masked = moved[at(x + 1, y, z)].not().and(cs, &moved[at(x + 2, y, z)])?;
+                    state[at(x, y, z)] = moved[at(x, y, z)].xor(cs, &masked)?;
+                }
+            }
+        }
+
+        // ι
+        for (z, bit) in state.iter_mut().take(64).enumerate() {
+            if round_constant >> z & 1 == 1 {
+                *bit = bit.not();
+            }
+        }
+    }
+    Ok(state)
+}
+
+/// keccak256 of a message already padded to whole blocks, as the digest after every block.
+///
+/// # Parameters
+/// - `blocks`: The padded message, 1088 bits per block, least significant bit of each byte first.
+///
+/// # Returns
+/// - `Result<Vec<Vec<Bit<F>>>, SynthesisError>`: The 256 digest bits after each block, the last
+///   being the hash of the whole message.
+pub fn keccak256<F: PrimeField>(cs: &ConstraintSystemRef<F>, blocks: &[Vec<Bit<F>>]) -> Result<Vec<Vec<Bit<F>>>, SynthesisError> {
+    let mut state = vec![Bit::Constant(false); STATE_BITS];
+    let mut digests = Vec::with_capacity(blocks.len());
+    for block in blocks {
+        assert_eq!(block.len(), 8 * RATE_BYTES, "keccak256 absorbs 1088 bits per block");
+        for (index, bit) in block.iter().enumerate() {
+            state[index] = state[index].xor(cs, bit)?;
+        }
+        state = keccak_f(cs, state)?;
+        digests.push(state[..DIGEST_BITS].to_vec());
+    }
+    Ok(digests)
+}
+
+/// The bits of `bytes`, least significant bit of each byte first, as constants.
+pub fn constant_bits<F: PrimeField>(bytes: &[u8]) -> Vec<Bit<F>> {
+    bytes.iter().flat_map(|byte| (0..8).map(move |bit| Bit::Constant(byte >> bit & 1 == 1))).collect()
+}
+
+/// The bytes of `bits`, when all their values are known.
+pub fn bytes_of<F: PrimeField>(bits: &[Bit<F>]) -> Option<Vec<u8>> {
+    bits.chunks(8)
+        .map(|byte| byte.iter().enumerate().try_fold(0u8, |acc, (index, bit)| Some(acc | (bit.value()? as u8) << index)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bn254::Fr;
+    use ark_relations::r1cs::ConstraintSystem;
+    use ethers::utils::keccak256 as native_keccak256;
+
+    fn padded(message: &[u8]) -> Vec<u8> {
+        let mut padded = message.to_vec();
+        padded.push(0x01);
+        padded.resize(padded.len().div_ceil(RATE_BYTES) * RATE_BYTES, 0);
+        *padded.last_mut().unwrap() |= 0x80;
+        padded
+    }
+
+    #[test]
+    fn test_matches_keccak256_over_one_and_two_blocks() {
+        for message in [&b""[..], b"{\"gamer\":\"Mage\"}", &[0x61; 200]] {
+            let cs = ConstraintSystem::<Fr>::new_ref();
+            let bits: Vec<Bit<Fr>> = padded(message)
+                .iter()
+                .flat_map(|byte| (0..8).map(move |bit| byte >> bit & 1 == 1))
+                .map(|value| Bit::witness(&cs, Some(value)).unwrap())
+                .collect();
+            let blocks: Vec<Vec<Bit<Fr>>> = bits.chunks(8 * RATE_BYTES).map(<[Bit<Fr>]>::to_vec).collect();
+
+            let digests = keccak256(&cs, &blocks).unwrap();
+
+            assert_eq!(bytes_of(digests.last().unwrap()).unwrap(), native_keccak256(message).to_vec());
+            assert!(cs.is_satisfied().unwrap());
+        }
+    }
+}
@@ -0,0 +1,196 @@
+use ai_module::inference::observation::ObservationField;
+use fingerprint::Fingerprint;
+use std::collections::BTreeSet;
+
+use crate::fingerprint_proof::keccak::RATE_BYTES;
+use crate::fingerprint_proof::proof_error::ProofError;
+use crate::fingerprint_proof::schema::FingerprintSchema;
+
+/// The text every fingerprint's JSON starts with.
+pub const OPENING: &[u8] = br#"{"gamer":""#;
+
+/// The keccak256 padding byte following the message.
+const PADDING_START: u8 = 0x01;
+/// The keccak256 padding bit ending the message's last block.
+const PADDING_END: u8 = 0x80;
+
+/// Where a slot's text may start, with which of its options.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Placement {
+    pub offset: usize,
+    pub option: usize,
+}
+
+/// One field of the fingerprint JSON: its value, followed by the text up to the next value.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Slot {
+    /// The fingerprint field, as in its JSON.
+    pub field: &'static str,
+    /// The values the field may hold.
+    pub values: Vec<String>,
+    /// The bytes of each value, JSON-escaped and followed by the text up to the next value.
+    pub options: Vec<Vec<u8>>,
+    /// Every offset and option the slot can take, given the lengths of the slots before it.
+    pub placements: Vec<Placement>,
+}
+
+/// The JSON of every fingerprint a schema allows, `serde_json::to_string(&fingerprint)` followed
+/// by the keccak256 padding, laid out as the circuit reads it.
+///
+/// The JSON is the fixed opening, then one slot per field. A slot starts where the one before it
+/// ends, so each slot lists every offset it can start at given the values allowed before it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FingerprintLayout {
+    slots: Vec<Slot>,
+    blocks: usize,
+}
+
+impl FingerprintLayout {
+    /// # Parameters
+    /// - `schema`: The values the fingerprint may hold.
+    ///
+    /// # Returns
+    /// - `Result<FingerprintLayout, ProofError>`: The layout, or an error if a field has no values.
+    pub fn new(schema: &FingerprintSchema) -> Result<Self, ProofError> {
+        let strikes: Vec<String> = schema.strikes.iter().map(u64::to_string).collect::<BTreeSet<_>>().into_iter().collect();
+        if strikes.is_empty() {
+            return Err(ProofError::Schema("`strikes` has no values".to_string()));
+        }
+        let fields: [(&'static str, Vec<String>, &[u8]); 5] = [
+            ("gamer", schema.values(ObservationField::Character)?, br#"","strikes":"#),
+            ("strikes", strikes, br#","place":""#),
+            ("place", schema.values(ObservationField::Place)?, br#"","weapon":""#),
+            ("weapon", schema.values(ObservationField::Ability)?, br#"","place2":""#),
+            ("place2", schema.values(ObservationField::Place2)?, &[b'"', b'}', PADDING_START]),
+        ];
+
+        let mut offsets = BTreeSet::from([OPENING.len()]);
+        let mut slots = Vec::with_capacity(fields.len());
+        for (field, values, text) in fields {
+            let options: Vec<Vec<u8>> = values
+                .iter()
+                .map(|value| {
+                    // Strikes are numbers, every other value a string whose quotes are in the fixed text
+                    let mut option = if field == "strikes" { value.clone().into_bytes() } else { json_string(value) };
+                    option.extend_from_slice(text);
+                    option
+                })
+                .collect();
+            let placements: Vec<Placement> = offsets
+                .iter()
+                .flat_map(|offset| (0..options.len()).map(move |option| Placement { offset: *offset, option }))
+                .collect();
+            offsets = placements.iter().map(|placement| placement.offset + options[placement.option].len()).collect();
+            slots.push(Slot { field, values, options, placements });
+        }
+
+        let longest = offsets.last().copied().unwrap_or_default();
+        Ok(FingerprintLayout { slots, blocks: longest.div_ceil(RATE_BYTES) })
+    }
+
+    pub fn slots(&self) -> &[Slot] {
+        &self.slots
+    }
+
+    /// The keccak256 blocks the longest fingerprint takes.
+    pub fn blocks(&self) -> usize {
+        self.blocks
+    }
+
+    /// The option of every slot holding `fingerprint`'s values.
+    ///
+    /// # Returns
+    /// - `Result<Vec<usize>, ProofError>`: The options, or the first value the schema does not allow.
+    pub fn choose(&self, fingerprint: &Fingerprint) -> Result<Vec<usize>, ProofError> {
+        let strikes = fingerprint.strikes.to_string();
+        let values = [&fingerprint.gamer, &strikes, &fingerprint.place, &fingerprint.weapon, &fingerprint.place2];
+        self.slots
+            .iter()
+            .zip(values)
+            .map(|(slot, value)| {
+                slot.values
+                    .iter()
+                    .position(|allowed| allowed == value)
+                    .ok_or_else(|| ProofError::NotInSchema { field: slot.field.to_string(), value: value.clone() })
+            })
+            .collect()
+    }
+
+    /// Where every slot is, with `choices`.
+    pub fn placements(&self, choices: &[usize]) -> Vec<Placement> {
+        let mut offset = OPENING.len();
+        self.slots
+            .iter()
+            .zip(choices)
+            .map(|(slot, &option)| {
+                let placement = Placement { offset, option };
+                offset += slot.options[option].len();
+                placement
+            })
+            .collect()
+    }
+
+    /// The padded message the circuit hashes with `choices`, `blocks() * 136` bytes long.
+    ///
+    /// The message ends in the block holding its `0x01` padding byte, whose last byte carries the
+    /// `0x80` padding bit; the blocks after it are zero and do not change the hash.
+    pub fn padded_message(&self, choices: &[usize]) -> Vec<u8> {
+        let mut message = OPENING.to_vec();
+        for (slot, &option) in self.slots.iter().zip(choices) {
+            message.extend_from_slice(&slot.options[option]);
+        }
+        let end_of_block = (message.len() - 1) / RATE_BYTES * RATE_BYTES + RATE_BYTES - 1;
+        message.resize(self.blocks * RATE_BYTES, 0);
+        message[end_of_block] |= PADDING_END;
+        message
+    }
+
+    /// The block the message ends in with `choices`.
+    pub fn last_block(&self, choices: &[usize]) -> usize {
+        let last = self.slots.len() - 1;
+        let placement = self.placements(choices)[last];
+        (placement.offset + self.slots[last].options[placement.option].len() - 1) / RATE_BYTES
+    }
+
+    /// Whether the last slot's `placement` ends the message in `block`.
+    pub fn ends_in(&self, placement: &Placement, block: usize) -> bool {
+        let last = &self.slots[self.slots.len() - 1];
+        (placement.offset + last.options[placement.option].len() - 1) / RATE_BYTES == block
+    }
+}
+
+/// The bytes between the quotes of `value` as a JSON string.
+fn json_string(value: &str) -> Vec<u8> {
+    let quoted = serde_json::to_string(value).unwrap_or_default().into_bytes();
+    quoted[1..quoted.len() - 1].to_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ai_module::plugins::arena::ArenaPlugin;
+    use ai_module::plugins::game_plugin::GamePlugin;
+
+    #[test]
+    fn test_lays_out_the_json_of_every_allowed_fingerprint() {
+        let layout = FingerprintLayout::new(&FingerprintSchema::new(ArenaPlugin::new().schema())).unwrap();
+        let fingerprint = Fingerprint {
+            gamer: "Knight".to_string(),
+            strikes: 0,
+            place: "Keep".to_string(),
+            weapon: "Shield Bash".to_string(),
+            place2: "South".to_string(),
+        };
+
+        let choices = layout.choose(&fingerprint).unwrap();
+        let message = layout.padded_message(&choices);
+
+        let json = serde_json::to_string(&fingerprint).unwrap();
+        assert_eq!(&message[..json.len()], json.as_bytes());
+        assert_eq!((message[json.len()], message[RATE_BYTES - 1], message.len()), (PADDING_START, PADDING_END, RATE_BYTES));
+        assert_eq!((layout.blocks(), layout.last_block(&choices)), (1, 0));
+
+        let unknown = Fingerprint { place2: "West".to_string(), ..fingerprint };
+        assert_eq!(layout.choose(&unknown), Err(ProofError::NotInSchema { field: "place2".to_string(), value: "West".to_string() }));
+    }
+}
@@ -0,0 +1,6 @@
+pub mod circuit;
+pub mod keccak;
+pub mod layout;
+pub mod proof_error;
+pub mod proof_file;
+pub mod schema;
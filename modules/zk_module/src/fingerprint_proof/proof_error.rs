@@ -0,0 +1,34 @@
+use ark_relations::r1cs::SynthesisError;
+use std::fmt;
+
+/// Why a fingerprint could not be proven, or a proof could not be checked.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProofError {
+    /// The schema cannot be proven against, for example a field without values.
+    Schema(String),
+    /// The fingerprint holds a value its schema does not allow.
+    NotInSchema { field: String, value: String },
+    /// The circuit could not be built, or the proof could not be created or checked.
+    Synthesis(String),
+    /// A key, proof or hash could not be read or written.
+    Encoding(String),
+}
+
+impl fmt::Display for ProofError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProofError::Schema(reason) => write!(f, "invalid schema: {}", reason),
+            ProofError::NotInSchema { field, value } => write!(f, "`{}` is `{}`, which the schema does not allow", field, value),
+            ProofError::Synthesis(reason) => write!(f, "proof system: {}", reason),
+            ProofError::Encoding(reason) => write!(f, "encoding: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for ProofError {}
+
+impl From<SynthesisError> for ProofError {
+    fn from(err: SynthesisError) -> Self {
+        ProofError::Synthesis(err.to_string())
+    }
+}
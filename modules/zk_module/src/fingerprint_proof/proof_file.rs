@@ -0,0 +1,85 @@
+use ark_bn254::Bn254;
+use ark_groth16::{Proof, ProvingKey, VerifyingKey};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+use crate::fingerprint_proof::proof_error::ProofError;
+
+/// A proof that a published fingerprint hash is the keccak256 hash of a fingerprint its schema
+/// allows, as shared with verifiers.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct FingerprintProof {
+    /// The fingerprint hash, `0x`-prefixed hex as published.
+    pub hash: String,
+    /// The Groth16 proof over BN254, compressed and hex-encoded.
+    pub proof: String,
+}
+
+impl FingerprintProof {
+    pub fn new(hash: &[u8; 32], proof: &Proof<Bn254>) -> Result<Self, ProofError> {
+        let mut bytes = Vec::new();
+        proof.serialize_compressed(&mut bytes).map_err(|err| ProofError::Encoding(err.to_string()))?;
+        Ok(FingerprintProof { hash: format!("0x{}", hex::encode(hash)), proof: format!("0x{}", hex::encode(bytes)) })
+    }
+
+    pub fn hash_bytes(&self) -> Result<[u8; 32], ProofError> {
+        parse_hash(&self.hash)
+    }
+
+    pub fn groth16(&self) -> Result<Proof<Bn254>, ProofError> {
+        let bytes = hex::decode(self.proof.trim_start_matches("0x")).map_err(|err| ProofError::Encoding(format!("proof: {}", err)))?;
+        Proof::deserialize_compressed(bytes.as_slice()).map_err(|err| ProofError::Encoding(format!("proof: {}", err)))
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ProofError> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path).map_err(|err| ProofError::Encoding(format!("cannot read {}: {}", path.display(), err)))?;
+        serde_json::from_str(&text).map_err(|err| ProofError::Encoding(format!("invalid {}: {}", path.display(), err)))
+    }
+}
+
+/// Parses a fingerprint hash, `0x`-prefixed or not.
+pub fn parse_hash(hash: &str) -> Result<[u8; 32], ProofError> {
+    let bytes = hex::decode(hash.trim_start_matches("0x")).map_err(|err| ProofError::Encoding(format!("hash {}: {}", hash, err)))?;
+    bytes.try_into().map_err(|_| ProofError::Encoding(format!("hash {} is not 32 bytes", hash)))
+}
+
+/// Writes a proving key. It is large and only read back by its owner, so it is not compressed.
+pub fn write_proving_key(path: impl AsRef<Path>, key: &ProvingKey<Bn254>) -> Result<(), ProofError> {
+    let mut bytes = Vec::new();
+    key.serialize_uncompressed(&mut bytes).map_err(|err| ProofError::Encoding(err.to_string()))?;
+    write(path.as_ref(), &bytes)
+}
+
+/// Reads a proving key written by `write_proving_key`, without checking its points.
+///
+/// The points are not checked to be on the curve or in the right subgroup, which is what makes
+/// loading a large key fast; a crafted key can make proving misbehave. Only load keys generated
+/// locally by `create_proof setup`, or received from a party trusted with the setup.
+pub fn read_proving_key(path: impl AsRef<Path>) -> Result<ProvingKey<Bn254>, ProofError> {
+    let bytes = read(path.as_ref())?;
+    ProvingKey::deserialize_uncompressed_unchecked(bytes.as_slice()).map_err(|err| ProofError::Encoding(format!("proving key: {}", err)))
+}
+
+/// Writes a verifying key, compressed.
+pub fn write_verifying_key(path: impl AsRef<Path>, key: &VerifyingKey<Bn254>) -> Result<(), ProofError> {
+    let mut bytes = Vec::new();
+    key.serialize_compressed(&mut bytes).map_err(|err| ProofError::Encoding(err.to_string()))?;
+    write(path.as_ref(), &bytes)
+}
+
+/// Reads a verifying key, checking every point.
+pub fn read_verifying_key(path: impl AsRef<Path>) -> Result<VerifyingKey<Bn254>, ProofError> {
+    let bytes = read(path.as_ref())?;
+    VerifyingKey::deserialize_compressed(bytes.as_slice()).map_err(|err| ProofError::Encoding(format!("verifying key: {}", err)))
+}
+
+fn write(path: &Path, bytes: &[u8]) -> Result<(), ProofError> {
+    fs::write(path, bytes).map_err(|err| ProofError::Encoding(format!("cannot write {}: {}", path.display(), err)))
+}
+
+fn read(path: &Path) -> Result<Vec<u8>, ProofError> {
+    fs::read(path).map_err(|err| ProofError::Encoding(format!("cannot read {}: {}", path.display(), err)))
+}
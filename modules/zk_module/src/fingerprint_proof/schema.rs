@@ -0,0 +1,73 @@
+use ai_module::inference::observation::{ObservationField, OutputSchema};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+
+use crate::fingerprint_proof::proof_error::ProofError;
+
+/// The values a fingerprint may hold: the values of each observation field, as in a model
+/// registry entry or a game plugin's schema, and the strikes.
+///
+/// The schema is built into the circuit, so keys generated for one schema only prove and check
+/// fingerprints of that schema.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct FingerprintSchema {
+    /// The values of `character`, `ability`, `place` and `place2`; every field needs at least one.
+    pub fields: OutputSchema,
+    /// The strikes a fingerprint may count; the coordinator always publishes 0.
+    #[serde(default = "no_strikes")]
+    pub strikes: Vec<u64>,
+}
+
+fn no_strikes() -> Vec<u64> {
+    vec![0]
+}
+
+impl FingerprintSchema {
+    /// A schema allowing `fields`, and no strikes.
+    pub fn new(fields: OutputSchema) -> Self {
+        FingerprintSchema { fields, strikes: no_strikes() }
+    }
+
+    /// Reads a schema file.
+    ///
+    /// # Parameters
+    /// - `path`: The JSON file, `{"fields": {"character": [...], ...}, "strikes": [0]}`.
+    ///
+    /// # Returns
+    /// - `Result<FingerprintSchema, ProofError>`: The schema, or why it cannot be read.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ProofError> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path).map_err(|err| ProofError::Schema(format!("cannot read {}: {}", path.display(), err)))?;
+        serde_json::from_str(&text).map_err(|err| ProofError::Schema(format!("invalid {}: {}", path.display(), err)))
+    }
+
+    /// The values `field` may hold, each once and sorted, so the circuit does not depend on the
+    /// order of the schema.
+    pub fn values(&self, field: ObservationField) -> Result<Vec<String>, ProofError> {
+        let values: Vec<String> = self.fields.get(&field).into_iter().flatten().cloned().collect::<BTreeSet<_>>().into_iter().collect();
+        if values.is_empty() {
+            return Err(ProofError::Schema(format!("`{}` has no values, a proof needs every value a field may hold", field)));
+        }
+        Ok(values)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ai_module::plugins::arena::ArenaPlugin;
+    use ai_module::plugins::game_plugin::GamePlugin;
+
+    #[test]
+    fn test_the_arena_schema_file_matches_the_arena_plugin() {
+        let schema = FingerprintSchema::load(concat!(env!("CARGO_MANIFEST_DIR"), "/schemas/arena.json")).unwrap();
+        assert_eq!(schema, FingerprintSchema::new(ArenaPlugin::new().schema()));
+        let shuffled = FingerprintSchema::new(OutputSchema::from([(ObservationField::Place2, vec!["South".to_string(), "North".to_string(), "South".to_string()])]));
+        assert_eq!(shuffled.values(ObservationField::Place2).unwrap(), schema.values(ObservationField::Place2).unwrap());
+
+        let partial = FingerprintSchema::new(OutputSchema::from([(ObservationField::Character, vec!["Mage".to_string()])]));
+        assert!(matches!(partial.values(ObservationField::Place), Err(ProofError::Schema(_))));
+    }
+}
@@ -0,0 +1 @@
+pub mod fingerprint_proof;
@@ -0,0 +1,53 @@
+use ark_bn254::Bn254;
+use ark_groth16::{Groth16, VerifyingKey};
+use ark_snark::SNARK;
+use zk_module::fingerprint_proof::circuit::public_inputs;
+use zk_module::fingerprint_proof::proof_error::ProofError;
+use zk_module::fingerprint_proof::proof_file::FingerprintProof;
+
+/// Checks that a proof shows its hash is the keccak256 hash of a fingerprint the schema allows.
+///
+/// Only the hash is public and the proof adds nothing to it. The hash is not salted, though, so
+/// a schema with few values does not hide the fingerprint: anyone can hash every fingerprint the
+/// schema allows until one matches.
+///
+/// # Parameters
+/// - `key`: The verifying key of the schema's circuit.
+/// - `proof`: The proof, with the hash it is about.
+///
+/// # Returns
+/// - `Result<bool, ProofError>`: Whether the proof holds, or an error if it cannot be decoded.
+pub fn verify_proof(key: &VerifyingKey<Bn254>, proof: &FingerprintProof) -> Result<bool, ProofError> {
+    let hash = proof.hash_bytes()?;
+    Ok(Groth16::<Bn254>::verify(key, &public_inputs(&hash), &proof.groth16()?)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ai_module::plugins::arena::ArenaPlugin;
+    use ai_module::plugins::game_plugin::GamePlugin;
+    use create_proof::{create_proof, setup};
+    use fingerprint::Fingerprint;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use zk_module::fingerprint_proof::layout::FingerprintLayout;
+    use zk_module::fingerprint_proof::schema::FingerprintSchema;
+
+    #[test]
+    fn test_accepts_the_proof_of_the_published_hash_only() {
+        let layout = FingerprintLayout::new(&FingerprintSchema::new(ArenaPlugin::new().schema())).unwrap();
+        let mut rng = StdRng::seed_from_u64(7);
+        let (proving_key, verifying_key) = setup(&layout, &mut rng).unwrap();
+        let fingerprint = Fingerprint { gamer: "Knight".to_string(), strikes: 0, place: "Keep".to_string(), weapon: "Shield Bash".to_string(), place2: "South".to_string() };
+
+        let proof = create_proof(&proving_key, &layout, &fingerprint, &mut rng).unwrap();
+        assert!(verify_proof(&verifying_key, &proof).unwrap());
+
+        // The same proof says nothing about another hash
+        let other = Fingerprint { place2: "North".to_string(), ..fingerprint };
+        let claimed = FingerprintProof { hash: fingerprint::create::create_hash::create_fingerprint_hash(&other).unwrap(), ..proof.clone() };
+        assert!(!verify_proof(&verifying_key, &claimed).unwrap());
+        assert!(verify_proof(&verifying_key, &FingerprintProof { proof: "0x00".to_string(), ..proof }).is_err());
+    }
+}
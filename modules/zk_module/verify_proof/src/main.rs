@@ -1,3 +1,44 @@
+use clap::Parser;
+use std::error::Error;
+use std::path::PathBuf;
+use std::process;
+use verify_proof::verify_proof;
+use zk_module::fingerprint_proof::proof_file::{parse_hash, read_verifying_key, FingerprintProof};
+
+/// Checks that a published fingerprint hash is the keccak256 hash of a fingerprint its schema
+/// allows.
+#[derive(Parser, Debug)]
+#[command(name = "verify_proof", version)]
+struct Cli {
+    /// The proof, as written by `create_proof prove`.
+    proof: PathBuf,
+
+    /// The verifying key of the schema.
+    #[arg(long, env = "ZK_VERIFYING_KEY")]
+    verifying_key: PathBuf,
+
+    /// The hash as published, which the proof must be about.
+    #[arg(long)]
+    hash: Option<String>,
+}
+
 fn main() {
-    println!("Hello, world!");
+    if let Err(err) = run(Cli::parse()) {
+        eprintln!("error: {}", err);
+        process::exit(1);
+    }
+}
+
+fn run(cli: Cli) -> Result<(), Box<dyn Error>> {
+    let proof = FingerprintProof::load(&cli.proof)?;
+    if let Some(hash) = &cli.hash {
+        if parse_hash(hash)? != proof.hash_bytes()? {
+            return Err(format!("the proof is about {}, not {}", proof.hash, hash).into());
+        }
+    }
+    if !verify_proof(&read_verifying_key(&cli.verifying_key)?, &proof)? {
+        return Err(format!("the proof of {} does not hold", proof.hash).into());
+    }
+    println!("valid proof of {}", proof.hash);
+    Ok(())
 }